@@ -0,0 +1,243 @@
+use crate::{AuditDetails, AuditEntry, AuditOperation, AuditPayload, OperationResult};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// `previous_hash` of the first entry in the log.
+pub const GENESIS_HASH: [u8; 32] = [0; 32];
+
+// Upper bound on entries checked by one `verify_audit_chain` call.
+pub const MAX_VERIFY_RANGE: u64 = 5_000;
+
+// Shape of entries in the pre-chain `AUDIT_TRAIL` map, kept so they can be
+// decoded once and appended to the log on upgrade.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct LegacyAuditEntry {
+    pub id: String,
+    pub timestamp: u64,
+    pub principal: Principal,
+    pub operation: AuditOperation,
+    pub resource_id: String,
+    pub resource_type: String,
+    pub details: LegacyAuditDetails,
+    pub result: OperationResult,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct LegacyAuditDetails {
+    pub operation_specific_data: String,
+    pub sensitive_data_redacted: bool,
+    pub related_entities: Vec<String>,
+    pub compliance_notes: Option<String>,
+}
+
+// Key of the resource and principal indexes: every entry filed under `owner`
+// (a resource ID or principal text), ordered by sequence.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AuditIndexKey {
+    pub owner: String,
+    pub sequence: u64,
+}
+
+impl AuditIndexKey {
+    pub fn new(owner: &str, sequence: u64) -> Self {
+        Self {
+            owner: owner.to_string(),
+            sequence,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AuditChainVerification {
+    pub from: u64,
+    pub to: u64, // Inclusive; clamped to the end of the log
+    pub valid: bool,
+    pub first_invalid: Option<u64>, // First sequence whose link does not match
+    pub last_hash: Vec<u8>,         // Hash of entry `to`, if the range is valid
+}
+
+// Prefixed to the hashed encoding; bump it with any change to how entries
+// are serialised.
+const AUDIT_HASH_VERSION: &[u8] = b"audit_entry_v1";
+
+// SHA-256 over a versioned JSON encoding of the entry. Unlike Candid, which
+// embeds the full type table, JSON encodes an enum value by its own variant
+// alone, so adding operations or payloads does not change the hash of
+// existing entries. Since every entry embeds the hash of its predecessor,
+// changing or removing any entry breaks every link after it.
+pub fn audit_entry_hash(entry: &AuditEntry) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(AUDIT_HASH_VERSION);
+    hasher.update(serde_json::to_vec(entry).expect("Audit entries serialise to JSON"));
+    hasher.finalize().to_vec()
+}
+
+// Places `entry` after `previous` (None for the first entry), assigning its
+// sequence number, ID and link.
+pub fn link_entry(previous: Option<&AuditEntry>, mut entry: AuditEntry) -> AuditEntry {
+    let (sequence, previous_hash) = match previous {
+        Some(previous) => (previous.sequence + 1, audit_entry_hash(previous)),
+        None => (0, GENESIS_HASH.to_vec()),
+    };
+    entry.id = format!("audit_{:016}", sequence);
+    entry.sequence = sequence;
+    entry.previous_hash = previous_hash;
+    entry
+}
+
+// Checks entries `from..=to` of a log of `len` entries, read through `get`.
+pub fn verify_chain(
+    from: u64,
+    to: u64,
+    len: u64,
+    get: impl Fn(u64) -> Option<AuditEntry>,
+) -> Result<AuditChainVerification, String> {
+    if len == 0 || from >= len {
+        return Err(format!("Audit log has {} entries", len));
+    }
+    let to = to.min(len - 1);
+    if to < from {
+        return Err("Range end precedes its start".to_string());
+    }
+    if to - from >= MAX_VERIFY_RANGE {
+        return Err(format!(
+            "At most {} entries can be verified per call",
+            MAX_VERIFY_RANGE
+        ));
+    }
+
+    let mut expected_hash = match from {
+        0 => GENESIS_HASH.to_vec(),
+        _ => get(from - 1)
+            .map(|entry| audit_entry_hash(&entry))
+            .unwrap_or_default(),
+    };
+    let mut first_invalid = None;
+
+    for sequence in from..=to {
+        match get(sequence) {
+            Some(entry) if entry.sequence == sequence && entry.previous_hash == expected_hash => {
+                expected_hash = audit_entry_hash(&entry);
+            }
+            _ => {
+                first_invalid = Some(sequence);
+                break;
+            }
+        }
+    }
+
+    Ok(AuditChainVerification {
+        from,
+        to,
+        valid: first_invalid.is_none(),
+        first_invalid,
+        last_hash: expected_hash,
+    })
+}
+
+impl From<LegacyAuditEntry> for AuditEntry {
+    // Sequence, ID and link are assigned by `link_entry` when appended.
+    fn from(legacy: LegacyAuditEntry) -> Self {
+        AuditEntry {
+            id: legacy.id,
+            sequence: 0,
+            timestamp: legacy.timestamp,
+            principal: legacy.principal,
+            operation: legacy.operation,
+            resource_id: legacy.resource_id,
+            resource_type: legacy.resource_type,
+            details: AuditDetails {
+                // Often not valid JSON, so kept verbatim rather than parsed
+                payload: AuditPayload::Legacy {
+                    raw: legacy.details.operation_specific_data,
+                },
+                sensitive_data_redacted: legacy.details.sensitive_data_redacted,
+                related_entities: legacy.details.related_entities,
+                compliance_notes: legacy.details.compliance_notes,
+            },
+            result: legacy.result,
+            previous_hash: Vec::new(),
+        }
+    }
+}
+
+// Latest state of the audit log together with the IC certificate over it.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AuditCheckpoint {
+    pub length: u64,
+    pub head_hash: Vec<u8>, // Hash of entry `length - 1`, or GENESIS_HASH
+    pub certificate: Option<Vec<u8>>, // CBOR certificate; None outside query calls
+}
+
+// The 32 bytes published through `set_certified_data`:
+// SHA-256(length as big-endian u64 || head hash).
+pub fn checkpoint_digest(length: u64, head_hash: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(length.to_be_bytes());
+    hasher.update(head_hash);
+    hasher.finalize().into()
+}
+
+// Off-chain check of downloaded entries against a checkpoint. The caller
+// must first validate `checkpoint.certificate` against the IC root key and
+// read the canister's `certified_data` from it; this then confirms that
+// `entries` (a contiguous range ending at the checkpoint's head) hash-link
+// into exactly the certified history.
+pub fn verify_entries_against_checkpoint(
+    entries: &[AuditEntry],
+    checkpoint: &AuditCheckpoint,
+    certified_data: &[u8],
+) -> Result<(), String> {
+    if checkpoint_digest(checkpoint.length, &checkpoint.head_hash) != certified_data {
+        return Err("Checkpoint does not match the certified data".to_string());
+    }
+
+    let Some(last) = entries.last() else {
+        return match checkpoint.length {
+            0 => Ok(()),
+            _ => Err("No entries to verify".to_string()),
+        };
+    };
+    if last.sequence + 1 != checkpoint.length {
+        return Err("Entries do not end at the checkpoint head".to_string());
+    }
+
+    for pair in entries.windows(2) {
+        if pair[1].sequence != pair[0].sequence + 1
+            || pair[1].previous_hash != audit_entry_hash(&pair[0])
+        {
+            return Err(format!("Broken link at sequence {}", pair[1].sequence));
+        }
+    }
+    if entries[0].sequence == 0 && entries[0].previous_hash != GENESIS_HASH {
+        return Err("First entry does not start from the genesis hash".to_string());
+    }
+
+    if audit_entry_hash(last) != checkpoint.head_hash {
+        return Err("Head hash does not match the last entry".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap};
+
+    #[test]
+    fn index_ranges_stay_within_one_owner() {
+        let mut index: StableBTreeMap<AuditIndexKey, (), _> =
+            StableBTreeMap::init(DefaultMemoryImpl::default());
+        for (owner, sequence) in [("id", 3), ("id:0", 1), ("id", 7), ("ids", 2), ("i", 9)] {
+            index.insert(AuditIndexKey::new(owner, sequence), ());
+        }
+
+        let sequences: Vec<u64> = index
+            .keys_range(AuditIndexKey::new("id", 0)..=AuditIndexKey::new("id", u64::MAX))
+            .rev()
+            .map(|key| key.sequence)
+            .collect();
+        assert_eq!(sequences, vec![7, 3]);
+    }
+}
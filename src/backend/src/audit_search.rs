@@ -0,0 +1,304 @@
+use crate::{AuditEntry, AuditOperation, AuditPayload, OperationResult};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_AUDIT_PAGE_SIZE: u32 = 50;
+pub const MAX_AUDIT_PAGE_SIZE: u32 = 100;
+pub const DEFAULT_AUDIT_EXPORT_CHUNK: u32 = 200;
+pub const MAX_AUDIT_EXPORT_CHUNK: u32 = 1_000;
+
+// Log entries examined per call, whether or not they match the filter.
+pub const MAX_AUDIT_SCAN: u64 = 10_000;
+
+const REDACTED: &str = "[REDACTED]";
+
+impl AuditPayload {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    // Same payload with wallet addresses passed through `mask_wallet_addresses`.
+    fn masked(self) -> Self {
+        match self {
+            AuditPayload::WalletLinked {
+                chain_type,
+                address,
+                signature_verified,
+            } => AuditPayload::WalletLinked {
+                chain_type,
+                address: mask_wallet_addresses(&address),
+                signature_verified,
+            },
+            AuditPayload::WalletLinkRejected {
+                chain_type,
+                address,
+                reason,
+            } => AuditPayload::WalletLinkRejected {
+                chain_type,
+                address: mask_wallet_addresses(&address),
+                reason,
+            },
+            AuditPayload::SanctionsAddressHit {
+                chain_type,
+                address,
+                list,
+            } => AuditPayload::SanctionsAddressHit {
+                chain_type,
+                address: mask_wallet_addresses(&address),
+                list,
+            },
+            AuditPayload::Legacy { raw } => AuditPayload::Legacy {
+                raw: mask_wallet_addresses(&raw),
+            },
+            payload => payload,
+        }
+    }
+}
+
+// Access to the whole audit log. Compliance officers (and admins) see entries
+// as recorded; auditors get wallet addresses masked and the details of
+// entries flagged `sensitive_data_redacted` withheld.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AuditRole {
+    ComplianceOfficer,
+    Auditor,
+}
+
+impl AuditRole {
+    pub fn is_privileged(&self) -> bool {
+        matches!(self, AuditRole::ComplianceOfficer)
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AuditSearchFilter {
+    pub operation: Option<AuditOperation>,
+    pub principal: Option<Principal>,
+    pub resource_type: Option<String>,
+    pub result: Option<OperationResult>, // Matched by variant, ignoring payloads
+    pub recorded_after: Option<u64>,
+    pub recorded_before: Option<u64>,
+}
+
+impl AuditSearchFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.operation
+            .as_ref()
+            .is_none_or(|operation| *operation == entry.operation)
+            && self
+                .principal
+                .is_none_or(|principal| principal == entry.principal)
+            && self
+                .resource_type
+                .as_ref()
+                .is_none_or(|resource_type| *resource_type == entry.resource_type)
+            && self.result.as_ref().is_none_or(|result| {
+                std::mem::discriminant(result) == std::mem::discriminant(&entry.result)
+            })
+            && self
+                .recorded_after
+                .is_none_or(|after| entry.timestamp >= after)
+            && self
+                .recorded_before
+                .is_none_or(|before| entry.timestamp < before)
+    }
+}
+
+// Newest first. `next_cursor` is the sequence number to pass back for the
+// following page; it may be set even when `entries` is short, if the scan
+// budget ran out before the start of the log.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AuditSearchPage {
+    pub entries: Vec<AuditEntry>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum AuditExportFormat {
+    Csv,
+    JsonLines,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AuditExportChunk {
+    pub data: String, // CSV chunks after the first omit the header row
+    pub entry_count: u32,
+    pub next_cursor: Option<u64>,
+}
+
+// Flat view of an entry shared by both export formats.
+#[derive(Serialize)]
+struct AuditExportRow {
+    sequence: u64,
+    id: String,
+    timestamp: u64,
+    principal: String,
+    operation: String,
+    resource_id: String,
+    resource_type: String,
+    result: String,
+    details: String,
+    sensitive_data_redacted: bool,
+    related_entities: String,
+    compliance_notes: String,
+    previous_hash: String,
+}
+
+const CSV_HEADER: &str = "sequence,id,timestamp,principal,operation,resource_id,resource_type,result,details,sensitive_data_redacted,related_entities,compliance_notes,previous_hash";
+
+impl From<&AuditEntry> for AuditExportRow {
+    fn from(entry: &AuditEntry) -> Self {
+        AuditExportRow {
+            sequence: entry.sequence,
+            id: entry.id.clone(),
+            timestamp: entry.timestamp,
+            principal: entry.principal.to_text(),
+            operation: format!("{:?}", entry.operation),
+            resource_id: entry.resource_id.clone(),
+            resource_type: entry.resource_type.clone(),
+            result: format!("{:?}", entry.result),
+            details: entry.details.payload.to_json(),
+            sensitive_data_redacted: entry.details.sensitive_data_redacted,
+            related_entities: entry.details.related_entities.join(";"),
+            compliance_notes: entry.details.compliance_notes.clone().unwrap_or_default(),
+            previous_hash: hex::encode(&entry.previous_hash),
+        }
+    }
+}
+
+impl AuditExportRow {
+    fn to_csv(&self) -> String {
+        [
+            self.sequence.to_string(),
+            csv_field(&self.id),
+            self.timestamp.to_string(),
+            csv_field(&self.principal),
+            csv_field(&self.operation),
+            csv_field(&self.resource_id),
+            csv_field(&self.resource_type),
+            csv_field(&self.result),
+            csv_field(&self.details),
+            self.sensitive_data_redacted.to_string(),
+            csv_field(&self.related_entities),
+            csv_field(&self.compliance_notes),
+            self.previous_hash.clone(),
+        ]
+        .join(",")
+    }
+}
+
+// RFC 4180 quoting; also neutralises leading formula characters so exports
+// are safe to open in spreadsheets.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+pub fn render_export(
+    entries: &[AuditEntry],
+    format: AuditExportFormat,
+    with_header: bool,
+) -> String {
+    let mut out = String::new();
+    match format {
+        AuditExportFormat::Csv => {
+            if with_header {
+                out.push_str(CSV_HEADER);
+                out.push('\n');
+            }
+            for entry in entries {
+                out.push_str(&AuditExportRow::from(entry).to_csv());
+                out.push('\n');
+            }
+        }
+        AuditExportFormat::JsonLines => {
+            for entry in entries {
+                out.push_str(&serde_json::to_string(&AuditExportRow::from(entry)).unwrap());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+// The entry as `role` may see it.
+pub fn redact_for_role(mut entry: AuditEntry, role: AuditRole) -> AuditEntry {
+    if role.is_privileged() {
+        return entry;
+    }
+    let details = &mut entry.details;
+    if details.sensitive_data_redacted {
+        details.payload = AuditPayload::Redacted;
+        details.compliance_notes = details
+            .compliance_notes
+            .as_ref()
+            .map(|_| REDACTED.to_string());
+    } else {
+        let payload = std::mem::replace(&mut details.payload, AuditPayload::Redacted);
+        details.payload = payload.masked();
+        details.compliance_notes = details
+            .compliance_notes
+            .as_deref()
+            .map(mask_wallet_addresses);
+    }
+    details.related_entities = details
+        .related_entities
+        .iter()
+        .map(|entity| mask_wallet_addresses(entity))
+        .collect();
+    entry
+}
+
+// Masks every token that looks like a Bitcoin, EVM or Solana address down to
+// its first six and last four characters.
+pub fn mask_wallet_addresses(text: &str) -> String {
+    fn flush(out: &mut String, token: &mut String) {
+        if looks_like_wallet_address(token) {
+            out.push_str(&token[..6]);
+            out.push('…');
+            out.push_str(&token[token.len() - 4..]);
+        } else {
+            out.push_str(token);
+        }
+        token.clear();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut token = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            token.push(c);
+        } else {
+            flush(&mut out, &mut token);
+            out.push(c);
+        }
+    }
+    flush(&mut out, &mut token);
+    out
+}
+
+fn looks_like_wallet_address(token: &str) -> bool {
+    let is_base58 = |s: &str| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+    };
+
+    if let Some(hex) = token.strip_prefix("0x") {
+        return hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    if token.starts_with("bc1") || token.starts_with("tb1") {
+        return (14..=74).contains(&token.len())
+            && token
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    }
+    (26..=44).contains(&token.len()) && is_base58(token)
+}
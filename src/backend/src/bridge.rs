@@ -1,16 +1,22 @@
-use crate::ChainType;
-use candid::{CandidType, Principal};
+use crate::release_tx::{ReleaseKind, SignedRelease};
+use crate::{rpc_flavor, ChainType, KYCLevel, Memory, ReleaseCheck, RpcFlavor};
+use candid::{CandidType, Decode, Encode, Principal};
 use ic_cdk::api::time;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::{StableBTreeMap, StableCell, Storable};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Bound::{Excluded, Unbounded};
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct BridgeRequest {
     pub request_id: String,
+    pub identity_id: String,
     pub from_chain: ChainType,
     pub to_chain: ChainType,
     pub asset_type: String,
-    pub amount: u64,
+    pub amount: u128, // Source-chain smallest units
     pub from_address: String,
     pub to_address: String,
     pub user_principal: Principal,
@@ -18,6 +24,94 @@ pub struct BridgeRequest {
     pub created_at: u64,
     pub completed_at: Option<u64>,
     pub transaction_hashes: Vec<String>,
+    pub status_history: Vec<BridgeStatusTransition>,
+    pub source_tx_hash: Option<String>, // Deposit transaction submitted by the user
+    pub release_transaction: Option<SignedRelease>, // Signed destination-chain release
+    pub quote_id: String,
+    pub fee: u128,            // Source-chain units, as quoted
+    pub receive_amount: u128, // Destination-chain units, as quoted
+    pub release_broadcast: Option<BroadcastState>,
+    pub refund_transaction: Option<SignedRelease>, // Signed source-chain refund
+    pub refund_broadcast: Option<BroadcastState>,
+    pub refund_claimed_at: Option<u64>, // Set when the owner asks for the refund
+}
+
+impl BridgeRequest {
+    // What queries return. Signed payouts are only ever broadcast by the
+    // canister, so that a release handed over to the refund flow cannot be
+    // put on chain by anyone else.
+    pub fn public_view(&self) -> BridgeRequest {
+        let withhold = |payout: &Option<SignedRelease>| {
+            payout.clone().map(|payout| SignedRelease {
+                raw_transaction: Vec::new(),
+                ..payout
+            })
+        };
+        BridgeRequest {
+            release_transaction: withhold(&self.release_transaction),
+            refund_transaction: withhold(&self.refund_transaction),
+            ..self.clone()
+        }
+    }
+}
+
+// Progress of submitting a signed payout to its chain.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BroadcastState {
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+    pub broadcast_at: Option<u64>,
+}
+
+impl BroadcastState {
+    fn new(now: u64) -> Self {
+        Self {
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            broadcast_at: None,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PayoutKind {
+    Release,
+    Refund,
+}
+
+// How long a request may sit in each in-flight state before it is moved to
+// RefundPending.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RouteTimeouts {
+    pub source_locked_ns: u64,
+    pub target_minting_ns: u64,
+}
+
+const DEFAULT_ROUTE_TIMEOUTS: RouteTimeouts = RouteTimeouts {
+    source_locked_ns: 6 * 3600 * 1_000_000_000,
+    target_minting_ns: 12 * 3600 * 1_000_000_000,
+};
+
+const BROADCAST_RETRY_BASE_NS: u64 = 60 * 1_000_000_000;
+const BROADCAST_RETRY_MAX_NS: u64 = 6 * 3600 * 1_000_000_000;
+const MAX_RELEASE_BROADCAST_ATTEMPTS: u32 = 5;
+
+// A stalled release is looked up on its chain at this interval until it is
+// either included or can no longer confirm.
+const RELEASE_CHECK_INTERVAL_NS: u64 = 10 * 60 * 1_000_000_000;
+
+// A submitted deposit the source chain does not know is looked up again with
+// exponential backoff, and the request fails once the lookups are exhausted.
+const DEPOSIT_LOOKUP_RETRY_BASE_NS: u64 = 60 * 1_000_000_000;
+const DEPOSIT_LOOKUP_RETRY_MAX_NS: u64 = 3600 * 1_000_000_000;
+const MAX_DEPOSIT_LOOKUP_MISSES: u32 = 12;
+
+// Failed lookups of a request's deposit transaction.
+struct DepositLookup {
+    misses: u32,
+    next_check_at: u64,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -28,6 +122,53 @@ pub enum BridgeStatus {
     Completed,
     Failed { reason: String },
     Cancelled,
+    RefundPending { reason: String },
+    Refunded,
+}
+
+impl BridgeStatus {
+    // Allowed edges of the bridge state machine. Completed, Failed,
+    // Cancelled and Refunded are terminal.
+    pub fn can_transition_to(&self, next: &BridgeStatus) -> bool {
+        matches!(
+            (self, next),
+            (BridgeStatus::Initiated, BridgeStatus::SourceLocked)
+                | (BridgeStatus::Initiated, BridgeStatus::Failed { .. })
+                | (BridgeStatus::Initiated, BridgeStatus::Cancelled)
+                | (BridgeStatus::SourceLocked, BridgeStatus::TargetMinting)
+                | (BridgeStatus::SourceLocked, BridgeStatus::Failed { .. })
+                | (BridgeStatus::TargetMinting, BridgeStatus::Completed)
+                | (BridgeStatus::TargetMinting, BridgeStatus::Failed { .. })
+                | (
+                    BridgeStatus::SourceLocked,
+                    BridgeStatus::RefundPending { .. }
+                )
+                | (
+                    BridgeStatus::TargetMinting,
+                    BridgeStatus::RefundPending { .. }
+                )
+                | (BridgeStatus::RefundPending { .. }, BridgeStatus::Refunded)
+        )
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BridgeStatus::Completed
+                | BridgeStatus::Failed { .. }
+                | BridgeStatus::Cancelled
+                | BridgeStatus::Refunded
+        )
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BridgeStatusTransition {
+    pub from: BridgeStatus,
+    pub to: BridgeStatus,
+    pub changed_by: Principal,
+    pub changed_at: u64,
+    pub transaction_hash: Option<String>,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -35,11 +176,33 @@ pub struct ChainConfig {
     pub chain_type: ChainType,
     pub rpc_url: String,
     pub bridge_contract: String,
-    pub supported_assets: Vec<String>,
-    pub min_amount: u64,
-    pub max_amount: u64,
-    pub fee_percentage: f64,
+    pub supported_assets: Vec<AssetConfig>,
+    pub fee_bps: u16,
     pub confirmation_blocks: u32,
+    pub evm_chain_id: Option<u64>, // EIP-155 chain id, required for EVM chains
+    pub enabled: bool,
+}
+
+impl ChainConfig {
+    pub fn asset(&self, symbol: &str) -> Option<&AssetConfig> {
+        self.supported_assets.iter().find(|a| a.symbol == symbol)
+    }
+}
+
+// Registry entry for an asset on one chain. Amounts and fees are expressed
+// in the asset's smallest unit on that chain (satoshis, wei, ...).
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AssetConfig {
+    pub symbol: String,
+    pub decimals: u8,
+    pub min_amount: u128,
+    pub max_amount: u128,
+    pub fixed_fee: u128,           // Charged when bridging out of this chain
+    pub destination_gas_fee: u128, // Estimated release cost on this chain
+    pub user_daily_limit: u128,    // Rolling 24h inflow per user
+    pub daily_limit: u128,         // Rolling 24h inflow across all users
+    pub outflow_breaker_threshold: u128, // Rolling 24h releases per route before it pauses
+    pub token_contract: Option<String>, // None for the chain's native asset
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -50,237 +213,2438 @@ pub struct BridgeTransactionHistory {
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct BridgeFee {
-    pub amount: u64,
-    pub percentage: f64,
-    pub fixed_fee: u64,
+    pub amount: u128,
+    pub fee_bps: u16,
+    pub fixed_fee: u128,
 }
 
-pub struct BridgeService {
-    pub requests: HashMap<String, BridgeRequest>,
-    pub chain_configs: HashMap<String, ChainConfig>,
-    pub user_history: HashMap<Principal, Vec<String>>, // Principal -> Vec<request_id>
+// A quote is binding for the principal it was issued to until `expires_at`.
+// `quote_id` authenticates the terms; see `initiate_cross_chain_bridge`.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BridgeQuote {
+    pub quote_id: String,
+    pub user_principal: Principal,
+    pub from_chain: ChainType,
+    pub to_chain: ChainType,
+    pub asset_type: String,
+    pub amount: u128,
+    pub fee: BridgeFee,
+    pub destination_gas_fee: u128,
+    pub receive_amount: u128,
+    pub source_decimals: u8,
+    pub destination_decimals: u8,
+    pub expires_at: u64,
+}
+
+pub const QUOTE_TTL_NS: u64 = 5 * 60 * 1_000_000_000; // 5 minutes
+
+const DAY_NS: u64 = 24 * 3600 * 1_000_000_000;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BridgeRoute {
+    pub from_chain: ChainType,
+    pub to_chain: ChainType,
+}
+
+impl BridgeRoute {
+    pub fn of(request: &BridgeRequest) -> Self {
+        Self {
+            from_chain: request.from_chain.clone(),
+            to_chain: request.to_chain.clone(),
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct CircuitBreakerTrip {
+    pub reason: String,
+    pub tripped_by: Principal,
+    pub tripped_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BridgeLimits {
+    pub max_daily_requests: u64, // Across all routes and users
+}
+
+impl Default for BridgeLimits {
+    fn default() -> Self {
+        Self {
+            max_daily_requests: 10_000,
+        }
+    }
 }
 
-impl Default for BridgeService {
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AssetVolume {
+    pub asset_type: String,
+    pub amount: u128,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RouteStatus {
+    pub route: BridgeRoute,
+    pub trip: Option<CircuitBreakerTrip>,
+    pub inflow_24h: Vec<AssetVolume>,  // Source-chain units
+    pub outflow_24h: Vec<AssetVolume>, // Destination-chain units
+}
+
+// All fields are optional; set fields must all match.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BridgeHistoryFilter {
+    pub status: Option<BridgeStatus>, // Matched by variant, ignoring payloads
+    pub from_chain: Option<ChainType>,
+    pub to_chain: Option<ChainType>,
+    pub asset_type: Option<String>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub user_principal: Option<Principal>, // Operator queries only
+}
+
+impl BridgeHistoryFilter {
+    fn matches(&self, request: &BridgeRequest) -> bool {
+        self.status.as_ref().is_none_or(|status| {
+            std::mem::discriminant(status) == std::mem::discriminant(&request.status)
+        }) && self
+            .from_chain
+            .as_ref()
+            .is_none_or(|chain| *chain == request.from_chain)
+            && self
+                .to_chain
+                .as_ref()
+                .is_none_or(|chain| *chain == request.to_chain)
+            && self
+                .asset_type
+                .as_ref()
+                .is_none_or(|asset| *asset == request.asset_type)
+            && self
+                .created_after
+                .is_none_or(|after| request.created_at >= after)
+            && self
+                .created_before
+                .is_none_or(|before| request.created_at < before)
+            && self
+                .user_principal
+                .is_none_or(|user| user == request.user_principal)
+    }
+}
+
+// Newest first. `next_cursor` is the ID to pass back for the following page.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BridgeRequestPage {
+    pub requests: Vec<BridgeRequest>,
+    pub next_cursor: Option<String>,
+}
+
+const UNKNOWN_CURSOR: &str = "Unknown cursor";
+
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RouteStats {
+    pub route: BridgeRoute,
+    pub request_count: u64,
+    pub completed_count: u64,
+    pub failed_count: u64, // Failed or refunded
+    pub cancelled_count: u64,
+    pub completed_volume: Vec<AssetVolume>, // Source-chain units
+    pub average_completion_time_ns: Option<u64>,
+    pub failure_rate: f64, // failed / (completed + failed)
+}
+
+// Running totals updated on every request and terminal transition.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct RouteStatsAccumulator {
+    route: BridgeRoute,
+    request_count: u64,
+    completed_count: u64,
+    failed_count: u64,
+    cancelled_count: u64,
+    completed_volume: Vec<AssetVolume>,
+    total_completion_time_ns: u128,
+}
+
+impl RouteStatsAccumulator {
+    fn new(route: BridgeRoute) -> Self {
+        Self {
+            route,
+            request_count: 0,
+            completed_count: 0,
+            failed_count: 0,
+            cancelled_count: 0,
+            completed_volume: Vec::new(),
+            total_completion_time_ns: 0,
+        }
+    }
+}
+
+impl Storable for RouteStatsAccumulator {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// One accepted request (inflow) or signed release (outflow) in the rolling
+// 24h window.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct VolumeEntry {
+    request_id: String,
+    user_principal: Principal,
+    route: BridgeRoute,
+    asset_type: String,
+    amount: u128,
+    at: u64,
+}
+
+impl Storable for VolumeEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// A paused route; the route is kept alongside the trip because the map is
+// keyed by `route_key`.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct RouteTrip {
+    route: BridgeRoute,
+    trip: CircuitBreakerTrip,
+}
+
+impl Storable for RouteTrip {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// Who may bridge: the KYC floor, and addresses usable without a linked
+// wallet.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct BridgeAccess {
+    min_kyc_level: KYCLevel,
+    allowed_addresses: Vec<(ChainType, String)>,
+}
+
+impl Default for BridgeAccess {
     fn default() -> Self {
-        Self::new()
+        Self {
+            min_kyc_level: KYCLevel::Basic,
+            allowed_addresses: Vec::new(),
+        }
+    }
+}
+
+impl Storable for BridgeAccess {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
     }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// Stable memories for the bridge state that must survive upgrades.
+pub struct BridgeMemories {
+    pub relayers: Memory,
+    pub consumed_deposits: Memory,
+    pub chain_configs: Memory,
+    pub used_quotes: Memory,
+    pub route_trips: Memory,
+    pub inflows: Memory,
+    pub outflows: Memory,
+    pub route_timeouts: Memory,
+    pub route_stats: Memory,
+    pub limits: Memory,
+    pub access: Memory,
+    pub requests: Memory,
+    pub user_history: Memory,
+    pub by_created: Memory,
+}
+
+pub struct BridgeService {
+    requests: StableBTreeMap<String, BridgeRequest, Memory>, // request_id -> request
+    chain_configs: StableBTreeMap<String, ChainConfig, Memory>, // chain_key -> config
+    user_history: StableBTreeMap<String, String, Memory>,    // history_key -> request_id
+    relayers: StableBTreeMap<Principal, (), Memory>, // Principals allowed to advance bridge requests
+    consumed_deposits: StableBTreeMap<String, String, Memory>, // deposit_key -> request_id
+    used_quotes: StableBTreeMap<String, u64, Memory>, // quote_id -> expires_at
+    limits: StableCell<BridgeLimits, Memory>,
+    route_trips: StableBTreeMap<String, RouteTrip, Memory>, // route_key -> trip
+    access: StableCell<BridgeAccess, Memory>,
+    route_timeouts: StableBTreeMap<String, RouteTimeouts, Memory>, // route_key -> timeouts
+    by_created: StableBTreeMap<String, String, Memory>,            // created_key -> request_id
+    route_stats: StableBTreeMap<String, RouteStatsAccumulator, Memory>, // route_key -> totals
+    inflows: StableBTreeMap<String, VolumeEntry, Memory>,          // request_id -> entry
+    outflows: StableBTreeMap<String, VolumeEntry, Memory>,         // request_id -> entry
+    deposit_lookups: HashMap<String, DepositLookup>,               // request_id -> failed lookups
+    deposit_cursor: Option<String>, // created_key of the last request the watcher checked
+    release_checks: HashMap<String, u64>, // request_id -> next on-chain release check
 }
 
 impl BridgeService {
-    pub fn new() -> Self {
+    pub fn new(memories: BridgeMemories) -> Self {
         let mut service = Self {
-            requests: HashMap::new(),
-            chain_configs: HashMap::new(),
-            user_history: HashMap::new(),
+            requests: StableBTreeMap::init(memories.requests),
+            chain_configs: StableBTreeMap::init(memories.chain_configs),
+            user_history: StableBTreeMap::init(memories.user_history),
+            relayers: StableBTreeMap::init(memories.relayers),
+            consumed_deposits: StableBTreeMap::init(memories.consumed_deposits),
+            used_quotes: StableBTreeMap::init(memories.used_quotes),
+            limits: StableCell::init(memories.limits, BridgeLimits::default())
+                .expect("Failed to init bridge limits"),
+            route_trips: StableBTreeMap::init(memories.route_trips),
+            access: StableCell::init(memories.access, BridgeAccess::default())
+                .expect("Failed to init bridge access"),
+            route_timeouts: StableBTreeMap::init(memories.route_timeouts),
+            by_created: StableBTreeMap::init(memories.by_created),
+            route_stats: StableBTreeMap::init(memories.route_stats),
+            inflows: StableBTreeMap::init(memories.inflows),
+            outflows: StableBTreeMap::init(memories.outflows),
+            deposit_lookups: HashMap::new(),
+            deposit_cursor: None,
+            release_checks: HashMap::new(),
         };
 
-        service.init_default_chains();
+        // Runtime changes to the chain configs persist across upgrades
+        if service.chain_configs.is_empty() {
+            service.init_default_chains();
+        }
         service
     }
 
     pub fn init_default_chains(&mut self) {
         // Bitcoin configuration
         self.chain_configs.insert(
-            "bitcoin".to_string(),
+            chain_key(&ChainType::Bitcoin),
             ChainConfig {
                 chain_type: ChainType::Bitcoin,
                 rpc_url: "https://blockstream.info/api/".to_string(),
                 bridge_contract: "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh".to_string(),
-                supported_assets: vec!["BTC".to_string()],
-                min_amount: 10000,     // 0.0001 BTC in satoshis
-                max_amount: 100000000, // 1 BTC in satoshis
-                fee_percentage: 0.5,
+                supported_assets: vec![AssetConfig {
+                    symbol: "BTC".to_string(),
+                    decimals: 8,
+                    min_amount: 10000,     // 0.0001 BTC
+                    max_amount: 100000000, // 1 BTC
+                    fixed_fee: 1000,
+                    destination_gas_fee: 2000,
+                    user_daily_limit: 200000000,
+                    daily_limit: 1000000000,
+                    outflow_breaker_threshold: 1000000000,
+                    token_contract: None,
+                }],
+                fee_bps: 50,
                 confirmation_blocks: 6,
+                evm_chain_id: None,
+                enabled: true,
             },
         );
 
         // Ethereum configuration
         self.chain_configs.insert(
-            "ethereum".to_string(),
+            chain_key(&ChainType::Ethereum),
             ChainConfig {
                 chain_type: ChainType::Ethereum,
-                rpc_url: "https://mainnet.infura.io/v3/".to_string(),
+                rpc_url: "https://ethereum-rpc.publicnode.com".to_string(),
                 bridge_contract: "0x742d35Cc6635C0532925a3b8D6C8D2f8C4bDD4A1".to_string(),
-                supported_assets: vec!["ETH".to_string(), "USDC".to_string(), "USDT".to_string()],
-                min_amount: 1000000000000000,     // 0.001 ETH in wei
-                max_amount: 10000000000000000000, // 10 ETH in wei
-                fee_percentage: 0.3,
+                supported_assets: vec![
+                    AssetConfig {
+                        symbol: "ETH".to_string(),
+                        decimals: 18,
+                        min_amount: 1000000000000000,     // 0.001 ETH
+                        max_amount: 10000000000000000000, // 10 ETH
+                        fixed_fee: 100000000000000,       // 0.0001 ETH
+                        destination_gas_fee: 200000000000000,
+                        user_daily_limit: 20000000000000000000,
+                        daily_limit: 100000000000000000000,
+                        outflow_breaker_threshold: 100000000000000000000,
+                        token_contract: None,
+                    },
+                    AssetConfig {
+                        symbol: "USDC".to_string(),
+                        decimals: 6,
+                        min_amount: 10000000,     // 10 USDC
+                        max_amount: 100000000000, // 100,000 USDC
+                        fixed_fee: 500000,        // 0.5 USDC
+                        destination_gas_fee: 2000000,
+                        user_daily_limit: 200000000000,
+                        daily_limit: 1000000000000,
+                        outflow_breaker_threshold: 1000000000000,
+                        token_contract: Some(
+                            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+                        ),
+                    },
+                    AssetConfig {
+                        symbol: "USDT".to_string(),
+                        decimals: 6,
+                        min_amount: 10000000,     // 10 USDT
+                        max_amount: 100000000000, // 100,000 USDT
+                        fixed_fee: 500000,        // 0.5 USDT
+                        destination_gas_fee: 2000000,
+                        user_daily_limit: 200000000000,
+                        daily_limit: 1000000000000,
+                        outflow_breaker_threshold: 1000000000000,
+                        token_contract: Some(
+                            "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string(),
+                        ),
+                    },
+                    AssetConfig {
+                        symbol: "BTC".to_string(), // Wrapped BTC
+                        decimals: 8,
+                        min_amount: 10000,     // 0.0001 BTC
+                        max_amount: 100000000, // 1 BTC
+                        fixed_fee: 1000,
+                        destination_gas_fee: 1000,
+                        user_daily_limit: 200000000,
+                        daily_limit: 1000000000,
+                        outflow_breaker_threshold: 1000000000,
+                        token_contract: Some(
+                            "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599".to_string(),
+                        ),
+                    },
+                ],
+                fee_bps: 30,
                 confirmation_blocks: 12,
+                evm_chain_id: Some(1),
+                enabled: true,
             },
         );
 
         // Solana configuration
         self.chain_configs.insert(
-            "solana".to_string(),
+            chain_key(&ChainType::Solana),
             ChainConfig {
                 chain_type: ChainType::Solana,
                 rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
                 bridge_contract: "HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz".to_string(),
-                supported_assets: vec!["SOL".to_string(), "USDC".to_string()],
-                min_amount: 10000000,      // 0.01 SOL in lamports
-                max_amount: 1000000000000, // 1000 SOL in lamports
-                fee_percentage: 0.2,
+                supported_assets: vec![AssetConfig {
+                    symbol: "SOL".to_string(),
+                    decimals: 9,
+                    min_amount: 10000000,      // 0.01 SOL
+                    max_amount: 1000000000000, // 1000 SOL
+                    fixed_fee: 1000000,        // 0.001 SOL
+                    destination_gas_fee: 5000,
+                    user_daily_limit: 2000000000000,
+                    daily_limit: 10000000000000,
+                    outflow_breaker_threshold: 10000000000000,
+                    token_contract: None,
+                }],
+                fee_bps: 20,
                 confirmation_blocks: 32,
+                evm_chain_id: None,
+                enabled: true,
             },
         );
     }
 
+    pub fn add_chain_config(&mut self, config: ChainConfig) -> Result<(), String> {
+        validate_chain_config(&config)?;
+        if self.has_chain_config(&config.chain_type) {
+            return Err("Chain is already configured".to_string());
+        }
+        self.chain_configs
+            .insert(chain_key(&config.chain_type), config);
+        Ok(())
+    }
+
+    pub fn update_chain_config(&mut self, config: ChainConfig) -> Result<(), String> {
+        validate_chain_config(&config)?;
+        if !self.has_chain_config(&config.chain_type) {
+            return Err("Chain configuration not found".to_string());
+        }
+        self.chain_configs
+            .insert(chain_key(&config.chain_type), config);
+        Ok(())
+    }
+
+    // Disabled chains stay listed so in-flight requests can still settle,
+    // but no new requests may use them.
+    pub fn disable_chain(&mut self, chain: &ChainType) -> Result<(), String> {
+        let mut config = self
+            .chain_config_for(chain)
+            .ok_or("Chain configuration not found")?;
+        config.enabled = false;
+        self.chain_configs.insert(chain_key(chain), config);
+        Ok(())
+    }
+
+    // Prices a transfer: the percentage and fixed fees are taken on the
+    // source chain, the remainder is rescaled to the destination asset's
+    // decimals and the destination gas estimate is deducted from it.
+    pub fn quote_bridge(
+        &self,
+        from_chain: &ChainType,
+        to_chain: &ChainType,
+        asset_type: &str,
+        amount: u128,
+        user_principal: Principal,
+        now: u64,
+    ) -> Result<BridgeQuote, String> {
+        self.validate_bridge_request(from_chain, to_chain, asset_type, amount)?;
+        self.check_volume_limits(user_principal, from_chain, asset_type, amount, now)?;
+
+        let source = self.asset_config(from_chain, asset_type)?;
+        let destination = self.asset_config(to_chain, asset_type)?;
+        let fee = self.calculate_bridge_fee(from_chain, asset_type, amount)?;
+
+        let net_amount = amount
+            .checked_sub(fee.amount)
+            .ok_or("Amount does not cover the bridge fee")?;
+        let receive_amount = scale_amount(net_amount, source.decimals, destination.decimals)?
+            .checked_sub(destination.destination_gas_fee)
+            .filter(|receive| *receive > 0)
+            .ok_or("Amount does not cover the destination gas fee")?;
+
+        Ok(BridgeQuote {
+            quote_id: String::new(),
+            user_principal,
+            from_chain: from_chain.clone(),
+            to_chain: to_chain.clone(),
+            asset_type: asset_type.to_string(),
+            amount,
+            fee,
+            destination_gas_fee: destination.destination_gas_fee,
+            receive_amount,
+            source_decimals: source.decimals,
+            destination_decimals: destination.decimals,
+            expires_at: now + QUOTE_TTL_NS,
+        })
+    }
+
+    // `quote` must already be authenticated by the caller; this enforces
+    // expiry, ownership and single use, and re-checks that the route is
+    // still open.
     pub fn initiate_bridge_request(
         &mut self,
-        from_chain: ChainType,
-        to_chain: ChainType,
-        asset_type: String,
-        amount: u64,
+        request_id: String,
+        identity_id: String,
+        quote: &BridgeQuote,
         from_address: String,
         to_address: String,
         user_principal: Principal,
     ) -> Result<String, String> {
-        // Generate unique request ID
-        let request_id = format!(
-            "bridge_{}_{}_{}",
-            time(),
-            &user_principal.to_string()[..8],
-            amount
-        );
+        if self.requests.contains_key(&request_id) {
+            return Err("Bridge request ID already exists".to_string());
+        }
+
+        let now = time();
+        if quote.user_principal != user_principal {
+            return Err("Quote was issued to a different principal".to_string());
+        }
+        if now > quote.expires_at {
+            return Err("Quote has expired".to_string());
+        }
+        if self.used_quotes.contains_key(&quote.quote_id) {
+            return Err("Quote has already been used".to_string());
+        }
 
         // Validate bridge request
-        self.validate_bridge_request(&from_chain, &to_chain, &asset_type, amount)?;
+        self.validate_bridge_request(
+            &quote.from_chain,
+            &quote.to_chain,
+            &quote.asset_type,
+            quote.amount,
+        )?;
+        self.check_volume_limits(
+            user_principal,
+            &quote.from_chain,
+            &quote.asset_type,
+            quote.amount,
+            now,
+        )?;
 
         // Create bridge request
         let bridge_request = BridgeRequest {
             request_id: request_id.clone(),
-            from_chain,
-            to_chain,
-            asset_type,
-            amount,
+            identity_id,
+            from_chain: quote.from_chain.clone(),
+            to_chain: quote.to_chain.clone(),
+            asset_type: quote.asset_type.clone(),
+            amount: quote.amount,
             from_address,
             to_address,
             user_principal,
             status: BridgeStatus::Initiated,
-            created_at: time(),
+            created_at: now,
             completed_at: None,
             transaction_hashes: Vec::new(),
+            status_history: Vec::new(),
+            source_tx_hash: None,
+            release_transaction: None,
+            quote_id: quote.quote_id.clone(),
+            fee: quote.fee.amount,
+            receive_amount: quote.receive_amount,
+            release_broadcast: None,
+            refund_transaction: None,
+            refund_broadcast: None,
+            refund_claimed_at: None,
         };
 
-        // Store request
-        self.requests.insert(request_id.clone(), bridge_request);
+        prune_volume(&mut self.inflows, now.saturating_sub(DAY_NS));
+        self.inflows.insert(
+            request_id.clone(),
+            VolumeEntry {
+                request_id: request_id.clone(),
+                user_principal,
+                route: BridgeRoute::of(&bridge_request),
+                asset_type: bridge_request.asset_type.clone(),
+                amount: bridge_request.amount,
+                at: now,
+            },
+        );
+
+        update_route_stats(
+            &mut self.route_stats,
+            BridgeRoute::of(&bridge_request),
+            |stats| stats.request_count += 1,
+        );
+        self.insert_request(bridge_request);
 
-        // Add to user history
-        self.user_history
-            .entry(user_principal)
-            .or_default()
-            .push(request_id.clone());
+        // Remember the quote until it would have expired anyway
+        let expired: Vec<String> = self
+            .used_quotes
+            .iter()
+            .filter(|(_, expires_at)| *expires_at < now)
+            .map(|(quote_id, _)| quote_id)
+            .collect();
+        for quote_id in expired {
+            self.used_quotes.remove(&quote_id);
+        }
+        self.used_quotes
+            .insert(quote.quote_id.clone(), quote.expires_at);
 
         Ok(request_id)
     }
 
-    pub fn get_bridge_request(&self, request_id: &str) -> Option<&BridgeRequest> {
-        self.requests.get(request_id)
+    // Stores a new request and files it in the chronological and per-user
+    // indexes.
+    fn insert_request(&mut self, request: BridgeRequest) {
+        let request_id = request.request_id.clone();
+        self.by_created.insert(
+            created_key(request.created_at, &request_id),
+            request_id.clone(),
+        );
+        self.user_history.insert(
+            history_key(&request.user_principal, request.created_at, &request_id),
+            request_id.clone(),
+        );
+        self.requests.insert(request_id, request);
+    }
+
+    pub fn has_request(&self, request_id: &str) -> bool {
+        self.requests.contains_key(&request_id.to_string())
     }
 
-    pub fn get_user_bridge_history(&self, user_principal: Principal) -> Vec<BridgeRequest> {
-        match self.user_history.get(&user_principal) {
-            Some(request_ids) => request_ids
-                .iter()
-                .filter_map(|id| self.requests.get(id).cloned())
-                .collect(),
-            None => Vec::new(),
+    pub fn get_bridge_request(&self, request_id: &str) -> Option<BridgeRequest> {
+        self.requests.get(&request_id.to_string())
+    }
+
+    pub fn get_user_bridge_history(
+        &self,
+        user_principal: Principal,
+        filter: &BridgeHistoryFilter,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<BridgeRequestPage, String> {
+        // The user's keys share the "{principal}/" prefix; '0' follows '/'
+        let user = user_principal.to_text();
+        let end = match cursor {
+            Some(cursor) => {
+                let request = self
+                    .get_bridge_request(cursor)
+                    .filter(|request| request.user_principal == user_principal)
+                    .ok_or(UNKNOWN_CURSOR)?;
+                history_key(&user_principal, request.created_at, cursor)
+            }
+            None => format!("{}0", user),
+        };
+        let ids = self
+            .user_history
+            .range(format!("{}/", user)..end)
+            .rev()
+            .map(|(_, id)| id);
+        Ok(self.page(ids, filter, limit))
+    }
+
+    // Operator view across all users.
+    pub fn list_bridge_requests(
+        &self,
+        filter: &BridgeHistoryFilter,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<BridgeRequestPage, String> {
+        let older = match cursor {
+            Some(cursor) => {
+                let request = self.get_bridge_request(cursor).ok_or(UNKNOWN_CURSOR)?;
+                self.by_created
+                    .range(..created_key(request.created_at, cursor))
+            }
+            None => self.by_created.iter(),
+        };
+        Ok(self.page(older.rev().map(|(_, id)| id), filter, limit))
+    }
+
+    fn page(
+        &self,
+        ids: impl Iterator<Item = String>,
+        filter: &BridgeHistoryFilter,
+        limit: usize,
+    ) -> BridgeRequestPage {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let mut requests: Vec<BridgeRequest> = ids
+            .filter_map(|id| self.requests.get(&id))
+            .filter(|request| filter.matches(request))
+            .take(limit + 1)
+            .map(|request| request.public_view())
+            .collect();
+
+        let next_cursor = if requests.len() > limit {
+            requests.truncate(limit);
+            requests.last().map(|request| request.request_id.clone())
+        } else {
+            None
+        };
+        BridgeRequestPage {
+            requests,
+            next_cursor,
+        }
+    }
+
+    pub fn route_stats(&self) -> Vec<RouteStats> {
+        self.route_stats
+            .iter()
+            .map(|(_, stats)| {
+                let finished = stats.completed_count + stats.failed_count;
+                RouteStats {
+                    route: stats.route.clone(),
+                    request_count: stats.request_count,
+                    completed_count: stats.completed_count,
+                    failed_count: stats.failed_count,
+                    cancelled_count: stats.cancelled_count,
+                    completed_volume: stats.completed_volume.clone(),
+                    average_completion_time_ns: (stats.completed_count > 0).then(|| {
+                        (stats.total_completion_time_ns / stats.completed_count as u128) as u64
+                    }),
+                    failure_rate: if finished > 0 {
+                        stats.failed_count as f64 / finished as f64
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect()
+    }
+
+    pub fn set_limits(&mut self, limits: BridgeLimits) {
+        let _ = self.limits.set(limits);
+    }
+
+    pub fn min_kyc_level(&self) -> KYCLevel {
+        self.access.get().min_kyc_level.clone()
+    }
+
+    pub fn set_min_kyc_level(&mut self, level: KYCLevel) {
+        let mut access = self.access.get().clone();
+        access.min_kyc_level = level;
+        let _ = self.access.set(access);
+    }
+
+    pub fn is_address_allowed(&self, chain: &ChainType, address: &str) -> bool {
+        self.access
+            .get()
+            .allowed_addresses
+            .iter()
+            .any(|(c, a)| c == chain && same_address(chain, a, address))
+    }
+
+    pub fn allow_address(&mut self, chain: ChainType, address: String) {
+        if !self.is_address_allowed(&chain, &address) {
+            let mut access = self.access.get().clone();
+            access.allowed_addresses.push((chain, address));
+            let _ = self.access.set(access);
         }
     }
 
+    pub fn disallow_address(&mut self, chain: &ChainType, address: &str) {
+        let mut access = self.access.get().clone();
+        access
+            .allowed_addresses
+            .retain(|(c, a)| !(c == chain && same_address(chain, a, address)));
+        let _ = self.access.set(access);
+    }
+
+    pub fn is_relayer(&self, principal: &Principal) -> bool {
+        self.relayers.contains_key(principal)
+    }
+
+    pub fn add_relayer(&mut self, relayer: Principal) {
+        self.relayers.insert(relayer, ());
+    }
+
+    pub fn remove_relayer(&mut self, relayer: &Principal) {
+        self.relayers.remove(relayer);
+    }
+
+    // Relayer-driven transition along the state machine.
     pub fn update_bridge_status(
         &mut self,
         request_id: &str,
         status: BridgeStatus,
         transaction_hash: Option<String>,
-    ) -> Result<(), String> {
-        match self.requests.get_mut(request_id) {
+        relayer: Principal,
+    ) -> Result<BridgeStatusTransition, String> {
+        if !self.is_relayer(&relayer) {
+            return Err("Only bridge relayers can update bridge status".to_string());
+        }
+        // Locking, minting, completion and refunds each have a dedicated,
+        // checked flow; only failing a request has none
+        if !matches!(status, BridgeStatus::Failed { .. }) {
+            return Err("Relayers can only mark bridge requests as failed".to_string());
+        }
+
+        self.apply_transition(request_id, status, transaction_hash, relayer)
+    }
+
+    // Users may only cancel their own requests before funds are locked.
+    pub fn cancel_bridge_request(
+        &mut self,
+        request_id: &str,
+        user_principal: Principal,
+    ) -> Result<BridgeStatusTransition, String> {
+        match self.get_bridge_request(request_id) {
             Some(request) => {
-                request.status = status;
-                if let Some(hash) = transaction_hash {
-                    request.transaction_hashes.push(hash);
+                if request.user_principal != user_principal {
+                    return Err("Only the request owner can cancel".to_string());
                 }
-                if matches!(
-                    request.status,
-                    BridgeStatus::Completed | BridgeStatus::Failed { .. } | BridgeStatus::Cancelled
-                ) {
-                    request.completed_at = Some(time());
+                if !matches!(request.status, BridgeStatus::Initiated) {
+                    return Err("Only initiated requests can be cancelled".to_string());
                 }
-                Ok(())
             }
-            None => Err("Bridge request not found".to_string()),
+            None => return Err("Bridge request not found".to_string()),
         }
-    }
 
-    pub fn calculate_bridge_fee(&self, from_chain: &ChainType, amount: u64) -> BridgeFee {
-        let chain_name = match from_chain {
-            ChainType::Bitcoin => "bitcoin",
-            ChainType::Ethereum => "ethereum",
-            ChainType::Solana => "solana",
-            _ => "ethereum", // default
-        };
+        self.apply_transition(request_id, BridgeStatus::Cancelled, None, user_principal)
+    }
 
-        if let Some(config) = self.chain_configs.get(chain_name) {
-            let percentage_fee = (amount as f64 * config.fee_percentage / 100.0) as u64;
-            let fixed_fee = 1000; // Base fixed fee
+    // Users attach the source-chain deposit transaction so the watcher can
+    // confirm the lock. A transaction can fund only one request.
+    pub fn submit_deposit_transaction(
+        &mut self,
+        request_id: &str,
+        user_principal: Principal,
+        tx_hash: String,
+    ) -> Result<(), String> {
+        let mut request = self
+            .get_bridge_request(request_id)
+            .ok_or("Bridge request not found")?;
+        if request.user_principal != user_principal {
+            return Err("Only the request owner can submit a deposit".to_string());
+        }
+        if !matches!(request.status, BridgeStatus::Initiated) {
+            return Err("Deposit can only be submitted for initiated requests".to_string());
+        }
 
-            BridgeFee {
-                amount: percentage_fee + fixed_fee,
-                percentage: config.fee_percentage,
-                fixed_fee,
-            }
-        } else {
-            // Default fee structure
-            BridgeFee {
-                amount: (amount as f64 * 0.5 / 100.0) as u64 + 1000,
-                percentage: 0.5,
-                fixed_fee: 1000,
-            }
+        let tx_hash = canonical_tx_hash(&request.from_chain, &tx_hash)?;
+        let key = deposit_key(&request.from_chain, &tx_hash);
+        if self
+            .consumed_deposits
+            .get(&key)
+            .is_some_and(|owner| owner != request_id)
+        {
+            return Err("Deposit transaction is already used by another request".to_string());
         }
+        // A replaced submission frees its transaction for other requests
+        if let Some(previous) = &request.source_tx_hash {
+            self.consumed_deposits
+                .remove(&deposit_key(&request.from_chain, previous));
+        }
+        self.consumed_deposits.insert(key, request_id.to_string());
+        self.deposit_lookups.remove(request_id);
+
+        request.source_tx_hash = Some(tx_hash);
+        self.requests.insert(request_id.to_string(), request);
+        Ok(())
     }
 
-    pub fn get_supported_chains(&self) -> Vec<ChainConfig> {
-        self.chain_configs.values().cloned().collect()
+    // Initiated requests with a submitted deposit of the source chain's
+    // native asset whose lookup is due. Token deposits are advanced by
+    // relayers. Each call resumes after the last request returned, so slow
+    // deposits cannot starve newer ones.
+    pub fn pending_deposit_checks(
+        &mut self,
+        now: u64,
+        limit: usize,
+    ) -> Vec<(BridgeRequest, ChainConfig)> {
+        let cursor = self.deposit_cursor.clone().unwrap_or_default();
+        let pending: Vec<(BridgeRequest, ChainConfig)> = self
+            .by_created
+            .range((Excluded(cursor.clone()), Unbounded))
+            .chain(self.by_created.range(..=cursor))
+            .filter_map(|(_, request_id)| self.requests.get(&request_id))
+            .filter(|r| matches!(r.status, BridgeStatus::Initiated) && r.source_tx_hash.is_some())
+            .filter(|r| native_asset(&r.from_chain) == Some(r.asset_type.as_str()))
+            .filter(|r| {
+                self.deposit_lookups
+                    .get(&r.request_id)
+                    .is_none_or(|lookup| lookup.next_check_at <= now)
+            })
+            .filter_map(|r| {
+                self.chain_config_for(&r.from_chain)
+                    .map(|config| (r, config))
+            })
+            .take(limit)
+            .collect();
+
+        if let Some((request, _)) = pending.last() {
+            self.deposit_cursor = Some(created_key(request.created_at, &request.request_id));
+        }
+        pending
     }
 
-    fn validate_bridge_request(
-        &self,
-        from_chain: &ChainType,
-        to_chain: &ChainType,
-        asset_type: &str,
-        amount: u64,
-    ) -> Result<(), String> {
-        if from_chain == to_chain {
-            return Err("Source and destination chains cannot be the same".to_string());
+    // Backs off after the source chain did not know a request's deposit
+    // transaction, failing the request once the lookups are exhausted.
+    pub fn record_deposit_miss(
+        &mut self,
+        request_id: &str,
+        now: u64,
+        observer: Principal,
+    ) -> Option<BridgeStatusTransition> {
+        let lookup = self
+            .deposit_lookups
+            .entry(request_id.to_string())
+            .or_insert(DepositLookup {
+                misses: 0,
+                next_check_at: now,
+            });
+        lookup.misses += 1;
+        lookup.next_check_at = now
+            + DEPOSIT_LOOKUP_RETRY_BASE_NS
+                .saturating_mul(1u64 << lookup.misses.min(16))
+                .min(DEPOSIT_LOOKUP_RETRY_MAX_NS);
+        if lookup.misses < MAX_DEPOSIT_LOOKUP_MISSES {
+            return None;
         }
 
-        let from_chain_name = match from_chain {
-            ChainType::Bitcoin => "bitcoin",
-            ChainType::Ethereum => "ethereum",
-            ChainType::Solana => "solana",
-            _ => return Err("Unsupported source chain".to_string()),
-        };
+        self.record_deposit_outcome(
+            request_id,
+            BridgeStatus::Failed {
+                reason: "Deposit transaction was not found on the source chain".to_string(),
+            },
+            observer,
+        )
+        .ok()
+    }
 
-        if let Some(config) = self.chain_configs.get(from_chain_name) {
-            if !config.supported_assets.contains(&asset_type.to_string()) {
-                return Err(format!(
-                    "Asset {} not supported on source chain",
-                    asset_type
-                ));
-            }
+    // The source chain knows the deposit, so earlier misses no longer count.
+    pub fn record_deposit_seen(&mut self, request_id: &str) {
+        self.deposit_lookups.remove(request_id);
+    }
 
-            if amount < config.min_amount {
-                return Err(format!("Amount below minimum: {}", config.min_amount));
-            }
+    // Transitions driven by the canister's own deposit watcher.
+    pub fn record_deposit_outcome(
+        &mut self,
+        request_id: &str,
+        status: BridgeStatus,
+        observer: Principal,
+    ) -> Result<BridgeStatusTransition, String> {
+        let tx_hash = self
+            .get_bridge_request(request_id)
+            .and_then(|r| r.source_tx_hash);
+        self.deposit_lookups.remove(request_id);
+        self.apply_transition(request_id, status, tx_hash, observer)
+    }
 
-            if amount > config.max_amount {
-                return Err(format!("Amount exceeds maximum: {}", config.max_amount));
-            }
-        } else {
-            return Err("Source chain configuration not found".to_string());
+    pub fn release_kind(&self, request: &BridgeRequest) -> Result<ReleaseKind, String> {
+        self.payout_kind(&request.to_chain, &request.asset_type)
+    }
+
+    // Pays out the chain's native asset directly; anything else goes through
+    // the chain's bridge contract, which only EVM chains have.
+    fn payout_kind(&self, chain: &ChainType, asset_type: &str) -> Result<ReleaseKind, String> {
+        if native_asset(chain) == Some(asset_type) {
+            return Ok(ReleaseKind::Native);
+        }
+        let config = self
+            .chain_config_for(chain)
+            .ok_or("Payout chain configuration not found")?;
+        if rpc_flavor(chain) != Some(RpcFlavor::EvmJsonRpc) {
+            return Err(format!(
+                "Bridge cannot pay out {} on {:?}",
+                asset_type, chain
+            ));
+        }
+        Ok(ReleaseKind::Mint {
+            contract: config.bridge_contract,
+        })
+    }
+
+    // Payout in destination units, fixed when the request was quoted.
+    pub fn release_amount(&self, request: &BridgeRequest) -> u128 {
+        request.receive_amount
+    }
+
+    pub fn record_release(
+        &mut self,
+        request_id: &str,
+        release: SignedRelease,
+        relayer: Principal,
+    ) -> Result<BridgeStatusTransition, String> {
+        if !self.is_relayer(&relayer) {
+            return Err("Only bridge relayers can sign releases".to_string());
+        }
+
+        let tx_hash = release.tx_hash.clone();
+        let transition = self.apply_transition(
+            request_id,
+            BridgeStatus::TargetMinting,
+            Some(tx_hash),
+            relayer,
+        )?;
+
+        if let Some(mut request) = self.get_bridge_request(request_id) {
+            request.release_transaction = Some(release);
+            request.release_broadcast = Some(BroadcastState::new(transition.changed_at));
+            self.outflows.insert(
+                request_id.to_string(),
+                VolumeEntry {
+                    request_id: request_id.to_string(),
+                    user_principal: request.user_principal,
+                    route: BridgeRoute::of(&request),
+                    asset_type: request.asset_type.clone(),
+                    amount: request.receive_amount,
+                    at: transition.changed_at,
+                },
+            );
+            self.requests.insert(request_id.to_string(), request);
         }
+        Ok(transition)
+    }
 
+    pub fn route_timeouts(&self, route: &BridgeRoute) -> RouteTimeouts {
+        self.route_timeouts
+            .get(&route_key(route))
+            .unwrap_or(DEFAULT_ROUTE_TIMEOUTS)
+    }
+
+    pub fn set_route_timeouts(
+        &mut self,
+        route: BridgeRoute,
+        timeouts: RouteTimeouts,
+    ) -> Result<(), String> {
+        if timeouts.source_locked_ns == 0 || timeouts.target_minting_ns == 0 {
+            return Err("Route timeouts must be positive".to_string());
+        }
+        self.route_timeouts.insert(route_key(&route), timeouts);
         Ok(())
     }
+
+    // Moves SourceLocked requests that outlived their route's timeout to
+    // RefundPending. Nothing was signed for them, so nothing can pay out.
+    // Stalled releases go through `pending_release_checks` instead.
+    pub fn expire_stalled_requests(
+        &mut self,
+        now: u64,
+        observer: Principal,
+    ) -> Vec<(String, BridgeStatusTransition)> {
+        let stalled: Vec<String> = self
+            .requests
+            .values()
+            .filter(|request| matches!(request.status, BridgeStatus::SourceLocked))
+            .filter(|request| {
+                let timeouts = self.route_timeouts(&BridgeRoute::of(request));
+                now.saturating_sub(status_since(request)) > timeouts.source_locked_ns
+            })
+            .map(|request| request.request_id)
+            .collect();
+
+        stalled
+            .into_iter()
+            .filter_map(|request_id| {
+                self.apply_transition(
+                    &request_id,
+                    BridgeStatus::RefundPending {
+                        reason: "Release was not signed in time".to_string(),
+                    },
+                    None,
+                    observer,
+                )
+                .ok()
+                .map(|transition| (request_id, transition))
+            })
+            .collect()
+    }
+
+    // Why a signed release that never reached its chain should be refunded,
+    // once its route's timeout passed or its broadcasts were exhausted.
+    fn release_stall_reason(&self, request: &BridgeRequest, now: u64) -> Option<String> {
+        if !matches!(request.status, BridgeStatus::TargetMinting) {
+            return None;
+        }
+        let state = request.release_broadcast.as_ref()?;
+        if state.broadcast_at.is_some() {
+            return None;
+        }
+        if state.attempts >= MAX_RELEASE_BROADCAST_ATTEMPTS {
+            return Some(format!(
+                "Release broadcast failed: {}",
+                state.last_error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        let timeouts = self.route_timeouts(&BridgeRoute::of(request));
+        (now.saturating_sub(status_since(request)) > timeouts.target_minting_ns)
+            .then(|| "Release was not broadcast in time".to_string())
+    }
+
+    // Whether the watcher should look a release up on chain: once it was
+    // broadcast, to complete it, or once it stalled, to decide on a refund.
+    fn release_needs_check(&self, request: &BridgeRequest, now: u64) -> bool {
+        matches!(request.status, BridgeStatus::TargetMinting)
+            && request
+                .release_broadcast
+                .as_ref()
+                .is_some_and(|state| state.broadcast_at.is_some())
+            || self.release_stall_reason(request, now).is_some()
+    }
+
+    // Releases due for an on-chain check, oldest first. A release is only
+    // refunded once its chain shows it can no longer confirm.
+    pub fn pending_release_checks(
+        &self,
+        now: u64,
+        limit: usize,
+    ) -> Vec<(String, SignedRelease, ChainConfig)> {
+        let mut due: Vec<(u64, String, SignedRelease)> = self
+            .requests
+            .values()
+            .filter(|request| {
+                self.release_checks
+                    .get(&request.request_id)
+                    .is_none_or(|next_check_at| *next_check_at <= now)
+            })
+            .filter(|request| self.release_needs_check(request, now))
+            .filter_map(|request| {
+                request
+                    .release_transaction
+                    .clone()
+                    .map(|release| (release.signed_at, request.request_id.clone(), release))
+            })
+            .collect();
+        due.sort_by_key(|(signed_at, ..)| *signed_at);
+
+        due.into_iter()
+            .filter_map(|(_, request_id, release)| {
+                self.chain_config_for(&release.chain)
+                    .map(|config| (request_id, release, config))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    // Applies the outcome of a release check: an included release completes
+    // the request, an invalidated one moves it to RefundPending.
+    pub fn record_release_check(
+        &mut self,
+        request_id: &str,
+        check: ReleaseCheck,
+        now: u64,
+        observer: Principal,
+    ) -> Option<BridgeStatusTransition> {
+        let request = self.get_bridge_request(request_id)?;
+        if !self.release_needs_check(&request, now) {
+            return None;
+        }
+        let tx_hash = request
+            .release_transaction
+            .as_ref()
+            .map(|release| release.tx_hash.clone());
+        let reason = self
+            .release_stall_reason(&request, now)
+            .unwrap_or_else(|| "Release can no longer confirm".to_string());
+
+        let (status, tx_hash) = match check {
+            ReleaseCheck::Pending => {
+                self.release_checks
+                    .insert(request_id.to_string(), now + RELEASE_CHECK_INTERVAL_NS);
+                return None;
+            }
+            ReleaseCheck::Included => (BridgeStatus::Completed, tx_hash),
+            ReleaseCheck::Invalidated => (BridgeStatus::RefundPending { reason }, None),
+        };
+        self.release_checks.remove(request_id);
+        self.apply_transition(request_id, status, tx_hash, observer)
+            .ok()
+    }
+
+    // Signed payouts whose next broadcast attempt is due, oldest first.
+    pub fn pending_broadcasts(
+        &self,
+        now: u64,
+        limit: usize,
+    ) -> Vec<(String, PayoutKind, SignedRelease, ChainConfig)> {
+        let mut due: Vec<(u64, String, PayoutKind, SignedRelease)> = Vec::new();
+        for request in self.requests.values() {
+            let (kind, transaction, state) = match request.status {
+                BridgeStatus::TargetMinting => (
+                    PayoutKind::Release,
+                    &request.release_transaction,
+                    &request.release_broadcast,
+                ),
+                BridgeStatus::RefundPending { .. } => (
+                    PayoutKind::Refund,
+                    &request.refund_transaction,
+                    &request.refund_broadcast,
+                ),
+                _ => continue,
+            };
+            if let (Some(transaction), Some(state)) = (transaction, state) {
+                if state.broadcast_at.is_none() && state.next_attempt_at <= now {
+                    due.push((
+                        state.next_attempt_at,
+                        request.request_id.clone(),
+                        kind,
+                        transaction.clone(),
+                    ));
+                }
+            }
+        }
+        due.sort_by_key(|(at, ..)| *at);
+
+        due.into_iter()
+            .filter_map(|(_, request_id, kind, transaction)| {
+                self.chain_config_for(&transaction.chain)
+                    .map(|config| (request_id, kind, transaction, config))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    // Records a broadcast attempt. Failures back off exponentially and keep
+    // retrying; a release that keeps failing is also checked on chain, see
+    // `pending_release_checks`.
+    pub fn record_broadcast(
+        &mut self,
+        request_id: &str,
+        kind: PayoutKind,
+        outcome: Result<(), String>,
+        now: u64,
+        observer: Principal,
+    ) -> Option<BridgeStatusTransition> {
+        let mut request = self.get_bridge_request(request_id)?;
+        let state = match kind {
+            PayoutKind::Release => request.release_broadcast.as_mut()?,
+            PayoutKind::Refund => request.refund_broadcast.as_mut()?,
+        };
+
+        let next_status = match outcome {
+            Ok(()) => {
+                state.broadcast_at = Some(now);
+                state.last_error = None;
+                (kind == PayoutKind::Refund).then_some(BridgeStatus::Refunded)
+            }
+            Err(error) => {
+                state.attempts += 1;
+                state.last_error = Some(error);
+                let backoff = BROADCAST_RETRY_BASE_NS
+                    .saturating_mul(1u64 << state.attempts.min(16))
+                    .min(BROADCAST_RETRY_MAX_NS);
+                state.next_attempt_at = now + backoff;
+                None
+            }
+        };
+
+        let tx_hash = request
+            .refund_transaction
+            .as_ref()
+            .map(|t| t.tx_hash.clone());
+        self.requests.insert(request_id.to_string(), request);
+        next_status.and_then(|status| {
+            self.apply_transition(request_id, status, tx_hash, observer)
+                .ok()
+        })
+    }
+
+    // The refund pays the locked amount back to `from_address`, less the
+    // estimated gas for paying out on the source chain.
+    pub fn refund_payout(&self, request: &BridgeRequest) -> Result<(ReleaseKind, u128), String> {
+        let asset = self.asset_config(&request.from_chain, &request.asset_type)?;
+        let amount = request
+            .amount
+            .checked_sub(asset.destination_gas_fee)
+            .filter(|amount| *amount > 0)
+            .ok_or("Locked amount does not cover the refund gas fee")?;
+        Ok((
+            self.payout_kind(&request.from_chain, &request.asset_type)?,
+            amount,
+        ))
+    }
+
+    pub fn claim_refund(
+        &mut self,
+        request_id: &str,
+        owner: Principal,
+        now: u64,
+    ) -> Result<(), String> {
+        let mut request = self
+            .get_bridge_request(request_id)
+            .ok_or("Bridge request not found")?;
+        if request.user_principal != owner {
+            return Err("Only the request owner can claim a refund".to_string());
+        }
+        if !matches!(request.status, BridgeStatus::RefundPending { .. }) {
+            return Err("Request is not awaiting a refund".to_string());
+        }
+        if request.refund_claimed_at.is_some() {
+            return Err("Refund has already been claimed".to_string());
+        }
+
+        request.refund_claimed_at = Some(now);
+        self.requests.insert(request_id.to_string(), request);
+        Ok(())
+    }
+
+    // A claimed refund that a relayer may sign.
+    pub fn refund_to_sign(
+        &self,
+        request_id: &str,
+        relayer: Principal,
+    ) -> Result<BridgeRequest, String> {
+        if !self.is_relayer(&relayer) {
+            return Err("Only bridge relayers can sign refunds".to_string());
+        }
+        let request = self
+            .get_bridge_request(request_id)
+            .ok_or("Bridge request not found")?;
+        if !matches!(request.status, BridgeStatus::RefundPending { .. }) {
+            return Err("Request is not awaiting a refund".to_string());
+        }
+        if request.refund_claimed_at.is_none() {
+            return Err("Refund has not been claimed".to_string());
+        }
+        if request.refund_transaction.is_some() {
+            return Err("Refund has already been signed".to_string());
+        }
+        Ok(request)
+    }
+
+    pub fn record_refund(
+        &mut self,
+        request_id: &str,
+        refund: SignedRelease,
+        relayer: Principal,
+    ) -> Result<(), String> {
+        let mut request = self.refund_to_sign(request_id, relayer)?;
+        request.refund_broadcast = Some(BroadcastState::new(refund.signed_at));
+        request.refund_transaction = Some(refund);
+        self.requests.insert(request_id.to_string(), request);
+        Ok(())
+    }
+
+    pub fn chain_config_for(&self, chain: &ChainType) -> Option<ChainConfig> {
+        self.chain_configs.get(&chain_key(chain))
+    }
+
+    pub fn has_chain_config(&self, chain: &ChainType) -> bool {
+        self.chain_configs.contains_key(&chain_key(chain))
+    }
+
+    fn apply_transition(
+        &mut self,
+        request_id: &str,
+        status: BridgeStatus,
+        transaction_hash: Option<String>,
+        changed_by: Principal,
+    ) -> Result<BridgeStatusTransition, String> {
+        match self.get_bridge_request(request_id) {
+            Some(mut request) => {
+                if !request.status.can_transition_to(&status) {
+                    return Err(format!(
+                        "Invalid bridge status transition: {:?} -> {:?}",
+                        request.status, status
+                    ));
+                }
+
+                let now = time();
+                let transition = BridgeStatusTransition {
+                    from: request.status.clone(),
+                    to: status.clone(),
+                    changed_by,
+                    changed_at: now,
+                    transaction_hash: transaction_hash.clone(),
+                };
+
+                // Requests that never locked funds stop counting against limits
+                if matches!(request.status, BridgeStatus::Initiated) && status.is_terminal() {
+                    self.inflows.remove(&request_id.to_string());
+                }
+                // Likewise releases that will be refunded instead
+                if matches!(status, BridgeStatus::RefundPending { .. }) {
+                    self.outflows.remove(&request_id.to_string());
+                }
+
+                request.status = status;
+                if let Some(hash) = transaction_hash {
+                    request.transaction_hashes.push(hash);
+                }
+                if request.status.is_terminal() {
+                    request.completed_at = Some(now);
+
+                    update_route_stats(&mut self.route_stats, BridgeRoute::of(&request), |stats| {
+                        match request.status {
+                            BridgeStatus::Completed => {
+                                stats.completed_count += 1;
+                                stats.total_completion_time_ns +=
+                                    now.saturating_sub(request.created_at) as u128;
+                                match stats
+                                    .completed_volume
+                                    .iter_mut()
+                                    .find(|volume| volume.asset_type == request.asset_type)
+                                {
+                                    Some(volume) => {
+                                        volume.amount = volume.amount.saturating_add(request.amount)
+                                    }
+                                    None => stats.completed_volume.push(AssetVolume {
+                                        asset_type: request.asset_type.clone(),
+                                        amount: request.amount,
+                                    }),
+                                }
+                            }
+                            BridgeStatus::Cancelled => stats.cancelled_count += 1,
+                            _ => stats.failed_count += 1,
+                        }
+                    });
+                }
+                request.status_history.push(transition.clone());
+                self.requests.insert(request_id.to_string(), request);
+
+                Ok(transition)
+            }
+            None => Err("Bridge request not found".to_string()),
+        }
+    }
+
+    pub fn ensure_route_open(
+        &self,
+        from_chain: &ChainType,
+        to_chain: &ChainType,
+    ) -> Result<(), String> {
+        let route = BridgeRoute {
+            from_chain: from_chain.clone(),
+            to_chain: to_chain.clone(),
+        };
+        match self.route_trips.get(&route_key(&route)) {
+            Some(paused) => Err(format!("Bridge route is paused: {}", paused.trip.reason)),
+            None => Ok(()),
+        }
+    }
+
+    fn check_volume_limits(
+        &self,
+        user_principal: Principal,
+        from_chain: &ChainType,
+        asset_type: &str,
+        amount: u128,
+        now: u64,
+    ) -> Result<(), String> {
+        let asset = self.asset_config(from_chain, asset_type)?;
+        let window_start = now.saturating_sub(DAY_NS);
+        let recent: Vec<VolumeEntry> = self
+            .inflows
+            .iter()
+            .map(|(_, entry)| entry)
+            .filter(|entry| entry.at >= window_start)
+            .collect();
+
+        if recent.len() as u64 >= self.limits.get().max_daily_requests {
+            return Err("Global daily bridge request limit reached".to_string());
+        }
+
+        let asset_entries = recent.iter().filter(|entry| {
+            entry.route.from_chain == *from_chain && entry.asset_type == asset_type
+        });
+        let (asset_total, user_total) =
+            asset_entries.fold((amount, amount), |(asset_total, user_total), entry| {
+                let user_amount = if entry.user_principal == user_principal {
+                    entry.amount
+                } else {
+                    0
+                };
+                (
+                    asset_total.saturating_add(entry.amount),
+                    user_total.saturating_add(user_amount),
+                )
+            });
+
+        if user_total > asset.user_daily_limit {
+            return Err(format!(
+                "Daily limit for {} exceeded: {} remaining",
+                asset_type,
+                asset.user_daily_limit.saturating_sub(user_total - amount)
+            ));
+        }
+        if asset_total > asset.daily_limit {
+            return Err(format!("Daily bridge capacity for {} reached", asset_type));
+        }
+
+        Ok(())
+    }
+
+    // Trips the route's circuit breaker if releasing `request_id` would push
+    // the route's 24h outflow of the asset past the destination threshold.
+    pub fn check_release_outflow(
+        &mut self,
+        request_id: &str,
+        relayer: Principal,
+    ) -> Result<(), CircuitBreakerTrip> {
+        let now = time();
+        let (route, asset_type, amount) = match self.get_bridge_request(request_id) {
+            Some(request) => (
+                BridgeRoute::of(&request),
+                request.asset_type,
+                request.receive_amount,
+            ),
+            None => return Ok(()),
+        };
+        if let Some(paused) = self.route_trips.get(&route_key(&route)) {
+            return Err(paused.trip);
+        }
+
+        let threshold = match self.asset_config(&route.to_chain, &asset_type) {
+            Ok(asset) => asset.outflow_breaker_threshold,
+            Err(_) => return Ok(()),
+        };
+
+        prune_volume(&mut self.outflows, now.saturating_sub(DAY_NS));
+        let total = self
+            .outflows
+            .iter()
+            .map(|(_, entry)| entry)
+            .filter(|entry| entry.route == route && entry.asset_type == asset_type)
+            .fold(amount, |total, entry| total.saturating_add(entry.amount));
+
+        if total > threshold {
+            return Err(self.trip_route(
+                route,
+                format!("24h outflow of {} exceeded {}", asset_type, threshold),
+                relayer,
+            ));
+        }
+        Ok(())
+    }
+
+    // A relayer saw something on chain that disagrees with our records;
+    // stop the request's route until an admin has looked at it.
+    pub fn report_mismatch(
+        &mut self,
+        request_id: &str,
+        details: &str,
+        relayer: Principal,
+    ) -> Result<(BridgeRoute, CircuitBreakerTrip), String> {
+        if !self.is_relayer(&relayer) {
+            return Err("Only bridge relayers can report mismatches".to_string());
+        }
+        if details.is_empty() || details.len() > 1000 {
+            return Err("Mismatch details must be 1-1000 characters".to_string());
+        }
+
+        let route = self
+            .get_bridge_request(request_id)
+            .map(|request| BridgeRoute::of(&request))
+            .ok_or("Bridge request not found")?;
+        let trip = self.trip_route(
+            route.clone(),
+            format!("Relayer reported mismatch on {}: {}", request_id, details),
+            relayer,
+        );
+        Ok((route, trip))
+    }
+
+    fn trip_route(
+        &mut self,
+        route: BridgeRoute,
+        reason: String,
+        tripped_by: Principal,
+    ) -> CircuitBreakerTrip {
+        let key = route_key(&route);
+        if let Some(paused) = self.route_trips.get(&key) {
+            return paused.trip;
+        }
+        let trip = CircuitBreakerTrip {
+            reason,
+            tripped_by,
+            tripped_at: time(),
+        };
+        self.route_trips.insert(
+            key,
+            RouteTrip {
+                route,
+                trip: trip.clone(),
+            },
+        );
+        trip
+    }
+
+    // Clears the breaker and the route's outflow window.
+    pub fn reset_route(&mut self, route: &BridgeRoute) -> Option<CircuitBreakerTrip> {
+        let cleared: Vec<String> = self
+            .outflows
+            .iter()
+            .filter(|(_, entry)| entry.route == *route)
+            .map(|(request_id, _)| request_id)
+            .collect();
+        for request_id in cleared {
+            self.outflows.remove(&request_id);
+        }
+        self.route_trips
+            .remove(&route_key(route))
+            .map(|paused| paused.trip)
+    }
+
+    pub fn route_statuses(&self) -> Vec<RouteStatus> {
+        let window_start = time().saturating_sub(DAY_NS);
+        let inflows: Vec<VolumeEntry> = self.inflows.iter().map(|(_, entry)| entry).collect();
+        let outflows: Vec<VolumeEntry> = self.outflows.iter().map(|(_, entry)| entry).collect();
+        let mut routes: Vec<BridgeRoute> = self
+            .route_trips
+            .iter()
+            .map(|(_, paused)| paused.route)
+            .collect();
+        for entry in inflows.iter().chain(outflows.iter()) {
+            if entry.at >= window_start && !routes.contains(&entry.route) {
+                routes.push(entry.route.clone());
+            }
+        }
+
+        let totals = |entries: &[VolumeEntry], route: &BridgeRoute| {
+            let mut volumes: Vec<AssetVolume> = Vec::new();
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.at >= window_start && entry.route == *route)
+            {
+                match volumes
+                    .iter_mut()
+                    .find(|volume| volume.asset_type == entry.asset_type)
+                {
+                    Some(volume) => volume.amount = volume.amount.saturating_add(entry.amount),
+                    None => volumes.push(AssetVolume {
+                        asset_type: entry.asset_type.clone(),
+                        amount: entry.amount,
+                    }),
+                }
+            }
+            volumes
+        };
+
+        routes
+            .into_iter()
+            .map(|route| RouteStatus {
+                trip: self
+                    .route_trips
+                    .get(&route_key(&route))
+                    .map(|paused| paused.trip),
+                inflow_24h: totals(&inflows, &route),
+                outflow_24h: totals(&outflows, &route),
+                route,
+            })
+            .collect()
+    }
+
+    pub fn calculate_bridge_fee(
+        &self,
+        from_chain: &ChainType,
+        asset_type: &str,
+        amount: u128,
+    ) -> Result<BridgeFee, String> {
+        let config = self
+            .chain_config_for(from_chain)
+            .ok_or("Source chain configuration not found")?;
+        let asset = config.asset(asset_type).ok_or(format!(
+            "Asset {} not supported on source chain",
+            asset_type
+        ))?;
+
+        let percentage_fee = amount
+            .checked_mul(config.fee_bps as u128)
+            .ok_or("Bridge fee overflow")?
+            / BPS_DENOMINATOR;
+        let total = percentage_fee
+            .checked_add(asset.fixed_fee)
+            .ok_or("Bridge fee overflow")?;
+
+        Ok(BridgeFee {
+            amount: total,
+            fee_bps: config.fee_bps,
+            fixed_fee: asset.fixed_fee,
+        })
+    }
+
+    fn asset_config(&self, chain: &ChainType, asset_type: &str) -> Result<AssetConfig, String> {
+        self.chain_config_for(chain)
+            .and_then(|config| config.asset(asset_type).cloned())
+            .ok_or(format!(
+                "Asset {} is not registered on {:?}",
+                asset_type, chain
+            ))
+    }
+
+    pub fn get_supported_chains(&self) -> Vec<ChainConfig> {
+        self.chain_configs
+            .iter()
+            .map(|(_, config)| config)
+            .collect()
+    }
+
+    fn validate_bridge_request(
+        &self,
+        from_chain: &ChainType,
+        to_chain: &ChainType,
+        asset_type: &str,
+        amount: u128,
+    ) -> Result<(), String> {
+        if from_chain == to_chain {
+            return Err("Source and destination chains cannot be the same".to_string());
+        }
+
+        self.ensure_route_open(from_chain, to_chain)?;
+
+        let from_config = self
+            .chain_config_for(from_chain)
+            .ok_or("Source chain configuration not found")?;
+        if !from_config.enabled {
+            return Err("Source chain is disabled".to_string());
+        }
+
+        let to_config = self
+            .chain_config_for(to_chain)
+            .ok_or("Destination chain configuration not found")?;
+        if !to_config.enabled {
+            return Err("Destination chain is disabled".to_string());
+        }
+        if to_config.asset(asset_type).is_none() {
+            return Err(format!(
+                "Asset {} not supported on destination chain",
+                asset_type
+            ));
+        }
+
+        let asset = from_config.asset(asset_type).ok_or(format!(
+            "Asset {} not supported on source chain",
+            asset_type
+        ))?;
+
+        // Releases pay out on the destination chain, refunds on the source
+        self.payout_kind(to_chain, asset_type)?;
+        self.payout_kind(from_chain, asset_type)?;
+
+        if amount < asset.min_amount {
+            return Err(format!("Amount below minimum: {}", asset.min_amount));
+        }
+
+        if amount > asset.max_amount {
+            return Err(format!("Amount exceeds maximum: {}", asset.max_amount));
+        }
+
+        Ok(())
+    }
+}
+
+// EVM addresses are hex and compared case-insensitively (checksum casing is
+// optional); everything else is case-sensitive.
+pub fn same_address(chain: &ChainType, a: &str, b: &str) -> bool {
+    match chain {
+        ChainType::Ethereum | ChainType::Polygon | ChainType::Avalanche => {
+            a.eq_ignore_ascii_case(b)
+        }
+        _ => a == b,
+    }
+}
+
+// Drops volume entries that fell out of the rolling window.
+fn prune_volume(entries: &mut StableBTreeMap<String, VolumeEntry, Memory>, window_start: u64) {
+    let expired: Vec<String> = entries
+        .iter()
+        .filter(|(_, entry)| entry.at < window_start)
+        .map(|(request_id, _)| request_id)
+        .collect();
+    for request_id in expired {
+        entries.remove(&request_id);
+    }
+}
+
+fn update_route_stats(
+    route_stats: &mut StableBTreeMap<String, RouteStatsAccumulator, Memory>,
+    route: BridgeRoute,
+    update: impl FnOnce(&mut RouteStatsAccumulator),
+) {
+    let key = route_key(&route);
+    let mut stats = route_stats
+        .get(&key)
+        .unwrap_or_else(|| RouteStatsAccumulator::new(route));
+    update(&mut stats);
+    route_stats.insert(key, stats);
+}
+
+// When the request entered its current status.
+fn status_since(request: &BridgeRequest) -> u64 {
+    request
+        .status_history
+        .last()
+        .map_or(request.created_at, |t| t.changed_at)
+}
+
+fn route_key(route: &BridgeRoute) -> String {
+    format!(
+        "{}->{}",
+        chain_key(&route.from_chain),
+        chain_key(&route.to_chain)
+    )
+}
+
+// Key of a chain in the stable chain config map; ChainType has no ordering.
+fn chain_key(chain: &ChainType) -> String {
+    format!("{:?}", chain)
+}
+
+// Zero-padded so that keys sort by creation time.
+fn created_key(created_at: u64, request_id: &str) -> String {
+    format!("{:020}:{}", created_at, request_id)
+}
+
+fn history_key(user: &Principal, created_at: u64, request_id: &str) -> String {
+    format!("{}/{}", user.to_text(), created_key(created_at, request_id))
+}
+
+// The one accepted spelling of a transaction hash on `chain`: 64 lowercase
+// hex digits for Bitcoin, the same with a `0x` prefix on EVM chains and a
+// base58 64-byte signature on Solana. Anything else is rejected, so the same
+// transaction cannot be submitted under several keys.
+fn canonical_tx_hash(chain: &ChainType, tx_hash: &str) -> Result<String, String> {
+    let is_hex_hash = |hex: &str| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit());
+    let canonical = match rpc_flavor(chain) {
+        Some(RpcFlavor::Esplora) if is_hex_hash(tx_hash) => Some(tx_hash.to_lowercase()),
+        Some(RpcFlavor::EvmJsonRpc) => tx_hash
+            .strip_prefix("0x")
+            .filter(|hex| is_hex_hash(hex))
+            .map(|hex| format!("0x{}", hex.to_lowercase())),
+        Some(RpcFlavor::SolanaJsonRpc) => bs58::decode(tx_hash)
+            .into_vec()
+            .ok()
+            .filter(|signature| signature.len() == 64)
+            .map(|signature| bs58::encode(signature).into_string()),
+        _ => None,
+    };
+    canonical.ok_or_else(|| "Invalid transaction hash".to_string())
+}
+
+// Key of a canonical deposit transaction hash in the consumed set.
+fn deposit_key(chain: &ChainType, tx_hash: &str) -> String {
+    format!("{:?}:{}", chain, tx_hash)
+}
+
+pub fn native_asset(chain: &ChainType) -> Option<&'static str> {
+    match chain {
+        ChainType::Bitcoin => Some("BTC"),
+        ChainType::Ethereum => Some("ETH"),
+        ChainType::Solana => Some("SOL"),
+        ChainType::Polygon => Some("POL"),
+        ChainType::Avalanche => Some("AVAX"),
+        ChainType::ICP => Some("ICP"),
+        ChainType::Custom { .. } => None,
+    }
+}
+
+const BPS_DENOMINATOR: u128 = 10_000;
+const MAX_FEE_BPS: u16 = 1_000; // 10%
+const MAX_ASSET_DECIMALS: u8 = 30;
+
+pub fn validate_chain_config(config: &ChainConfig) -> Result<(), String> {
+    if let ChainType::Custom { name, .. } = &config.chain_type {
+        if name.trim().is_empty() {
+            return Err("Custom chain name is required".to_string());
+        }
+    }
+
+    if !config.rpc_url.starts_with("https://") {
+        return Err("RPC URL must use https".to_string());
+    }
+
+    if config.bridge_contract.trim().is_empty() {
+        return Err("Bridge contract address is required".to_string());
+    }
+
+    if config.fee_bps > MAX_FEE_BPS {
+        return Err(format!("Fee must not exceed {} basis points", MAX_FEE_BPS));
+    }
+
+    if config.confirmation_blocks == 0 {
+        return Err("Confirmation blocks must be at least 1".to_string());
+    }
+
+    let is_evm = rpc_flavor(&config.chain_type) == Some(RpcFlavor::EvmJsonRpc);
+    if is_evm != config.evm_chain_id.is_some_and(|id| id > 0) {
+        return Err("EVM chains, and only EVM chains, need an EVM chain id".to_string());
+    }
+
+    if config.supported_assets.is_empty() {
+        return Err("At least one supported asset is required".to_string());
+    }
+
+    for (index, asset) in config.supported_assets.iter().enumerate() {
+        if asset.symbol.trim().is_empty() {
+            return Err("Asset symbol is required".to_string());
+        }
+        if config.supported_assets[..index]
+            .iter()
+            .any(|other| other.symbol == asset.symbol)
+        {
+            return Err(format!("Duplicate asset {}", asset.symbol));
+        }
+        if asset.decimals > MAX_ASSET_DECIMALS {
+            return Err(format!("Asset {} has too many decimals", asset.symbol));
+        }
+        if !is_evm && native_asset(&config.chain_type).is_some_and(|native| native != asset.symbol)
+        {
+            return Err(format!(
+                "Asset {} cannot be paid out on a non-EVM chain",
+                asset.symbol
+            ));
+        }
+        if asset.min_amount == 0 || asset.min_amount > asset.max_amount {
+            return Err(format!(
+                "Asset {} must have 0 < min_amount <= max_amount",
+                asset.symbol
+            ));
+        }
+        if asset.user_daily_limit < asset.min_amount || asset.user_daily_limit > asset.daily_limit {
+            return Err(format!(
+                "Asset {} must have min_amount <= user_daily_limit <= daily_limit",
+                asset.symbol
+            ));
+        }
+        if asset.outflow_breaker_threshold == 0 {
+            return Err(format!(
+                "Asset {} needs a non-zero outflow breaker threshold",
+                asset.symbol
+            ));
+        }
+        // The percentage fee plus fixed fee must leave something to bridge
+        let min_fee = asset.min_amount * config.fee_bps as u128 / BPS_DENOMINATOR;
+        if asset.fixed_fee.saturating_add(min_fee) >= asset.min_amount {
+            return Err(format!(
+                "Asset {} fees exceed its minimum amount",
+                asset.symbol
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Converts `amount` between decimal precisions, truncating any dust.
+pub fn scale_amount(amount: u128, from_decimals: u8, to_decimals: u8) -> Result<u128, String> {
+    if to_decimals >= from_decimals {
+        10u128
+            .checked_pow((to_decimals - from_decimals) as u32)
+            .and_then(|factor| amount.checked_mul(factor))
+            .ok_or("Amount overflow while converting decimals".to_string())
+    } else {
+        Ok(10u128
+            .checked_pow((from_decimals - to_decimals) as u32)
+            .map_or(0, |factor| amount / factor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::release_tx::ReleaseGuard;
+    use ic_stable_structures::memory_manager::{MemoryId, MemoryManager};
+    use ic_stable_structures::DefaultMemoryImpl;
+
+    fn all_statuses() -> Vec<BridgeStatus> {
+        vec![
+            BridgeStatus::Initiated,
+            BridgeStatus::SourceLocked,
+            BridgeStatus::TargetMinting,
+            BridgeStatus::Completed,
+            BridgeStatus::Failed {
+                reason: "failed".to_string(),
+            },
+            BridgeStatus::Cancelled,
+            BridgeStatus::RefundPending {
+                reason: "stalled".to_string(),
+            },
+            BridgeStatus::Refunded,
+        ]
+    }
+
+    fn name(status: &BridgeStatus) -> &'static str {
+        match status {
+            BridgeStatus::Initiated => "Initiated",
+            BridgeStatus::SourceLocked => "SourceLocked",
+            BridgeStatus::TargetMinting => "TargetMinting",
+            BridgeStatus::Completed => "Completed",
+            BridgeStatus::Failed { .. } => "Failed",
+            BridgeStatus::Cancelled => "Cancelled",
+            BridgeStatus::RefundPending { .. } => "RefundPending",
+            BridgeStatus::Refunded => "Refunded",
+        }
+    }
+
+    fn service() -> BridgeService {
+        service_on(&MemoryManager::init(DefaultMemoryImpl::default()))
+    }
+
+    fn service_on(manager: &MemoryManager<DefaultMemoryImpl>) -> BridgeService {
+        BridgeService::new(BridgeMemories {
+            relayers: manager.get(MemoryId::new(0)),
+            consumed_deposits: manager.get(MemoryId::new(1)),
+            chain_configs: manager.get(MemoryId::new(2)),
+            used_quotes: manager.get(MemoryId::new(3)),
+            route_trips: manager.get(MemoryId::new(4)),
+            inflows: manager.get(MemoryId::new(5)),
+            outflows: manager.get(MemoryId::new(6)),
+            route_timeouts: manager.get(MemoryId::new(7)),
+            route_stats: manager.get(MemoryId::new(8)),
+            limits: manager.get(MemoryId::new(9)),
+            access: manager.get(MemoryId::new(10)),
+            requests: manager.get(MemoryId::new(11)),
+            user_history: manager.get(MemoryId::new(12)),
+            by_created: manager.get(MemoryId::new(13)),
+        })
+    }
+
+    fn request(status: BridgeStatus) -> BridgeRequest {
+        BridgeRequest {
+            request_id: "bridge_1".to_string(),
+            identity_id: "gt_id_1".to_string(),
+            from_chain: ChainType::Bitcoin,
+            to_chain: ChainType::Ethereum,
+            asset_type: "BTC".to_string(),
+            amount: 100_000,
+            from_address: "bc1qsource".to_string(),
+            to_address: "0xdestination".to_string(),
+            user_principal: Principal::anonymous(),
+            status,
+            created_at: 0,
+            completed_at: None,
+            transaction_hashes: Vec::new(),
+            status_history: Vec::new(),
+            source_tx_hash: None,
+            release_transaction: None,
+            quote_id: "quote".to_string(),
+            fee: 1_000,
+            receive_amount: 98_000,
+            release_broadcast: None,
+            refund_transaction: None,
+            refund_broadcast: None,
+            refund_claimed_at: None,
+        }
+    }
+
+    #[test]
+    fn allows_only_the_documented_edges() {
+        let allowed = [
+            ("Initiated", "SourceLocked"),
+            ("Initiated", "Failed"),
+            ("Initiated", "Cancelled"),
+            ("SourceLocked", "TargetMinting"),
+            ("SourceLocked", "Failed"),
+            ("SourceLocked", "RefundPending"),
+            ("TargetMinting", "Completed"),
+            ("TargetMinting", "Failed"),
+            ("TargetMinting", "RefundPending"),
+            ("RefundPending", "Refunded"),
+        ];
+        for from in all_statuses() {
+            for to in all_statuses() {
+                assert_eq!(
+                    from.can_transition_to(&to),
+                    allowed.contains(&(name(&from), name(&to))),
+                    "{} -> {}",
+                    name(&from),
+                    name(&to)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_statuses_have_no_outgoing_edges() {
+        for from in all_statuses().iter().filter(|s| s.is_terminal()) {
+            assert!(all_statuses().iter().all(|to| !from.can_transition_to(to)));
+        }
+    }
+
+    #[test]
+    fn rejects_illegal_relayer_transitions() {
+        let relayer = Principal::from_slice(&[1]);
+        let mut service = service();
+        service.add_relayer(relayer);
+        service.insert_request(request(BridgeStatus::Completed));
+
+        let result = service.update_bridge_status(
+            "bridge_1",
+            BridgeStatus::Failed {
+                reason: "late".to_string(),
+            },
+            None,
+            relayer,
+        );
+        assert_eq!(
+            result.err(),
+            Some(
+                "Invalid bridge status transition: Completed -> Failed { reason: \"late\" }"
+                    .to_string()
+            )
+        );
+        assert!(matches!(
+            service.get_bridge_request("bridge_1").unwrap().status,
+            BridgeStatus::Completed
+        ));
+    }
+
+    #[test]
+    fn keeps_flows_with_dedicated_checks_off_the_manual_path() {
+        let relayer = Principal::from_slice(&[1]);
+        let mut service = service();
+        service.add_relayer(relayer);
+        service.insert_request(request(BridgeStatus::TargetMinting));
+
+        for status in [
+            BridgeStatus::Completed,
+            BridgeStatus::RefundPending {
+                reason: "stalled".to_string(),
+            },
+        ] {
+            assert_eq!(
+                service
+                    .update_bridge_status("bridge_1", status, None, relayer)
+                    .err(),
+                Some("Relayers can only mark bridge requests as failed".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_transitions_from_non_relayers() {
+        let mut service = service();
+        service.insert_request(request(BridgeStatus::Initiated));
+
+        let result = service.update_bridge_status(
+            "bridge_1",
+            BridgeStatus::SourceLocked,
+            None,
+            Principal::from_slice(&[2]),
+        );
+        assert_eq!(
+            result.err(),
+            Some("Only bridge relayers can update bridge status".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_reused_deposit_transactions() {
+        let owner = Principal::anonymous();
+        let mut service = service();
+        for id in ["bridge_1", "bridge_2"] {
+            let mut pending = request(BridgeStatus::Initiated);
+            pending.request_id = id.to_string();
+            service.insert_request(pending);
+        }
+
+        let txid = "ab".repeat(32);
+        service
+            .submit_deposit_transaction("bridge_1", owner, txid.to_uppercase())
+            .unwrap();
+        assert_eq!(
+            service.submit_deposit_transaction("bridge_2", owner, txid.clone()),
+            Err("Deposit transaction is already used by another request".to_string())
+        );
+
+        // Suffixes would reach the same transaction through the RPC URL
+        for spelling in [
+            format!("{}/", txid),
+            format!("{}?x", txid),
+            format!("{}#", txid),
+            format!("0x{}", txid),
+        ] {
+            assert_eq!(
+                service.submit_deposit_transaction("bridge_2", owner, spelling),
+                Err("Invalid transaction hash".to_string())
+            );
+        }
+
+        // Replacing the submission releases the first transaction
+        service
+            .submit_deposit_transaction("bridge_1", owner, "12".repeat(32))
+            .unwrap();
+        assert!(service
+            .submit_deposit_transaction("bridge_2", owner, txid)
+            .is_ok());
+    }
+
+    #[test]
+    fn accepts_only_canonical_transaction_hashes() {
+        let hash = "Ab".repeat(32);
+        assert_eq!(
+            canonical_tx_hash(&ChainType::Ethereum, &format!("0x{}", hash)),
+            Ok(format!("0x{}", hash.to_lowercase()))
+        );
+        assert!(canonical_tx_hash(&ChainType::Ethereum, &hash).is_err());
+        assert!(canonical_tx_hash(&ChainType::Bitcoin, &hash[2..]).is_err());
+
+        let signature = bs58::encode([7u8; 64]).into_string();
+        assert_eq!(
+            canonical_tx_hash(&ChainType::Solana, &signature),
+            Ok(signature.clone())
+        );
+        assert!(canonical_tx_hash(&ChainType::Solana, &format!("{}/", signature)).is_err());
+        assert!(
+            canonical_tx_hash(&ChainType::Solana, &bs58::encode([7u8; 32]).into_string()).is_err()
+        );
+    }
+
+    #[test]
+    fn rotates_deposit_checks_and_backs_off_missing_deposits() {
+        let mut service = service();
+        for (created_at, id) in [(1, "bridge_1"), (2, "bridge_2"), (3, "bridge_3")] {
+            let mut pending = request(BridgeStatus::Initiated);
+            pending.request_id = id.to_string();
+            pending.created_at = created_at;
+            pending.source_tx_hash = Some(format!("{}_tx", id));
+            service.insert_request(pending);
+        }
+        let ids = |service: &mut BridgeService, now| {
+            service
+                .pending_deposit_checks(now, 2)
+                .into_iter()
+                .map(|(r, _)| r.request_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(&mut service, 0), ["bridge_1", "bridge_2"]);
+        assert_eq!(ids(&mut service, 0), ["bridge_3", "bridge_1"]);
+
+        assert!(service
+            .record_deposit_miss("bridge_2", 0, Principal::anonymous())
+            .is_none());
+        assert_eq!(ids(&mut service, 0), ["bridge_3", "bridge_1"]);
+        assert_eq!(
+            ids(&mut service, DEPOSIT_LOOKUP_RETRY_MAX_NS),
+            ["bridge_2", "bridge_3"]
+        );
+    }
+
+    #[test]
+    fn refunds_stalled_releases_only_after_an_on_chain_check() {
+        let mut service = service();
+        let mut minting = request(BridgeStatus::TargetMinting);
+        minting.release_transaction = Some(SignedRelease {
+            chain: ChainType::Ethereum,
+            raw_transaction: vec![0x02, 0xf8],
+            tx_hash: "0xrelease".to_string(),
+            signed_at: 0,
+            guard: ReleaseGuard::EvmNonce {
+                sender: "0xbridge".to_string(),
+                nonce: 7,
+            },
+        });
+        minting.release_broadcast = Some(BroadcastState {
+            attempts: MAX_RELEASE_BROADCAST_ATTEMPTS,
+            next_attempt_at: 0,
+            last_error: Some("nonce too low".to_string()),
+            broadcast_at: None,
+        });
+        service.insert_request(minting);
+        let due = |service: &BridgeService, now| {
+            service
+                .pending_release_checks(now, 10)
+                .into_iter()
+                .map(|(request_id, ..)| request_id)
+                .collect::<Vec<_>>()
+        };
+
+        // Exhausted broadcasts alone do not refund the release
+        assert_eq!(due(&service, 0), ["bridge_1"]);
+        assert!(service
+            .record_release_check("bridge_1", ReleaseCheck::Pending, 0, Principal::anonymous())
+            .is_none());
+        assert!(matches!(
+            service.get_bridge_request("bridge_1").unwrap().status,
+            BridgeStatus::TargetMinting
+        ));
+        assert!(due(&service, 0).is_empty());
+        assert_eq!(due(&service, RELEASE_CHECK_INTERVAL_NS), ["bridge_1"]);
+
+        let view = service
+            .get_bridge_request("bridge_1")
+            .unwrap()
+            .public_view();
+        assert!(view.release_transaction.unwrap().raw_transaction.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_cursors() {
+        let owner = Principal::anonymous();
+        let mut service = service();
+        service.insert_request(request(BridgeStatus::Initiated));
+        let filter = BridgeHistoryFilter {
+            status: None,
+            from_chain: None,
+            to_chain: None,
+            asset_type: None,
+            created_after: None,
+            created_before: None,
+            user_principal: None,
+        };
+
+        assert_eq!(
+            service
+                .get_user_bridge_history(owner, &filter, Some("bridge_1"), 10)
+                .map(|page| page.requests.len()),
+            Ok(0)
+        );
+        assert_eq!(
+            service
+                .get_user_bridge_history(owner, &filter, Some("bridge_2"), 10)
+                .err(),
+            Some("Unknown cursor".to_string())
+        );
+        assert_eq!(
+            service
+                .list_bridge_requests(&filter, Some("bridge_2"), 10)
+                .err(),
+            Some("Unknown cursor".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_requests_across_upgrades() {
+        let owner = Principal::anonymous();
+        let manager = MemoryManager::init(DefaultMemoryImpl::default());
+        let mut service = service_on(&manager);
+        for (created_at, id, user) in [
+            (1, "bridge_1", owner),
+            (2, "bridge_2", Principal::from_slice(&[9])),
+            (3, "bridge_3", owner),
+        ] {
+            let mut pending = request(BridgeStatus::RefundPending {
+                reason: "stalled".to_string(),
+            });
+            pending.request_id = id.to_string();
+            pending.created_at = created_at;
+            pending.user_principal = user;
+            service.insert_request(pending);
+        }
+        service.claim_refund("bridge_1", owner, 5).unwrap();
+
+        // An upgrade rebuilds the service on the same memories
+        let service = service_on(&manager);
+        assert_eq!(
+            service
+                .get_bridge_request("bridge_1")
+                .unwrap()
+                .refund_claimed_at,
+            Some(5)
+        );
+        let ids = |page: BridgeRequestPage| {
+            page.requests
+                .into_iter()
+                .map(|request| request.request_id)
+                .collect::<Vec<_>>()
+        };
+        let filter = BridgeHistoryFilter::default();
+        assert_eq!(
+            ids(service
+                .get_user_bridge_history(owner, &filter, None, 10)
+                .unwrap()),
+            ["bridge_3", "bridge_1"]
+        );
+        assert_eq!(
+            ids(service
+                .list_bridge_requests(&filter, Some("bridge_3"), 10)
+                .unwrap()),
+            ["bridge_2", "bridge_1"]
+        );
+    }
+
+    #[test]
+    fn refunds_are_claimed_by_owners_and_signed_by_relayers() {
+        let owner = Principal::anonymous();
+        let relayer = Principal::from_slice(&[1]);
+        let mut service = service();
+        service.add_relayer(relayer);
+        service.insert_request(request(BridgeStatus::RefundPending {
+            reason: "stalled".to_string(),
+        }));
+
+        assert_eq!(
+            service.refund_to_sign("bridge_1", relayer).err(),
+            Some("Refund has not been claimed".to_string())
+        );
+        assert_eq!(
+            service.claim_refund("bridge_1", relayer, 0),
+            Err("Only the request owner can claim a refund".to_string())
+        );
+        service.claim_refund("bridge_1", owner, 0).unwrap();
+        assert_eq!(
+            service.refund_to_sign("bridge_1", owner).err(),
+            Some("Only bridge relayers can sign refunds".to_string())
+        );
+        assert!(service.refund_to_sign("bridge_1", relayer).is_ok());
+    }
+
+    #[test]
+    fn rejects_routes_without_a_payout() {
+        let mut service = service();
+        let ethereum = service.chain_config_for(&ChainType::Ethereum).unwrap();
+        let usdc = ethereum.asset("USDC").unwrap().clone();
+
+        // Configs stored before tokens were restricted to EVM chains
+        let mut solana = service.chain_config_for(&ChainType::Solana).unwrap();
+        solana.supported_assets.push(usdc.clone());
+        assert_eq!(
+            validate_chain_config(&solana),
+            Err("Asset USDC cannot be paid out on a non-EVM chain".to_string())
+        );
+        service
+            .chain_configs
+            .insert(chain_key(&ChainType::Solana), solana);
+
+        let amount = usdc.min_amount;
+        let unsupported = Err("Bridge cannot pay out USDC on Solana".to_string());
+        assert_eq!(
+            service.validate_bridge_request(
+                &ChainType::Ethereum,
+                &ChainType::Solana,
+                "USDC",
+                amount
+            ),
+            unsupported
+        );
+        assert_eq!(
+            service.validate_bridge_request(
+                &ChainType::Solana,
+                &ChainType::Ethereum,
+                "USDC",
+                amount
+            ),
+            unsupported
+        );
+        assert!(service
+            .validate_bridge_request(&ChainType::Bitcoin, &ChainType::Ethereum, "BTC", 100_000)
+            .is_ok());
+    }
 }
@@ -0,0 +1,720 @@
+use crate::{BridgeRequest, ChainConfig, ChainType, ReleaseGuard, SignedRelease};
+use base64::Engine;
+use ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse, TransformContext,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Name of the canister query used as the HTTPS outcall transform.
+pub const RPC_TRANSFORM_METHOD: &str = "transform_chain_rpc";
+
+const MAX_RESPONSE_BYTES: u64 = 64 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RpcFlavor {
+    /// Blockstream/Esplora REST API (Bitcoin)
+    Esplora,
+    /// Esplora `block-height/{height}`, whether a Bitcoin block exists yet
+    EsploraBlock,
+    /// Ethereum-style JSON-RPC (Ethereum, Polygon, Avalanche C-chain)
+    EvmJsonRpc,
+    /// Solana JSON-RPC
+    SolanaJsonRpc,
+    /// `eth_getBlockTransactionCountByNumber`, whether an EVM block exists yet
+    EvmBlock,
+    /// `getBlocksWithLimit`, whether Solana produced a block at or after a slot
+    SolanaBlock,
+    /// Esplora `POST /tx` broadcast of a raw Bitcoin transaction
+    EsploraBroadcast,
+    /// `eth_sendRawTransaction`
+    EvmBroadcast,
+    /// Solana `sendTransaction`
+    SolanaBroadcast,
+    /// Esplora `tx/{txid}/outspend/{vout}`, what spent a release input
+    EsploraOutspend,
+    /// Release receipt and the sender's finalized nonce
+    EvmRelease,
+    /// Release signature status and whether its blockhash is still valid
+    SolanaRelease,
+}
+
+pub fn rpc_flavor(chain: &ChainType) -> Option<RpcFlavor> {
+    match chain {
+        ChainType::Bitcoin => Some(RpcFlavor::Esplora),
+        ChainType::Ethereum | ChainType::Polygon | ChainType::Avalanche => {
+            Some(RpcFlavor::EvmJsonRpc)
+        }
+        ChainType::Solana => Some(RpcFlavor::SolanaJsonRpc),
+        _ => None,
+    }
+}
+
+/// Passed to the transform so that every replica reduces its raw RPC
+/// response to the same canonical outcome. Nothing that depends on the
+/// chain tip goes into the outcome, as replicas see different tips.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RpcTransformContext {
+    pub flavor: RpcFlavor,
+    pub deposit_address: String,
+    /// The release being checked, for release status lookups
+    pub release: Option<ReleaseContext>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReleaseContext {
+    pub tx_hash: String,
+    pub nonce: Option<u64>, // EVM only
+}
+
+/// What the source chain reports about a deposit transaction, restricted to
+/// outputs paying the bridge deposit address.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DepositObservation {
+    pub from_addresses: Vec<String>,
+    pub amount: u128,
+    /// Block (slot on Solana) that included the transaction, if any
+    pub block_height: Option<u64>,
+    /// False if the transaction was included but reverted or failed
+    pub succeeded: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RpcOutcome {
+    Deposit(DepositObservation),
+    /// The node does not know the deposit transaction
+    NotFound,
+    /// Whether the probed block height has been reached
+    BlockReached(bool),
+    /// The node accepted the transaction or already knew it
+    Broadcast,
+    Release(ReleaseCheck),
+    Error(String),
+}
+
+/// Whether a signed release can still pay out.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReleaseCheck {
+    /// Not on chain, but nothing else has consumed its guard either
+    Pending,
+    /// Included and succeeded
+    Included,
+    /// Its guard was consumed by another transaction, or it was included but
+    /// failed; it can never pay out
+    Invalidated,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DepositCheck {
+    Pending,
+    NotFound,
+    Confirmed,
+    Mismatch(String),
+}
+
+/// Builds the outcall that looks up `tx_hash` on the request's source chain.
+pub fn deposit_lookup_request(
+    config: &ChainConfig,
+    tx_hash: &str,
+) -> Result<CanisterHttpRequestArgument, String> {
+    let flavor = rpc_flavor(&config.chain_type).ok_or("Source chain has no RPC support")?;
+    let context = RpcTransformContext {
+        flavor,
+        deposit_address: config.bridge_contract.clone(),
+        release: None,
+    };
+
+    let (method, url, body) = match flavor {
+        RpcFlavor::Esplora => (
+            HttpMethod::GET,
+            format!("{}/tx/{}", config.rpc_url.trim_end_matches('/'), tx_hash),
+            None,
+        ),
+        RpcFlavor::EvmJsonRpc => (
+            HttpMethod::POST,
+            config.rpc_url.clone(),
+            Some(json!([
+                {"jsonrpc": "2.0", "id": 1, "method": "eth_getTransactionByHash", "params": [tx_hash]},
+                {"jsonrpc": "2.0", "id": 2, "method": "eth_getTransactionReceipt", "params": [tx_hash]}
+            ])),
+        ),
+        RpcFlavor::SolanaJsonRpc => (
+            HttpMethod::POST,
+            config.rpc_url.clone(),
+            Some(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "getTransaction", "params": [
+                    tx_hash,
+                    {"encoding": "jsonParsed", "commitment": "confirmed", "maxSupportedTransactionVersion": 0}
+                ]
+            })),
+        ),
+        _ => return Err("Unsupported lookup flavor".to_string()),
+    };
+
+    Ok(build_request(method, url, body.map(json_body), &context))
+}
+
+/// Builds the outcall that submits a signed payout to `config`'s chain.
+/// Every replica sends it, so nodes answering "already known" count as
+/// success.
+pub fn broadcast_request(
+    config: &ChainConfig,
+    raw_transaction: &[u8],
+) -> Result<CanisterHttpRequestArgument, String> {
+    let flavor = match rpc_flavor(&config.chain_type) {
+        Some(RpcFlavor::Esplora) => RpcFlavor::EsploraBroadcast,
+        Some(RpcFlavor::EvmJsonRpc) => RpcFlavor::EvmBroadcast,
+        Some(RpcFlavor::SolanaJsonRpc) => RpcFlavor::SolanaBroadcast,
+        _ => return Err("Chain has no broadcast support".to_string()),
+    };
+    let context = RpcTransformContext {
+        flavor,
+        deposit_address: config.bridge_contract.clone(),
+        release: None,
+    };
+
+    let (url, body) = match flavor {
+        RpcFlavor::EsploraBroadcast => (
+            format!("{}/tx", config.rpc_url.trim_end_matches('/')),
+            (hex::encode(raw_transaction).into_bytes(), "text/plain"),
+        ),
+        RpcFlavor::EvmBroadcast => (
+            config.rpc_url.clone(),
+            json_body(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "eth_sendRawTransaction",
+                "params": [format!("0x{}", hex::encode(raw_transaction))]
+            })),
+        ),
+        _ => (
+            config.rpc_url.clone(),
+            json_body(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "sendTransaction",
+                "params": [
+                    base64::engine::general_purpose::STANDARD.encode(raw_transaction),
+                    {"encoding": "base64"}
+                ]
+            })),
+        ),
+    };
+
+    Ok(build_request(HttpMethod::POST, url, Some(body), &context))
+}
+
+fn json_body(value: Value) -> (Vec<u8>, &'static str) {
+    (value.to_string().into_bytes(), "application/json")
+}
+
+/// Height of the block that gives a transaction included at `block_height`
+/// `required` confirmations.
+pub fn confirmation_height(block_height: u64, required: u64) -> u64 {
+    block_height.saturating_add(required.saturating_sub(1))
+}
+
+/// Builds the outcall that asks whether `config`'s chain has produced a
+/// block at `height`. Confirmation depth is decided from this after
+/// consensus rather than by comparing against each replica's own tip.
+pub fn block_probe_request(
+    config: &ChainConfig,
+    height: u64,
+) -> Result<CanisterHttpRequestArgument, String> {
+    let flavor = match rpc_flavor(&config.chain_type) {
+        Some(RpcFlavor::Esplora) => RpcFlavor::EsploraBlock,
+        Some(RpcFlavor::EvmJsonRpc) => RpcFlavor::EvmBlock,
+        Some(RpcFlavor::SolanaJsonRpc) => RpcFlavor::SolanaBlock,
+        _ => return Err("Chain has no RPC support".to_string()),
+    };
+    let context = RpcTransformContext {
+        flavor,
+        deposit_address: config.bridge_contract.clone(),
+        release: None,
+    };
+
+    let request = match flavor {
+        RpcFlavor::EsploraBlock => build_request(
+            HttpMethod::GET,
+            format!(
+                "{}/block-height/{}",
+                config.rpc_url.trim_end_matches('/'),
+                height
+            ),
+            None,
+            &context,
+        ),
+        RpcFlavor::EvmBlock => build_request(
+            HttpMethod::POST,
+            config.rpc_url.clone(),
+            Some(json_body(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "eth_getBlockTransactionCountByNumber",
+                "params": [format!("0x{:x}", height)]
+            }))),
+            &context,
+        ),
+        _ => build_request(
+            HttpMethod::POST,
+            config.rpc_url.clone(),
+            Some(json_body(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "getBlocksWithLimit",
+                "params": [height, 1, {"commitment": "confirmed"}]
+            }))),
+            &context,
+        ),
+    };
+    Ok(request)
+}
+
+/// Builds the outcalls that decide whether `release` can still confirm on
+/// `config`'s chain: one per spent input on Bitcoin, a single batch
+/// elsewhere. Nonces and blockhashes are read at finalized commitment so a
+/// release mined between the two lookups is never reported invalidated.
+pub fn release_check_requests(
+    config: &ChainConfig,
+    release: &SignedRelease,
+) -> Result<Vec<CanisterHttpRequestArgument>, String> {
+    let flavor = match (rpc_flavor(&config.chain_type), &release.guard) {
+        (Some(RpcFlavor::Esplora), ReleaseGuard::BitcoinInputs { .. }) => {
+            RpcFlavor::EsploraOutspend
+        }
+        (Some(RpcFlavor::EvmJsonRpc), ReleaseGuard::EvmNonce { .. }) => RpcFlavor::EvmRelease,
+        (Some(RpcFlavor::SolanaJsonRpc), ReleaseGuard::SolanaBlockhash { .. }) => {
+            RpcFlavor::SolanaRelease
+        }
+        _ => return Err("Release does not match the chain configuration".to_string()),
+    };
+    let mut context = RpcTransformContext {
+        flavor,
+        deposit_address: config.bridge_contract.clone(),
+        release: Some(ReleaseContext {
+            tx_hash: release.tx_hash.clone(),
+            nonce: None,
+        }),
+    };
+
+    let requests = match &release.guard {
+        ReleaseGuard::BitcoinInputs { utxos } => utxos
+            .iter()
+            .map(|utxo| {
+                let mut txid = utxo.txid.clone();
+                txid.reverse();
+                build_request(
+                    HttpMethod::GET,
+                    format!(
+                        "{}/tx/{}/outspend/{}",
+                        config.rpc_url.trim_end_matches('/'),
+                        hex::encode(txid),
+                        utxo.vout
+                    ),
+                    None,
+                    &context,
+                )
+            })
+            .collect(),
+        ReleaseGuard::EvmNonce { sender, nonce } => {
+            context.release = Some(ReleaseContext {
+                tx_hash: release.tx_hash.clone(),
+                nonce: Some(*nonce),
+            });
+            vec![build_request(
+                HttpMethod::POST,
+                config.rpc_url.clone(),
+                Some(json_body(json!([
+                    {"jsonrpc": "2.0", "id": 1, "method": "eth_getTransactionReceipt", "params": [release.tx_hash]},
+                    {"jsonrpc": "2.0", "id": 2, "method": "eth_getTransactionCount", "params": [sender, "finalized"]}
+                ]))),
+                &context,
+            )]
+        }
+        ReleaseGuard::SolanaBlockhash { recent_blockhash } => vec![build_request(
+            HttpMethod::POST,
+            config.rpc_url.clone(),
+            Some(json_body(json!([
+                {"jsonrpc": "2.0", "id": 1, "method": "getSignatureStatuses", "params": [
+                    [release.tx_hash], {"searchTransactionHistory": true}
+                ]},
+                {"jsonrpc": "2.0", "id": 2, "method": "isBlockhashValid", "params": [
+                    recent_blockhash, {"commitment": "finalized"}
+                ]}
+            ]))),
+            &context,
+        )],
+    };
+    Ok(requests)
+}
+
+fn build_request(
+    method: HttpMethod,
+    url: String,
+    body: Option<(Vec<u8>, &str)>,
+    context: &RpcTransformContext,
+) -> CanisterHttpRequestArgument {
+    let mut headers = vec![HttpHeader {
+        name: "Accept".to_string(),
+        value: "application/json".to_string(),
+    }];
+    if let Some((_, content_type)) = &body {
+        headers.push(HttpHeader {
+            name: "Content-Type".to_string(),
+            value: content_type.to_string(),
+        });
+    }
+
+    CanisterHttpRequestArgument {
+        url,
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        method,
+        headers,
+        body: body.map(|(bytes, _)| bytes),
+        transform: Some(TransformContext::from_name(
+            RPC_TRANSFORM_METHOD.to_string(),
+            serde_json::to_vec(context).unwrap_or_default(),
+        )),
+    }
+}
+
+/// Cycles to attach to an outcall on a 13-node subnet.
+pub fn outcall_cycles(request: &CanisterHttpRequestArgument) -> u128 {
+    const NODES: u128 = 13;
+    let request_bytes = (request.url.len()
+        + request.body.as_ref().map_or(0, |b| b.len())
+        + request
+            .headers
+            .iter()
+            .map(|h| h.name.len() + h.value.len())
+            .sum::<usize>()) as u128;
+    let response_bytes = request.max_response_bytes.unwrap_or(2_000_000) as u128;
+
+    (3_000_000 + 60_000 * NODES) * NODES
+        + 400 * NODES * request_bytes
+        + 800 * NODES * response_bytes
+}
+
+/// Reduces a raw RPC response to a canonical, replica-independent body.
+pub fn transform_rpc_response(response: HttpResponse, context: &[u8]) -> HttpResponse {
+    let outcome = match serde_json::from_slice::<RpcTransformContext>(context) {
+        // Broadcast rejections arrive with error statuses but still need
+        // inspecting for "already known"
+        Ok(context) if is_broadcast(context.flavor) => {
+            parse_broadcast(response.status == 200u16, &response.body, &context)
+        }
+        Ok(context) if response.status == 200u16 => parse_rpc_response(&response.body, &context),
+        // Esplora answers 404 for unknown transactions and future blocks
+        Ok(context) if response.status == 404u16 && context.flavor == RpcFlavor::Esplora => {
+            RpcOutcome::NotFound
+        }
+        Ok(context) if response.status == 404u16 && context.flavor == RpcFlavor::EsploraBlock => {
+            RpcOutcome::BlockReached(false)
+        }
+        Ok(_) => RpcOutcome::Error("RPC returned a non-success status".to_string()),
+        Err(_) => RpcOutcome::Error("Invalid transform context".to_string()),
+    };
+
+    HttpResponse {
+        status: 200u16.into(),
+        headers: Vec::new(),
+        body: serde_json::to_vec(&outcome).unwrap_or_default(),
+    }
+}
+
+pub fn parse_rpc_response(body: &[u8], context: &RpcTransformContext) -> RpcOutcome {
+    let parsed = match context.flavor {
+        RpcFlavor::Esplora => parse_esplora_tx(body, context).map(RpcOutcome::Deposit),
+        RpcFlavor::EvmJsonRpc => parse_evm_batch(body, context),
+        RpcFlavor::SolanaJsonRpc => parse_solana_tx(body, context),
+        RpcFlavor::EsploraBlock | RpcFlavor::EvmBlock | RpcFlavor::SolanaBlock => {
+            parse_block_probe(body, context.flavor).map(RpcOutcome::BlockReached)
+        }
+        RpcFlavor::EsploraBroadcast | RpcFlavor::EvmBroadcast | RpcFlavor::SolanaBroadcast => {
+            return parse_broadcast(true, body, context)
+        }
+        RpcFlavor::EsploraOutspend | RpcFlavor::EvmRelease | RpcFlavor::SolanaRelease => {
+            parse_release_check(body, context).map(RpcOutcome::Release)
+        }
+    };
+    parsed.unwrap_or_else(RpcOutcome::Error)
+}
+
+fn is_broadcast(flavor: RpcFlavor) -> bool {
+    matches!(
+        flavor,
+        RpcFlavor::EsploraBroadcast | RpcFlavor::EvmBroadcast | RpcFlavor::SolanaBroadcast
+    )
+}
+
+// Error messages nodes return for a transaction they have already seen.
+const ALREADY_KNOWN_ERRORS: [&str; 4] = [
+    "already known",
+    "already in block chain",
+    "already processed",
+    "txn-already-known",
+];
+
+fn parse_broadcast(success_status: bool, body: &[u8], context: &RpcTransformContext) -> RpcOutcome {
+    let text = String::from_utf8_lossy(body).to_lowercase();
+    if ALREADY_KNOWN_ERRORS
+        .iter()
+        .any(|known| text.contains(known))
+    {
+        return RpcOutcome::Broadcast;
+    }
+    if !success_status {
+        return RpcOutcome::Error("Broadcast rejected".to_string());
+    }
+
+    match context.flavor {
+        // Esplora answers a successful broadcast with the plain txid
+        RpcFlavor::EsploraBroadcast => RpcOutcome::Broadcast,
+        _ => match serde_json::from_slice::<Value>(body) {
+            Ok(response) if response["error"].is_null() && !response["result"].is_null() => {
+                RpcOutcome::Broadcast
+            }
+            Ok(response) => RpcOutcome::Error(format!(
+                "Broadcast rejected: {}",
+                response["error"]["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+            )),
+            Err(_) => RpcOutcome::Error("Malformed broadcast response".to_string()),
+        },
+    }
+}
+
+/// Compares an observed deposit, already at the configured confirmation
+/// depth, against the bridge request it should fund.
+pub fn check_deposit(request: &BridgeRequest, observation: &DepositObservation) -> DepositCheck {
+    if !observation.succeeded {
+        return DepositCheck::Mismatch("Deposit transaction failed on chain".to_string());
+    }
+
+    let case_insensitive = matches!(rpc_flavor(&request.from_chain), Some(RpcFlavor::EvmJsonRpc));
+    let sender_matches = observation.from_addresses.iter().any(|address| {
+        if case_insensitive {
+            address.eq_ignore_ascii_case(&request.from_address)
+        } else {
+            *address == request.from_address
+        }
+    });
+
+    if !sender_matches {
+        return DepositCheck::Mismatch(
+            "Deposit was not sent from the request's source address".to_string(),
+        );
+    }
+
+    if observation.amount != request.amount {
+        return DepositCheck::Mismatch(format!(
+            "Deposit amount {} does not match requested amount {}",
+            observation.amount, request.amount
+        ));
+    }
+
+    DepositCheck::Confirmed
+}
+
+fn parse_esplora_tx(
+    body: &[u8],
+    context: &RpcTransformContext,
+) -> Result<DepositObservation, String> {
+    let tx: Value = serde_json::from_slice(body).map_err(|_| "Malformed Esplora response")?;
+
+    let from_addresses = tx["vin"]
+        .as_array()
+        .ok_or("Esplora transaction has no inputs")?
+        .iter()
+        .filter_map(|vin| vin["prevout"]["scriptpubkey_address"].as_str())
+        .map(str::to_string)
+        .collect();
+
+    let amount = tx["vout"]
+        .as_array()
+        .ok_or("Esplora transaction has no outputs")?
+        .iter()
+        .filter(|vout| vout["scriptpubkey_address"].as_str() == Some(&context.deposit_address))
+        .filter_map(|vout| vout["value"].as_u64())
+        .map(|value| value as u128)
+        .sum();
+
+    let block_height = if tx["status"]["confirmed"].as_bool() == Some(true) {
+        tx["status"]["block_height"].as_u64()
+    } else {
+        None
+    };
+
+    Ok(DepositObservation {
+        from_addresses,
+        amount,
+        block_height,
+        succeeded: true,
+    })
+}
+
+fn parse_block_probe(body: &[u8], flavor: RpcFlavor) -> Result<bool, String> {
+    if flavor == RpcFlavor::EsploraBlock {
+        // A 200 response carries the block hash
+        return Ok(true);
+    }
+
+    let response: Value =
+        serde_json::from_slice(body).map_err(|_| "Malformed JSON-RPC response")?;
+    if !response["error"].is_null() {
+        return Err("Block lookup failed".to_string());
+    }
+    Ok(match flavor {
+        RpcFlavor::EvmBlock => !response["result"].is_null(),
+        _ => response["result"]
+            .as_array()
+            .is_some_and(|blocks| !blocks.is_empty()),
+    })
+}
+
+fn parse_release_check(body: &[u8], context: &RpcTransformContext) -> Result<ReleaseCheck, String> {
+    let release = context
+        .release
+        .as_ref()
+        .ok_or("Release check without a release")?;
+
+    match context.flavor {
+        RpcFlavor::EsploraOutspend => {
+            let outspend: Value =
+                serde_json::from_slice(body).map_err(|_| "Malformed Esplora response")?;
+            let confirmed = outspend["status"]["confirmed"].as_bool() == Some(true);
+            Ok(match outspend["spent"].as_bool() {
+                Some(true) if !confirmed => ReleaseCheck::Pending,
+                Some(true) if outspend["txid"].as_str() == Some(&release.tx_hash) => {
+                    ReleaseCheck::Included
+                }
+                Some(true) => ReleaseCheck::Invalidated,
+                Some(false) => ReleaseCheck::Pending,
+                None => return Err("Malformed Esplora outspend".to_string()),
+            })
+        }
+        RpcFlavor::EvmRelease => {
+            let (receipt, count) = batch_results(body)?;
+            if !receipt.is_null() {
+                return Ok(if receipt["status"].as_str() == Some("0x1") {
+                    ReleaseCheck::Included
+                } else {
+                    ReleaseCheck::Invalidated
+                });
+            }
+            let count = parse_hex_u128(count.as_str().ok_or("Nonce lookup failed")?)?;
+            let nonce = release.nonce.ok_or("EVM release check without a nonce")?;
+            Ok(if count > nonce as u128 {
+                ReleaseCheck::Invalidated
+            } else {
+                ReleaseCheck::Pending
+            })
+        }
+        _ => {
+            let (statuses, blockhash) = batch_results(body)?;
+            let status = statuses["value"]
+                .get(0)
+                .ok_or("Signature status lookup failed")?;
+            if !status.is_null() {
+                return Ok(if !status["err"].is_null() {
+                    ReleaseCheck::Invalidated
+                } else if status["confirmationStatus"].as_str() == Some("finalized") {
+                    ReleaseCheck::Included
+                } else {
+                    ReleaseCheck::Pending
+                });
+            }
+            Ok(match blockhash["value"].as_bool() {
+                Some(false) => ReleaseCheck::Invalidated,
+                Some(true) => ReleaseCheck::Pending,
+                None => return Err("Blockhash lookup failed".to_string()),
+            })
+        }
+    }
+}
+
+fn parse_evm_batch(body: &[u8], context: &RpcTransformContext) -> Result<RpcOutcome, String> {
+    let (tx, receipt) = batch_results(body)?;
+    if tx.is_null() {
+        return Ok(RpcOutcome::NotFound);
+    }
+
+    let to = tx["to"].as_str().unwrap_or_default();
+    let amount = if to.eq_ignore_ascii_case(&context.deposit_address) {
+        parse_hex_u128(tx["value"].as_str().ok_or("Missing transaction value")?)?
+    } else {
+        0
+    };
+
+    // The receipt only exists once the transaction is mined
+    let block_height = match receipt["blockNumber"].as_str() {
+        Some(number) => Some(parse_hex_u128(number)? as u64),
+        None => None,
+    };
+
+    Ok(RpcOutcome::Deposit(DepositObservation {
+        from_addresses: tx["from"]
+            .as_str()
+            .map(str::to_string)
+            .into_iter()
+            .collect(),
+        amount,
+        block_height,
+        succeeded: receipt.is_null() || receipt["status"].as_str() == Some("0x1"),
+    }))
+}
+
+fn parse_solana_tx(body: &[u8], context: &RpcTransformContext) -> Result<RpcOutcome, String> {
+    let response: Value =
+        serde_json::from_slice(body).map_err(|_| "Malformed JSON-RPC response")?;
+    if !response["error"].is_null() {
+        return Err("Transaction lookup failed".to_string());
+    }
+    let tx = &response["result"];
+    if tx.is_null() {
+        return Ok(RpcOutcome::NotFound);
+    }
+
+    let mut from_addresses = Vec::new();
+    let mut amount: u128 = 0;
+    let instructions = tx["transaction"]["message"]["instructions"]
+        .as_array()
+        .ok_or("Transaction has no instructions")?;
+    for instruction in instructions {
+        let parsed = &instruction["parsed"];
+        if instruction["program"].as_str() != Some("system")
+            || parsed["type"].as_str() != Some("transfer")
+            || parsed["info"]["destination"].as_str() != Some(&context.deposit_address)
+        {
+            continue;
+        }
+        if let Some(source) = parsed["info"]["source"].as_str() {
+            from_addresses.push(source.to_string());
+        }
+        amount += parsed["info"]["lamports"].as_u64().unwrap_or(0) as u128;
+    }
+
+    Ok(RpcOutcome::Deposit(DepositObservation {
+        from_addresses,
+        amount,
+        block_height: Some(tx["slot"].as_u64().ok_or("Missing transaction slot")?),
+        succeeded: tx["meta"]["err"].is_null(),
+    }))
+}
+
+// Splits a two-element JSON-RPC batch response into its `id` 1 and 2 results.
+fn batch_results(body: &[u8]) -> Result<(Value, Value), String> {
+    let responses: Vec<Value> =
+        serde_json::from_slice(body).map_err(|_| "Malformed JSON-RPC batch response")?;
+
+    let result_for = |id: u64| {
+        responses
+            .iter()
+            .find(|r| r["id"].as_u64() == Some(id))
+            .map(|r| r["result"].clone())
+            .ok_or_else(|| format!("Missing JSON-RPC response {}", id))
+    };
+
+    Ok((result_for(1)?, result_for(2)?))
+}
+
+fn parse_hex_u128(value: &str) -> Result<u128, String> {
+    let digits = value.trim_start_matches("0x");
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(digits, 16).map_err(|_| format!("Invalid hex quantity {}", value))
+}
@@ -0,0 +1,128 @@
+use crate::{AMLStatus, ComplianceStatus, KYCLevel, SanctionsStatus};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+const DAY_NS: u64 = 24 * 3600 * 1_000_000_000;
+
+pub const MAX_EVIDENCE_REFERENCE_LENGTH: usize = 256;
+pub const MAX_KYC_EVIDENCE: usize = 20;
+
+// Reference to KYC material held by the provider (document ID, IPFS hash,
+// provider case number). The material itself never enters the canister.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct KycEvidence {
+    pub reference: String,
+    pub evidence_type: String, // e.g. "passport", "proof_of_address"
+    pub submitted_by: Principal,
+    pub submitted_at: u64,
+}
+
+// Who may change compliance data, and how long each check stays valid
+// before the identity must be screened again.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct CompliancePolicy {
+    pub kyc_providers: Vec<Principal>,
+    pub kyc_validity_ns: u64,
+    pub aml_validity_ns: u64,
+    pub sanctions_validity_ns: u64,
+    pub rescreening_notice_ns: u64, // How early identities enter the re-screening queue
+}
+
+impl Default for CompliancePolicy {
+    fn default() -> Self {
+        Self {
+            kyc_providers: Vec::new(),
+            kyc_validity_ns: 365 * DAY_NS,
+            aml_validity_ns: 180 * DAY_NS,
+            sanctions_validity_ns: 30 * DAY_NS,
+            rescreening_notice_ns: 7 * DAY_NS,
+        }
+    }
+}
+
+impl CompliancePolicy {
+    pub fn is_kyc_provider(&self, principal: &Principal) -> bool {
+        self.kyc_providers.contains(principal)
+    }
+}
+
+// What a downstream canister needs to gate an action on an identity's
+// compliance, with expired checks already reverted.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ComplianceAttestation {
+    pub identity_id: String,
+    pub did: String,
+    pub kyc_level: KYCLevel,
+    pub aml_status: AMLStatus,
+    pub sanctions_status: SanctionsStatus,
+    pub jurisdiction: String,
+    pub valid_until: Option<u64>, // Earliest expiry among current checks
+    pub issued_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RescreeningItem {
+    pub identity_id: String,
+    pub kyc_expires_at: Option<u64>,
+    pub aml_expires_at: Option<u64>,
+    pub sanctions_expires_at: Option<u64>,
+}
+
+impl ComplianceStatus {
+    // The status as of `now`: KYC falls back to None, AML to NotScreened and
+    // sanctions to NotChecked once their validity has run out.
+    pub fn effective(&self, now: u64) -> ComplianceStatus {
+        let expired = |expires_at: Option<u64>| expires_at.is_some_and(|at| now >= at);
+
+        let mut status = self.clone();
+        if expired(status.kyc_expires_at) {
+            status.kyc_level = KYCLevel::None;
+        }
+        if expired(status.aml_expires_at) {
+            status.aml_status = AMLStatus::NotScreened;
+        }
+        if expired(status.sanctions_expires_at) {
+            status.sanctions_check = SanctionsStatus::NotChecked;
+        }
+        status
+    }
+
+    // Earliest expiry among the checks that are still in effect.
+    pub fn valid_until(&self, now: u64) -> Option<u64> {
+        [
+            self.kyc_expires_at,
+            self.aml_expires_at,
+            self.sanctions_expires_at,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|at| *at > now)
+        .min()
+    }
+
+    // Whether any completed check has expired or will within `notice_ns`.
+    pub fn needs_rescreening(&self, now: u64, notice_ns: u64) -> bool {
+        [
+            self.kyc_expires_at,
+            self.aml_expires_at,
+            self.sanctions_expires_at,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|at| at <= now.saturating_add(notice_ns))
+    }
+
+    pub fn kyc_evidence(&self) -> &[KycEvidence] {
+        self.kyc_evidence.as_deref().unwrap_or_default()
+    }
+}
+
+pub fn validate_kyc_evidence(reference: &str, evidence_type: &str) -> Result<(), String> {
+    if reference.trim().is_empty() || reference.len() > MAX_EVIDENCE_REFERENCE_LENGTH {
+        return Err("Evidence reference must be 1-256 characters".to_string());
+    }
+    if evidence_type.trim().is_empty() || evidence_type.len() > 64 {
+        return Err("Evidence type must be 1-64 characters".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,430 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+const MB: usize = 1024 * 1024;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DetectedFileType {
+    Pdf,
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+    Doc,
+    Docx,
+    PlainText,
+    Csv,
+}
+
+impl DetectedFileType {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            DetectedFileType::Pdf => "application/pdf",
+            DetectedFileType::Jpeg => "image/jpeg",
+            DetectedFileType::Png => "image/png",
+            DetectedFileType::Gif => "image/gif",
+            DetectedFileType::WebP => "image/webp",
+            DetectedFileType::Doc => "application/msword",
+            DetectedFileType::Docx => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+            DetectedFileType::PlainText => "text/plain",
+            DetectedFileType::Csv => "text/csv",
+        }
+    }
+
+    pub fn max_size(&self) -> usize {
+        match self {
+            DetectedFileType::Pdf | DetectedFileType::Doc | DetectedFileType::Docx => 10 * MB,
+            DetectedFileType::Jpeg
+            | DetectedFileType::Png
+            | DetectedFileType::Gif
+            | DetectedFileType::WebP => 5 * MB,
+            DetectedFileType::PlainText | DetectedFileType::Csv => 2 * MB,
+        }
+    }
+}
+
+/// Result of validating an upload against its declared MIME type.
+pub struct ValidatedContent {
+    pub detected_type: DetectedFileType,
+    pub data: Vec<u8>,
+    pub metadata_stripped: bool,
+}
+
+/// Sniffs `data`, checks it against `declared_mime_type` and the per-type
+/// size cap, and removes EXIF/GPS and similar metadata from images.
+pub fn validate_content(
+    declared_mime_type: &str,
+    data: Vec<u8>,
+) -> Result<ValidatedContent, String> {
+    if data.is_empty() {
+        return Err("File is empty".to_string());
+    }
+
+    let detected_type = detect_file_type(&data, declared_mime_type)
+        .ok_or("Unrecognised or unsupported file content")?;
+
+    if detected_type.mime_type() != declared_mime_type {
+        return Err(format!(
+            "Declared type {} does not match detected type {}",
+            declared_mime_type,
+            detected_type.mime_type()
+        ));
+    }
+
+    if data.len() > detected_type.max_size() {
+        return Err(format!(
+            "File size exceeds maximum limit for {} ({}MB)",
+            detected_type.mime_type(),
+            detected_type.max_size() / MB
+        ));
+    }
+
+    let (data, metadata_stripped) = match detected_type {
+        DetectedFileType::Jpeg => strip_jpeg_metadata(&data)?,
+        DetectedFileType::Png => strip_png_metadata(&data)?,
+        DetectedFileType::WebP => strip_webp_metadata(&data)?,
+        _ => (data, false),
+    };
+
+    Ok(ValidatedContent {
+        detected_type,
+        data,
+        metadata_stripped,
+    })
+}
+
+/// Identifies the file type from magic bytes. Text content is reported as
+/// CSV only when the client declared CSV and the content is tabular.
+pub fn detect_file_type(data: &[u8], declared_mime_type: &str) -> Option<DetectedFileType> {
+    if data.starts_with(b"%PDF-") {
+        return Some(DetectedFileType::Pdf);
+    }
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(DetectedFileType::Jpeg);
+    }
+    if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Some(DetectedFileType::Png);
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some(DetectedFileType::Gif);
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some(DetectedFileType::WebP);
+    }
+    if data.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        return Some(DetectedFileType::Doc);
+    }
+    if data.starts_with(b"PK\x03\x04") {
+        // DOCX is a ZIP package whose part names include word/document.xml
+        return contains(data, b"word/document.xml").then_some(DetectedFileType::Docx);
+    }
+
+    let text = std::str::from_utf8(data).ok()?;
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return None;
+    }
+
+    if declared_mime_type == DetectedFileType::Csv.mime_type() && looks_like_csv(text) {
+        Some(DetectedFileType::Csv)
+    } else {
+        Some(DetectedFileType::PlainText)
+    }
+}
+
+fn looks_like_csv(text: &str) -> bool {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let columns = match lines.next() {
+        Some(header) => header.matches(',').count(),
+        None => return false,
+    };
+    columns > 0 && lines.all(|line| line.matches(',').count() == columns)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+// Drops APP1 (EXIF/XMP) and APP13 (IPTC) segments. Everything from the
+// start-of-scan marker onwards is copied verbatim.
+fn strip_jpeg_metadata(data: &[u8]) -> Result<(Vec<u8>, bool), String> {
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[0..2]);
+    let mut stripped = false;
+    let mut pos = 2;
+
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err("Malformed JPEG segment".to_string());
+        }
+        let marker = data[pos + 1];
+        if marker == 0xDA {
+            break;
+        }
+
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let end = pos + 2 + length;
+        if length < 2 || end > data.len() {
+            return Err("Malformed JPEG segment length".to_string());
+        }
+
+        if marker == 0xE1 || marker == 0xED {
+            stripped = true;
+        } else {
+            output.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+
+    output.extend_from_slice(&data[pos.min(data.len())..]);
+    Ok((output, stripped))
+}
+
+// Drops eXIf and textual (tEXt, iTXt, zTXt) chunks.
+fn strip_png_metadata(data: &[u8]) -> Result<(Vec<u8>, bool), String> {
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[0..8]);
+    let mut stripped = false;
+    let mut pos = 8;
+
+    while pos < data.len() {
+        if pos + 12 > data.len() {
+            return Err("Malformed PNG chunk".to_string());
+        }
+        let length =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        // The length comes from the file and may overflow on wasm32
+        let end = match (pos + 12).checked_add(length) {
+            Some(end) if end <= data.len() => end,
+            _ => return Err("Malformed PNG chunk length".to_string()),
+        };
+
+        if matches!(chunk_type, b"eXIf" | b"tEXt" | b"iTXt" | b"zTXt") {
+            stripped = true;
+        } else {
+            output.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+
+    Ok((output, stripped))
+}
+
+// Drops EXIF and XMP chunks, clears their VP8X flags and fixes the RIFF size.
+fn strip_webp_metadata(data: &[u8]) -> Result<(Vec<u8>, bool), String> {
+    const VP8X_XMP_FLAG: u8 = 0x04;
+    const VP8X_EXIF_FLAG: u8 = 0x08;
+
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[0..12]);
+    let mut stripped = false;
+    let mut pos = 12;
+
+    while pos < data.len() {
+        if pos + 8 > data.len() {
+            return Err("Malformed WebP chunk".to_string());
+        }
+        let chunk_type = &data[pos..pos + 4];
+        let length =
+            u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]])
+                as usize;
+        // Chunks are padded to an even size
+        let end = match (pos + 8)
+            .checked_add(length)
+            .and_then(|end| end.checked_add(length & 1))
+        {
+            Some(end) if end <= data.len() => end,
+            _ => return Err("Malformed WebP chunk length".to_string()),
+        };
+
+        match chunk_type {
+            b"EXIF" | b"XMP " => stripped = true,
+            b"VP8X" if length >= 1 => {
+                let flags_index = output.len() + 8;
+                output.extend_from_slice(&data[pos..end]);
+                output[flags_index] &= !(VP8X_EXIF_FLAG | VP8X_XMP_FLAG);
+            }
+            _ => output.extend_from_slice(&data[pos..end]),
+        }
+        pos = end;
+    }
+
+    let riff_size = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok((output, stripped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    fn png_chunk(chunk_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = (body.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(body);
+        chunk.extend_from_slice(&[0; 4]); // CRC is not checked
+        chunk
+    }
+
+    fn webp_chunk(chunk_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = chunk_type.to_vec();
+        chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn webp(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut file = b"RIFF".to_vec();
+        file.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+        file.extend_from_slice(b"WEBP");
+        file.extend_from_slice(&body);
+        file
+    }
+
+    #[test]
+    fn detects_types_by_magic_bytes() {
+        let cases: [(&[u8], DetectedFileType); 6] = [
+            (b"%PDF-1.7\n", DetectedFileType::Pdf),
+            (&[0xFF, 0xD8, 0xFF, 0xE0], DetectedFileType::Jpeg),
+            (&PNG_SIGNATURE, DetectedFileType::Png),
+            (b"GIF89a....", DetectedFileType::Gif),
+            (b"RIFF\x04\x00\x00\x00WEBP", DetectedFileType::WebP),
+            (
+                &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1],
+                DetectedFileType::Doc,
+            ),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(detect_file_type(data, "text/plain"), Some(expected));
+        }
+    }
+
+    #[test]
+    fn requires_document_part_for_docx() {
+        assert_eq!(
+            detect_file_type(b"PK\x03\x04....word/document.xml", "text/plain"),
+            Some(DetectedFileType::Docx)
+        );
+        assert_eq!(
+            detect_file_type(b"PK\x03\x04....other.xml", "text/plain"),
+            None
+        );
+    }
+
+    #[test]
+    fn detects_csv_only_when_declared_and_tabular() {
+        let csv = b"name,amount\nalice,1\nbob,2\n";
+        assert_eq!(
+            detect_file_type(csv, "text/csv"),
+            Some(DetectedFileType::Csv)
+        );
+        assert_eq!(
+            detect_file_type(csv, "text/plain"),
+            Some(DetectedFileType::PlainText)
+        );
+        assert_eq!(
+            detect_file_type(b"name,amount\nalice\n", "text/csv"),
+            Some(DetectedFileType::PlainText)
+        );
+        assert_eq!(detect_file_type(b"binary\x00data", "text/plain"), None);
+    }
+
+    #[test]
+    fn rejects_declared_type_mismatch() {
+        let result = validate_content("image/png", b"%PDF-1.7\n".to_vec());
+        assert_eq!(
+            result.err(),
+            Some(
+                "Declared type image/png does not match detected type application/pdf".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn strips_jpeg_exif_segments() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46]); // APP0
+        jpeg.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x04, 0x45, 0x78]); // APP1 (EXIF)
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x11, 0x22]); // SOS and scan data
+
+        let content = validate_content("image/jpeg", jpeg).unwrap();
+        assert!(content.metadata_stripped);
+        assert_eq!(
+            content.data,
+            vec![
+                0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46, 0xFF, 0xDA, 0x00, 0x02, 0x11, 0x22
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_png_text_and_exif_chunks() {
+        let ihdr = png_chunk(b"IHDR", &[0; 13]);
+        let iend = png_chunk(b"IEND", &[]);
+        let png = [
+            PNG_SIGNATURE.to_vec(),
+            ihdr.clone(),
+            png_chunk(b"tEXt", b"Author\0someone"),
+            png_chunk(b"eXIf", &[1, 2, 3]),
+            iend.clone(),
+        ]
+        .concat();
+
+        let content = validate_content("image/png", png).unwrap();
+        assert!(content.metadata_stripped);
+        assert_eq!(content.data, [PNG_SIGNATURE.to_vec(), ihdr, iend].concat());
+    }
+
+    #[test]
+    fn rejects_png_chunk_lengths_past_the_end() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&u32::MAX.to_be_bytes());
+        png.extend_from_slice(b"tEXt");
+        png.extend_from_slice(&[0; 4]);
+
+        assert_eq!(
+            validate_content("image/png", png).err(),
+            Some("Malformed PNG chunk length".to_string())
+        );
+    }
+
+    #[test]
+    fn strips_webp_exif_and_clears_vp8x_flags() {
+        let vp8x = webp_chunk(b"VP8X", &[0x0C, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let image = webp_chunk(b"VP8L", &[1, 2, 3]);
+        let original = webp(&[vp8x, image.clone(), webp_chunk(b"EXIF", &[4, 5])]);
+
+        let content = validate_content("image/webp", original).unwrap();
+        assert!(content.metadata_stripped);
+        let expected = webp(&[
+            webp_chunk(b"VP8X", &[0x00, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            image,
+        ]);
+        assert_eq!(content.data, expected);
+    }
+
+    #[test]
+    fn rejects_webp_chunk_lengths_past_the_end() {
+        let mut chunk = b"EXIF".to_vec();
+        chunk.extend_from_slice(&u32::MAX.to_le_bytes());
+        let original = webp(&[chunk]);
+
+        assert_eq!(
+            validate_content("image/webp", original).err(),
+            Some("Malformed WebP chunk length".to_string())
+        );
+    }
+}
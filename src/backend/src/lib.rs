@@ -7,17 +7,27 @@
 //! - Enhanced compliance tracking
 //! - AI verification hooks
 
+use ic_cdk::api::management_canister::ecdsa::{
+    ecdsa_public_key, sign_with_ecdsa, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument,
+    SignWithEcdsaArgument,
+};
+use ic_cdk::api::management_canister::http_request::{http_request, HttpResponse, TransformArgs};
 use ic_cdk::api::management_canister::main::raw_rand;
+use ic_cdk::api::management_canister::schnorr::{
+    schnorr_public_key, sign_with_schnorr, SchnorrAlgorithm, SchnorrKeyId,
+    SchnorrPublicKeyArgument, SignWithSchnorrArgument,
+};
 use ic_cdk::api::{caller, id, time};
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::time::Duration;
 
 use candid::{CandidType, Decode, Encode, Principal};
 use ic_cdk_macros::{export_candid, init, post_upgrade, pre_upgrade, query, update};
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
     storable::Bound,
-    DefaultMemoryImpl, StableBTreeMap, StableCell, Storable,
+    DefaultMemoryImpl, StableBTreeMap, StableCell, StableLog, Storable,
 };
 use serde::{Deserialize, Serialize};
 
@@ -27,10 +37,62 @@ use sha2::{Digest, Sha256};
 mod bridge;
 pub use bridge::*;
 
+// Source-chain RPC access for bridge deposit detection
+mod chain_rpc;
+pub use chain_rpc::*;
+
+// Destination-chain release transaction building
+mod release_tx;
+pub use release_tx::*;
+
+// Upload content sniffing and metadata stripping
+mod content;
+pub use content::*;
+
 // File storage module
 mod storage;
 pub use storage::*;
 
+// vetKD key derivation for the encrypted document vault
+mod vetkeys;
+pub use vetkeys::*;
+
+// Hash-chained audit log helpers
+mod audit_log;
+pub use audit_log::*;
+
+// KYC, AML and sanctions screening workflow
+mod compliance;
+pub use compliance::*;
+
+// Sanctioned address and name lists with fuzzy name matching
+mod sanctions;
+pub use sanctions::*;
+
+// Role-gated audit search and export
+mod audit_search;
+pub use audit_search::*;
+
+// Sliding-window rate limits, bans and per-principal overrides
+mod rate_limit;
+pub use rate_limit::*;
+
+// Per-jurisdiction policy rules
+mod policy;
+pub use policy::*;
+
+// Deterministic risk scoring
+mod risk;
+pub use risk::*;
+
+// Typed reputation events with decay and per-source caps
+mod reputation;
+pub use reputation::*;
+
+// Counterparty reports, reviewer decisions and appeals
+mod reports;
+pub use reports::*;
+
 // Memory management types
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -72,7 +134,7 @@ pub struct VerifiableCredential {
     pub status: CredentialStatus,
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum CredentialType {
     Government,
     Academic,
@@ -179,6 +241,8 @@ pub struct ReputationEvent {
     pub timestamp: u64,
     pub reason: String,
     pub verified_by: Option<Principal>,
+    pub source: Option<ReputationSource>, // None for events recorded before sources were typed
+    pub reference: Option<String>,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -199,7 +263,7 @@ pub struct LinkedWallet {
     pub linked_at: u64,
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ChainType {
     Bitcoin,
     Ethereum,
@@ -253,9 +317,13 @@ pub struct ComplianceStatus {
     pub last_updated: u64,
     pub jurisdiction: String,
     pub compliance_documents: Vec<String>, // IPFS hashes
+    pub kyc_evidence: Option<Vec<KycEvidence>>,
+    pub kyc_expires_at: Option<u64>,
+    pub aml_expires_at: Option<u64>,
+    pub sanctions_expires_at: Option<u64>,
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum KYCLevel {
     None,
     Basic,
@@ -303,6 +371,7 @@ pub struct RiskFactor {
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct AuditEntry {
     pub id: String,
+    pub sequence: u64, // Position in the append-only audit log
     pub timestamp: u64,
     pub principal: Principal,
     pub operation: AuditOperation,
@@ -310,9 +379,10 @@ pub struct AuditEntry {
     pub resource_type: String,
     pub details: AuditDetails,
     pub result: OperationResult,
+    pub previous_hash: Vec<u8>, // SHA-256 of the entry at `sequence - 1`
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum AuditOperation {
     CreateIdentity,
     UpdateIdentity,
@@ -325,16 +395,138 @@ pub enum AuditOperation {
     CrossChainVerification,
     AIVerification,
     ComplianceUpdate,
+    BridgeStatusChange,
+    BridgeCircuitBreaker,
+    BridgeRefund,
+    SanctionsScreening,
+    PolicyEnforcement,
+    IdentityReport,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct AuditDetails {
-    pub operation_specific_data: String, // JSON serialized data
+    pub payload: AuditPayload,
     pub sensitive_data_redacted: bool,
     pub related_entities: Vec<String>,
     pub compliance_notes: Option<String>,
 }
 
+// Operation-specific audit data. Serialized with serde_json for export.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum AuditPayload {
+    IdentityCreated {
+        credentials_count: u32,
+        ii_anchor: Option<u64>,
+    },
+    ReputationUpdated {
+        old_score: f64,
+        new_score: f64,
+        change: f64,
+    },
+    CredentialAdded,
+    WalletLinked {
+        chain_type: ChainType,
+        address: String,
+        signature_verified: bool,
+    },
+    WalletLinkRejected {
+        chain_type: ChainType,
+        address: String,
+        reason: String,
+    },
+    AssetLinked {
+        asset_id: String,
+        ai_request_id: Option<String>,
+    },
+    AssetVerified {
+        asset_id: String,
+        fraud_score: f64,
+        confidence: f64,
+    },
+    BridgeStatusChanged {
+        from: BridgeStatus,
+        to: BridgeStatus,
+        transaction_hash: Option<String>,
+    },
+    RefundClaimed {
+        claimed_at: u64,
+    },
+    RefundSigned {
+        chain: ChainType,
+        to_address: String,
+        amount: u128,
+        transaction_hash: String,
+    },
+    CircuitBreakerTripped {
+        reason: String,
+        tripped_by: Principal,
+    },
+    CircuitBreakerReset {
+        was_tripped: bool,
+    },
+    KycEvidenceSubmitted {
+        evidence_type: String,
+        reference: String,
+    },
+    KycLevelChanged {
+        from: KYCLevel,
+        to: KYCLevel,
+        expires_at: Option<u64>,
+    },
+    AmlScreened {
+        status: AMLStatus,
+        expires_at: Option<u64>,
+    },
+    SanctionsChecked {
+        status: SanctionsStatus,
+        expires_at: Option<u64>,
+    },
+    SanctionsAddressHit {
+        chain_type: ChainType,
+        address: String,
+        list: String,
+    },
+    SanctionsNameHit {
+        name: String,
+        listed_name: String,
+        list: String,
+        score: u8,
+    },
+    JurisdictionChanged {
+        from: String,
+        to: String,
+    },
+    PolicyViolated {
+        jurisdiction: String,
+        rule: PolicyRule,
+        message: String,
+    },
+    IdentityReported {
+        report_id: u64,
+        category: ReportCategory,
+        evidence_files: u32,
+    },
+    ReportResolved {
+        report_id: u64,
+        outcome: ReportOutcome,
+    },
+    ReportAppealed {
+        report_id: u64,
+    },
+    ReportAppealDecided {
+        report_id: u64,
+        granted: bool,
+    },
+    VerificationStatusChanged {
+        from: VerificationStatus,
+        to: VerificationStatus,
+    },
+    Redacted, // Withheld from the reader's role
+    Legacy {
+        raw: String, // Hand-formatted JSON recorded before payloads were typed
+    },
+}
+
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub enum OperationResult {
     Success,
@@ -363,6 +555,7 @@ pub struct RateLimitTracker {
     pub count: u32,
     pub window_start: u64,
     pub last_operation: u64,
+    pub previous_count: Option<u32>, // Count of the window before `window_start`
 }
 
 #[derive(CandidType, Deserialize, Debug)]
@@ -376,6 +569,8 @@ pub enum Error {
     EmergencyPause,
     InsufficientSignatures,
     OperationExpired,
+    PolicyViolation(PolicyViolation),
+    AlreadyRecorded, // A reputation event with the same source and reference exists
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -402,6 +597,65 @@ impl Storable for MultiSigOperation {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+pub const MAX_ADMINS: usize = 10;
+
+// Principals allowed to administer the canister; `threshold` of them must
+// sign a multi-sig operation before it executes.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AdminSet {
+    pub admins: Vec<Principal>,
+    pub threshold: u8,
+}
+
+impl Default for AdminSet {
+    fn default() -> Self {
+        Self {
+            admins: Vec::new(),
+            threshold: 1,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum AdminChange {
+    Add(Principal),
+    Remove(Principal),
+    SetThreshold(u8),
+}
+
+impl AdminSet {
+    // The set after `change`, keeping 1 <= threshold <= admins.
+    pub fn apply(&self, change: &AdminChange) -> Result<AdminSet, String> {
+        let mut updated = self.clone();
+        match change {
+            AdminChange::Add(admin) => {
+                if *admin == Principal::anonymous() {
+                    return Err("The anonymous principal cannot be an admin".to_string());
+                }
+                if updated.admins.contains(admin) {
+                    return Err("Principal is already an admin".to_string());
+                }
+                if updated.admins.len() >= MAX_ADMINS {
+                    return Err(format!("At most {} admins are allowed", MAX_ADMINS));
+                }
+                updated.admins.push(*admin);
+            }
+            AdminChange::Remove(admin) => {
+                if !updated.admins.contains(admin) {
+                    return Err("Principal is not an admin".to_string());
+                }
+                updated.admins.retain(|existing| existing != admin);
+            }
+            AdminChange::SetThreshold(threshold) => updated.threshold = *threshold,
+        }
+
+        if updated.threshold == 0 || updated.threshold as usize > updated.admins.len() {
+            return Err("Threshold must be between 1 and the number of admins".to_string());
+        }
+        Ok(updated)
+    }
+}
+
 //=============================================================================
 // GLOBAL STATE MANAGEMENT
 //=============================================================================
@@ -422,12 +676,46 @@ thread_local! {
         )
     );
 
-    static AUDIT_TRAIL: RefCell<StableBTreeMap<String, AuditEntry, Memory>> = RefCell::new(
+    static RATE_LIMIT_VIOLATIONS: RefCell<StableBTreeMap<String, ViolationRecord, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(41))),
+        )
+    );
+
+    // Pre-chain audit entries; drained into AUDIT_LOG on upgrade.
+    static LEGACY_AUDIT_TRAIL: RefCell<StableBTreeMap<String, LegacyAuditEntry, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2))),
         )
     );
 
+    // Append-only, hash-chained audit log indexed by sequence number.
+    static AUDIT_LOG: RefCell<StableLog<AuditEntry, Memory, Memory>> = RefCell::new(
+        StableLog::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12))),
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13))),
+        ).expect("Failed to init audit log")
+    );
+
+    // (resource ID, sequence) and (principal, sequence) keys into AUDIT_LOG
+    static AUDIT_BY_RESOURCE: RefCell<StableBTreeMap<AuditIndexKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14))),
+        )
+    );
+
+    static AUDIT_BY_PRINCIPAL: RefCell<StableBTreeMap<AuditIndexKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(15))),
+        )
+    );
+
+    static AUDIT_ROLES: RefCell<StableBTreeMap<Principal, AuditRole, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(16))),
+        )
+    );
+
     static RATE_LIMIT_CONFIG: RefCell<StableCell<RateLimitConfig, Memory>> = RefCell::new(
         StableCell::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))),
@@ -443,17 +731,127 @@ thread_local! {
         ).expect("Failed to init rate limit config")
     );
 
-    static BRIDGE_SERVICE: RefCell<BridgeService> = RefCell::new(BridgeService::new());
+    static BRIDGE_SERVICE: RefCell<BridgeService> = RefCell::new(BridgeService::new(BridgeMemories {
+        relayers: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(26))),
+        consumed_deposits: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(27))),
+        chain_configs: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(28))),
+        used_quotes: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(30))),
+        route_trips: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(31))),
+        inflows: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(32))),
+        outflows: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(33))),
+        route_timeouts: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(34))),
+        route_stats: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(35))),
+        limits: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(36))),
+        access: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(37))),
+        requests: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(38))),
+        user_history: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(39))),
+        by_created: MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(40))),
+    }));
 
     static FILE_STORAGE: RefCell<FileStorageService> = RefCell::new(FileStorageService::new());
 
+    static VETKD_CONFIG: RefCell<StableCell<VetKdConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(24))),
+            VetKdConfig::default(),
+        ).expect("Failed to init vetKD config")
+    );
+
     static EMERGENCY_PAUSE: RefCell<bool> = const { RefCell::new(false) };
 
+    static BRIDGE_WATCHER_RUNNING: RefCell<bool> = const { RefCell::new(false) };
+
+    // Heap-only; metrics restart from zero after an upgrade.
+    static JANITOR_STATE: RefCell<JanitorState> = RefCell::new(JanitorState::default());
+
+    // Threshold ECDSA / Schnorr master key used to sign bridge releases
+    static THRESHOLD_KEY_NAME: RefCell<StableCell<String, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(25))),
+            "dfx_test_key".to_string(),
+        ).expect("Failed to init threshold key name")
+    );
+
+    // Authenticates bridge quotes. Regenerated on upgrade, which invalidates
+    // outstanding quotes.
+    static QUOTE_SECRET: RefCell<Option<[u8; 32]>> = const { RefCell::new(None) };
+
     static MULTI_SIG_PENDING: RefCell<StableBTreeMap<String, MultiSigOperation, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9))),
         )
     );
+
+    static ADMIN_SET: RefCell<StableCell<AdminSet, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(29))),
+            AdminSet::default(),
+        ).expect("Failed to init admin set")
+    );
+
+    static RATE_LIMIT_POLICY: RefCell<StableCell<RateLimitPolicy, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11))),
+            RateLimitPolicy::default(),
+        ).expect("Failed to init rate limit policy")
+    );
+
+    static COMPLIANCE_POLICY: RefCell<StableCell<CompliancePolicy, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(17))),
+            CompliancePolicy::default(),
+        ).expect("Failed to init compliance policy")
+    );
+
+    // Keyed by `sanctions_address_key`
+    static SANCTIONED_ADDRESSES: RefCell<StableBTreeMap<String, SanctionsListing, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(18))),
+        )
+    );
+
+    // Keyed by `normalize_name`
+    static SANCTIONED_NAMES: RefCell<StableBTreeMap<String, SanctionsListing, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(19))),
+        )
+    );
+
+    static SANCTIONS_CONFIG: RefCell<StableCell<SanctionsConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(20))),
+            SanctionsConfig::default(),
+        ).expect("Failed to init sanctions config")
+    );
+
+    // Keyed by jurisdiction; DEFAULT_JURISDICTION applies where none matches
+    static JURISDICTION_POLICIES: RefCell<StableBTreeMap<String, JurisdictionPolicy, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(21))),
+        )
+    );
+
+    static REPUTATION_CONFIG: RefCell<StableCell<ReputationConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(22))),
+            ReputationConfig::default(),
+        ).expect("Failed to init reputation config")
+    );
+
+    // Keyed by report ID, assigned in increasing order
+    static IDENTITY_REPORTS: RefCell<StableBTreeMap<u64, IdentityReport, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(23))),
+        )
+    );
+
+    // Monotonic counter for file and bridge request IDs; never reset.
+    static ID_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(10))),
+            0,
+        ).expect("Failed to init ID counter")
+    );
 }
 
 //=============================================================================
@@ -480,6 +878,96 @@ impl Storable for RateLimitTracker {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl Storable for ViolationRecord {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SanctionsListing {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SanctionsConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for JurisdictionPolicy {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for ReputationConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for VetKdConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for IdentityReport {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for CompliancePolicy {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RateLimitPolicy {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 impl Storable for RateLimitConfig {
     fn to_bytes(&self) -> Cow<[u8]> {
         Cow::Owned(Encode!(self).unwrap())
@@ -500,6 +988,36 @@ impl Storable for AuditEntry {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl Storable for AuditIndexKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AuditRole {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for LegacyAuditEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 impl Storable for CrossChainSignature {
     fn to_bytes(&self) -> Cow<[u8]> {
         Cow::Owned(Encode!(self).unwrap())
@@ -530,6 +1048,56 @@ impl Storable for RiskAssessment {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl Storable for AdminSet {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RouteTimeouts {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for ChainConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for BridgeLimits {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for BridgeRequest {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 //=============================================================================
 // HELPER FUNCTIONS
 //=============================================================================
@@ -623,70 +1191,63 @@ fn validate_wallet_address(address: &str, chain_type: &ChainType) -> Result<()>
     Ok(())
 }
 
-fn check_rate_limit(operation_type: &str) -> Result<()> {
+// Two levels per principal: the operation's own hourly limit and a combined
+// limit across all operations. Exceeding either counts as a violation, and
+// repeated violations lead to temporary bans of increasing length.
+fn check_rate_limit(operation: RateLimitedOperation) -> Result<()> {
     let caller = caller();
-    let current_time = time();
-    let hour_in_ns = 3600 * 1_000_000_000; // 1 hour in nanoseconds
-
-    let rate_limits = RATE_LIMIT_CONFIG.with(|config| config.borrow().get().clone());
+    let now = time();
 
-    let max_operations = match operation_type {
-        "create_identity" => rate_limits.max_identity_creates_per_hour,
-        "add_credential" => rate_limits.max_credential_adds_per_hour,
-        "link_wallet" => rate_limits.max_wallet_links_per_hour,
-        "link_asset" => rate_limits.max_asset_links_per_hour,
-        "verification_request" => rate_limits.max_verification_requests_per_hour,
-        _ => 100, // Default limit
-    };
+    let config = RATE_LIMIT_CONFIG.with(|config| config.borrow().get().clone());
+    let policy = RATE_LIMIT_POLICY.with(|policy| policy.borrow().get().clone());
+    if policy.is_allow_listed(&caller) {
+        return Ok(());
+    }
 
-    let tracker_key = format!("{}:{}", caller, operation_type);
+    let violation_key = caller.to_text();
+    let mut violations = RATE_LIMIT_VIOLATIONS
+        .with(|violations| violations.borrow().get(&violation_key))
+        .unwrap_or_else(|| ViolationRecord::new(caller));
+    if violations.is_banned(now) {
+        return Err(Error::RateLimitExceeded);
+    }
 
     RATE_LIMITS.with(|limits| {
         let mut limits_map = limits.borrow_mut();
 
-        if let Some(mut tracker) = limits_map.get(&tracker_key) {
-            // Reset counter if window has passed
-            if current_time - tracker.window_start > hour_in_ns {
-                tracker.count = 0;
-                tracker.window_start = current_time;
+        let levels = [
+            (
+                Some(operation),
+                policy.limit_for(&caller, Some(operation), operation.default_limit(&config)),
+            ),
+            (
+                None,
+                policy.limit_for(&caller, None, policy.max_operations_per_hour),
+            ),
+        ];
+
+        let mut trackers = Vec::new();
+        for (level, limit) in levels {
+            let key = tracker_key(&caller, level);
+            let label = level.map_or("all", |op| op.label());
+            let mut tracker = limits_map
+                .get(&key)
+                .unwrap_or_else(|| new_tracker(caller, label, now));
+
+            if !try_consume(&mut tracker, limit, now) {
+                record_violation(&mut violations, &policy, now);
+                RATE_LIMIT_VIOLATIONS.with(|records| {
+                    records.borrow_mut().insert(violation_key, violations);
+                });
+                return Err(Error::RateLimitExceeded);
             }
+            trackers.push((key, tracker));
+        }
 
-            if tracker.count >= max_operations {
-                // Enhanced security: Add exponential backoff for repeated violations
-                let violation_key = format!("violation_{}", caller);
-                RATE_LIMITS.with(|vl| {
-                    let mut violation_tracker = RateLimitTracker {
-                        principal: caller,
-                        operation_type: "violations".to_string(),
-                        count: 1,
-                        window_start: current_time,
-                        last_operation: current_time,
-                    };
-
-                    if let Some(existing_violations) = vl.borrow().get(&violation_key) {
-                        violation_tracker.count = existing_violations.count + 1;
-                    }
-
-                    vl.borrow_mut().insert(violation_key, violation_tracker);
-                });
-                return Err(Error::RateLimitExceeded);
-            }
-
-            tracker.count += 1;
-            tracker.last_operation = current_time;
-            limits_map.insert(tracker_key, tracker);
-        } else {
-            // Create new tracker
-            let new_tracker = RateLimitTracker {
-                principal: caller,
-                operation_type: operation_type.to_string(),
-                count: 1,
-                window_start: current_time,
-                last_operation: current_time,
-            };
-            limits_map.insert(tracker_key, new_tracker);
+        // Only charge the quotas once every level has admitted the call
+        for (key, tracker) in trackers {
+            limits_map.insert(key, tracker);
         }
-
         Ok(())
     })
 }
@@ -715,6 +1276,46 @@ async fn generate_secure_random_id(prefix: &str) -> Result<String> {
     Ok(format!("{}_{:016x}_{}", prefix, timestamp, random_hex))
 }
 
+// IDs of the form `{prefix}_{counter}_{random}`. The stable counter makes
+// them unique even when several are issued in the same round by the same
+// caller; `raw_rand` keeps them unguessable. `exists` guards against reuse.
+async fn generate_unique_id(prefix: &str, exists: impl Fn(&str) -> bool) -> Result<String> {
+    let sequence = ID_COUNTER.with(|counter| {
+        let mut counter = counter.borrow_mut();
+        let next = *counter.get() + 1;
+        counter
+            .set(next)
+            .map_err(|e| Error::CanisterError(format!("Failed to advance ID counter: {:?}", e)))?;
+        Ok(next)
+    })?;
+
+    let (random_bytes,) = raw_rand().await.map_err(|e| {
+        Error::CanisterError(format!("Failed to generate secure random bytes: {:?}", e))
+    })?;
+
+    if random_bytes.len() < 8 {
+        return Err(Error::CanisterError(
+            "Insufficient random bytes generated".to_string(),
+        ));
+    }
+
+    let id = format!(
+        "{}_{:016x}_{}",
+        prefix,
+        sequence,
+        hex::encode(&random_bytes[0..8])
+    );
+
+    if exists(&id) {
+        return Err(Error::CanisterError(format!(
+            "Generated duplicate ID {}",
+            id
+        )));
+    }
+
+    Ok(id)
+}
+
 fn generate_did(identity_id: &str, owner: &Principal) -> Result<String> {
     let mut hasher = Sha256::new();
     hasher.update(identity_id.as_bytes());
@@ -726,36 +1327,30 @@ fn generate_did(identity_id: &str, owner: &Principal) -> Result<String> {
 }
 
 fn is_admin() -> Result<()> {
-    let config = RATE_LIMIT_CONFIG.with(|c| c.borrow().get().clone());
-    if caller() != config.admin {
+    if !current_admin_set().admins.contains(&caller()) {
         Err(Error::Unauthorized)
     } else {
         Ok(())
     }
 }
 
-fn emergency_pause_check() -> Result<()> {
-    if EMERGENCY_PAUSE.with(|p| *p.borrow()) {
-        return Err(Error::EmergencyPause);
+// Canisters upgraded from before the admin set existed fall back to the
+// single configured admin.
+fn current_admin_set() -> AdminSet {
+    let admin_set = ADMIN_SET.with(|admin_set| admin_set.borrow().get().clone());
+    if admin_set.admins.is_empty() {
+        let config = RATE_LIMIT_CONFIG.with(|c| c.borrow().get().clone());
+        return AdminSet {
+            admins: vec![config.admin],
+            threshold: 1,
+        };
     }
-    Ok(())
+    admin_set
 }
 
-fn validate_asset_value(value: f64) -> Result<()> {
-    if value <= 0.0 {
-        return Err(Error::InvalidInput(
-            "Asset value must be positive".to_string(),
-        ));
-    }
-    if value > 1_000_000_000.0 {
-        return Err(Error::InvalidInput(
-            "Asset value exceeds maximum limit".to_string(),
-        ));
-    }
-    if value.is_nan() || value.is_infinite() {
-        return Err(Error::InvalidInput(
-            "Asset value must be a valid number".to_string(),
-        ));
+fn emergency_pause_check() -> Result<()> {
+    if EMERGENCY_PAUSE.with(|p| *p.borrow()) {
+        return Err(Error::EmergencyPause);
     }
     Ok(())
 }
@@ -782,7 +1377,6 @@ fn validate_timestamp(timestamp: u64) -> Result<()> {
 async fn create_multi_sig_operation(
     operation_type: String,
     operation_data: String,
-    required_signatures: u8,
 ) -> Result<String> {
     let operation_id = generate_secure_random_id("multisig").await?;
     let current_time = time();
@@ -791,17 +1385,15 @@ async fn create_multi_sig_operation(
         id: operation_id.clone(),
         operation_type,
         operation_data,
-        required_signatures,
+        required_signatures: current_admin_set().threshold,
         signatures: vec![caller()], // Creator automatically signs
         created_at: current_time,
         expires_at: current_time + (24 * 3600 * 1_000_000_000), // 24 hours
         executed: false,
     };
 
-    MULTI_SIG_PENDING.with(|pending| {
-        pending.borrow_mut().insert(operation_id.clone(), operation);
-    });
-
+    // With a threshold of one the creator's signature is enough
+    execute_if_approved(operation)?;
     Ok(operation_id)
 }
 
@@ -812,55 +1404,133 @@ async fn sign_multi_sig_operation(operation_id: String) -> Result<bool> {
     // Only admins can sign multi-sig operations
     is_admin()?;
 
-    MULTI_SIG_PENDING.with(|pending| {
-        let mut pending_map = pending.borrow_mut();
+    let mut operation = MULTI_SIG_PENDING
+        .with(|pending| pending.borrow().get(&operation_id))
+        .ok_or_else(|| Error::NotFound("Multi-sig operation not found".to_string()))?;
 
-        if let Some(mut operation) = pending_map.get(&operation_id) {
-            // Check if operation has expired
-            if time() > operation.expires_at {
-                pending_map.remove(&operation_id);
-                return Err(Error::OperationExpired);
-            }
+    // Check if operation has expired
+    if time() > operation.expires_at {
+        MULTI_SIG_PENDING.with(|pending| pending.borrow_mut().remove(&operation_id));
+        return Err(Error::OperationExpired);
+    }
 
-            // Check if already executed
-            if operation.executed {
-                return Err(Error::InvalidInput(
-                    "Operation already executed".to_string(),
-                ));
-            }
+    // Check if already executed
+    if operation.executed {
+        return Err(Error::InvalidInput(
+            "Operation already executed".to_string(),
+        ));
+    }
 
-            // Add signature if not already present
-            if !operation.signatures.contains(&caller_principal) {
-                operation.signatures.push(caller_principal);
-            }
+    // Add signature if not already present
+    if !operation.signatures.contains(&caller_principal) {
+        operation.signatures.push(caller_principal);
+    }
 
-            let has_enough_signatures =
-                operation.signatures.len() as u8 >= operation.required_signatures;
+    execute_if_approved(operation)
+}
 
-            if has_enough_signatures {
-                operation.executed = true;
-                pending_map.insert(operation_id.clone(), operation.clone());
+// Stores `operation` and runs it once enough current admins have signed.
+// It is marked executed only after it succeeds, so a failed execution stays
+// pending and is retried by the next signature.
+fn execute_if_approved(mut operation: MultiSigOperation) -> Result<bool> {
+    let admin_set = current_admin_set();
+    let signed = operation
+        .signatures
+        .iter()
+        .filter(|signer| admin_set.admins.contains(signer))
+        .count();
+    let required = operation.required_signatures.max(admin_set.threshold) as usize;
 
-                // Execute the operation
-                match operation.operation_type.as_str() {
-                    "emergency_pause" => {
-                        EMERGENCY_PAUSE.with(|p| *p.borrow_mut() = true);
-                    }
-                    "emergency_unpause" => {
-                        EMERGENCY_PAUSE.with(|p| *p.borrow_mut() = false);
-                    }
-                    _ => return Err(Error::InvalidInput("Unknown operation type".to_string())),
-                }
+    MULTI_SIG_PENDING.with(|pending| {
+        pending
+            .borrow_mut()
+            .insert(operation.id.clone(), operation.clone())
+    });
+    if signed < required {
+        return Ok(false); // More signatures needed
+    }
 
-                Ok(true) // Operation executed
-            } else {
-                pending_map.insert(operation_id, operation);
-                Ok(false) // More signatures needed
-            }
-        } else {
-            Err(Error::NotFound("Multi-sig operation not found".to_string()))
+    execute_multi_sig_operation(&operation)?;
+    operation.executed = true;
+    MULTI_SIG_PENDING.with(|pending| pending.borrow_mut().insert(operation.id.clone(), operation));
+    Ok(true) // Operation executed
+}
+
+fn execute_multi_sig_operation(operation: &MultiSigOperation) -> Result<()> {
+    match operation.operation_type.as_str() {
+        "emergency_pause" => {
+            EMERGENCY_PAUSE.with(|p| *p.borrow_mut() = true);
         }
-    })
+        "emergency_unpause" => {
+            EMERGENCY_PAUSE.with(|p| *p.borrow_mut() = false);
+        }
+        "add_chain_config" | "update_chain_config" => {
+            let config: ChainConfig = serde_json::from_str(&operation.operation_data)
+                .map_err(|e| Error::InvalidInput(e.to_string()))?;
+            BRIDGE_SERVICE
+                .with(|service| {
+                    let mut service = service.borrow_mut();
+                    if operation.operation_type == "add_chain_config" {
+                        service.add_chain_config(config)
+                    } else {
+                        service.update_chain_config(config)
+                    }
+                })
+                .map_err(Error::InvalidInput)?;
+        }
+        "disable_chain" => {
+            let chain: ChainType = serde_json::from_str(&operation.operation_data)
+                .map_err(|e| Error::InvalidInput(e.to_string()))?;
+            BRIDGE_SERVICE
+                .with(|service| service.borrow_mut().disable_chain(&chain))
+                .map_err(Error::InvalidInput)?;
+        }
+        "admin_change" => {
+            let change: AdminChange = serde_json::from_str(&operation.operation_data)
+                .map_err(|e| Error::InvalidInput(e.to_string()))?;
+            let updated = current_admin_set()
+                .apply(&change)
+                .map_err(Error::InvalidInput)?;
+            ADMIN_SET.with(|admin_set| {
+                let _ = admin_set.borrow_mut().set(updated);
+            });
+        }
+        _ => return Err(Error::InvalidInput("Unknown operation type".to_string())),
+    }
+    Ok(())
+}
+
+// Admin membership and threshold changes go through the multi-sig like any
+// other privileged operation.
+async fn propose_admin_change(change: AdminChange) -> Result<String> {
+    is_admin()?;
+    current_admin_set()
+        .apply(&change)
+        .map_err(Error::InvalidInput)?;
+    let operation_data =
+        serde_json::to_string(&change).map_err(|e| Error::InvalidInput(e.to_string()))?;
+    create_multi_sig_operation("admin_change".to_string(), operation_data).await
+}
+
+#[update]
+async fn add_admin(admin: Principal) -> Result<String> {
+    propose_admin_change(AdminChange::Add(admin)).await
+}
+
+#[update]
+async fn remove_admin(admin: Principal) -> Result<String> {
+    propose_admin_change(AdminChange::Remove(admin)).await
+}
+
+#[update]
+async fn set_admin_threshold(threshold: u8) -> Result<String> {
+    propose_admin_change(AdminChange::SetThreshold(threshold)).await
+}
+
+#[query]
+fn get_admin_set() -> Result<AdminSet> {
+    is_admin()?;
+    Ok(current_admin_set())
 }
 
 #[update]
@@ -869,7 +1539,6 @@ async fn emergency_pause() -> Result<String> {
     create_multi_sig_operation(
         "emergency_pause".to_string(),
         "Emergency pause activated".to_string(),
-        2, // Require 2 admin signatures
     )
     .await
 }
@@ -880,7 +1549,6 @@ async fn emergency_unpause() -> Result<String> {
     create_multi_sig_operation(
         "emergency_unpause".to_string(),
         "Emergency pause deactivated".to_string(),
-        2, // Require 2 admin signatures
     )
     .await
 }
@@ -896,10 +1564,9 @@ fn create_audit_entry(
     details: AuditDetails,
     result: OperationResult,
 ) {
-    let audit_id = format!("audit_{}_{}", time(), caller());
-
-    let audit_entry = AuditEntry {
-        id: audit_id.clone(),
+    append_audit_entry(AuditEntry {
+        id: String::new(),
+        sequence: 0,
         timestamp: time(),
         principal: caller(),
         operation,
@@ -907,11 +1574,93 @@ fn create_audit_entry(
         resource_type,
         details,
         result,
-    };
+        previous_hash: Vec::new(),
+    });
+}
 
-    AUDIT_TRAIL.with(|trail| {
-        trail.borrow_mut().insert(audit_id, audit_entry);
+// Links `entry` to the current head of the log, appends it and indexes it.
+fn append_audit_entry(entry: AuditEntry) {
+    let entry = AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        let previous = log.len().checked_sub(1).and_then(|last| log.get(last));
+        let entry = link_entry(previous.as_ref(), entry);
+        log.append(&entry).expect("Failed to append audit entry");
+        entry
     });
+    certify_audit_head();
+
+    AUDIT_BY_RESOURCE.with(|index| {
+        index
+            .borrow_mut()
+            .insert(AuditIndexKey::new(&entry.resource_id, entry.sequence), ())
+    });
+    AUDIT_BY_PRINCIPAL.with(|index| {
+        index.borrow_mut().insert(
+            AuditIndexKey::new(&entry.principal.to_text(), entry.sequence),
+            (),
+        )
+    });
+}
+
+// Current length and head hash of the audit log.
+fn audit_head() -> (u64, Vec<u8>) {
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        match log.len().checked_sub(1).and_then(|last| log.get(last)) {
+            Some(head) => (log.len(), audit_entry_hash(&head)),
+            None => (0, GENESIS_HASH.to_vec()),
+        }
+    })
+}
+
+// Publishes the audit head as the canister's certified data, so a query
+// replica cannot serve a forked history without failing verification.
+fn certify_audit_head() {
+    let (length, head_hash) = audit_head();
+    ic_cdk::api::set_certified_data(&checkpoint_digest(length, &head_hash));
+}
+
+// Newest-first page of the entries filed under `key` in `index`.
+fn audit_page(
+    index: &StableBTreeMap<AuditIndexKey, (), Memory>,
+    key: String,
+    offset: usize,
+    limit: usize,
+) -> Vec<AuditEntry> {
+    let sequences: Vec<u64> = index
+        .keys_range(AuditIndexKey::new(&key, 0)..=AuditIndexKey::new(&key, u64::MAX))
+        .rev()
+        .skip(offset)
+        .take(limit)
+        .map(|key| key.sequence)
+        .collect();
+
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        sequences
+            .into_iter()
+            .filter_map(|sequence| log.get(sequence))
+            .collect()
+    })
+}
+
+// Moves every entry of the pre-chain keyed map into the log. Runs inside
+// post_upgrade, before any call can append, so the legacy entries form an
+// unbroken prefix of the log. Legacy IDs start with the nanosecond
+// timestamp, so key order is oldest first.
+fn migrate_legacy_audit_trail() {
+    let legacy: Vec<(String, LegacyAuditEntry)> =
+        LEGACY_AUDIT_TRAIL.with(|trail| trail.borrow().iter().collect());
+    if legacy.is_empty() {
+        return;
+    }
+
+    let migrated = legacy.len();
+    for (id, entry) in legacy {
+        append_audit_entry(entry.into());
+        LEGACY_AUDIT_TRAIL.with(|trail| trail.borrow_mut().remove(&id));
+    }
+    ic_cdk::println!("Migrated {} legacy audit entries", migrated);
 }
 
 //=============================================================================
@@ -1075,7 +1824,7 @@ async fn verify_wallet_signature(
 }
 
 async fn request_ai_verification(identity_id: String) -> Result<String> {
-    check_rate_limit("verification_request")?;
+    check_rate_limit(RateLimitedOperation::VerificationRequest)?;
 
     let verification_id = generate_secure_random_id("ai_verify").await?;
 
@@ -1083,70 +1832,16 @@ async fn request_ai_verification(identity_id: String) -> Result<String> {
     // This is a placeholder for AI integration
 
     // Update reputation based on AI results (mock positive result)
-    update_reputation_score(
+    record_reputation_event(
         &identity_id,
-        5.0,
+        ReputationSource::IdentityVerified,
         "AI verification completed successfully".to_string(),
-    )
-    .await?;
+        None,
+    )?;
 
     Ok(verification_id)
 }
 
-async fn update_reputation_score(
-    identity_id: &str,
-    score_change: f64,
-    reason: String,
-) -> Result<()> {
-    IDENTITIES.with(|identities| {
-        let mut identities_map = identities.borrow_mut();
-        if let Some(mut identity) = identities_map.get(&identity_id.to_string()) {
-            let old_score = identity.reputation_score;
-            identity.reputation_score = (identity.reputation_score + score_change)
-                .max(0.0)
-                .min(100.0);
-
-            let reputation_event = ReputationEvent {
-                event_type: if score_change > 0.0 {
-                    ReputationEventType::SystemAction
-                } else {
-                    ReputationEventType::FraudReported
-                },
-                score_change,
-                timestamp: time(),
-                reason: reason.clone(),
-                verified_by: Some(caller()),
-            };
-
-            identity.reputation_history.push(reputation_event);
-            identity.updated_at = time();
-
-            identities_map.insert(identity_id.to_string(), identity.clone());
-
-            // Create audit entry
-            create_audit_entry(
-                AuditOperation::UpdateIdentity,
-                identity_id.to_string(),
-                "reputation_update".to_string(),
-                AuditDetails {
-                    operation_specific_data: format!(
-                        "{{\"old_score\":{},\"new_score\":{},\"change\": {}}}",
-                        old_score, identity.reputation_score, score_change
-                    ),
-                    sensitive_data_redacted: false,
-                    related_entities: vec![],
-                    compliance_notes: Some(reason),
-                },
-                OperationResult::Success,
-            );
-
-            Ok(())
-        } else {
-            Err(Error::NotFound("Identity not found".to_string()))
-        }
-    })
-}
-
 //=============================================================================
 // CORE API FUNCTIONS
 //=============================================================================
@@ -1157,7 +1852,7 @@ async fn create_identity(
     initial_credentials: Vec<VerifiableCredential>,
     privacy_settings: PrivacySettings,
 ) -> Result<String> {
-    check_rate_limit("create_identity")?;
+    check_rate_limit(RateLimitedOperation::CreateIdentity)?;
 
     let caller_principal = caller();
     let current_time = time();
@@ -1165,20 +1860,22 @@ async fn create_identity(
     let identity_id = generate_secure_random_id("gt_id").await?;
     let did = generate_did(&identity_id, &caller_principal)?;
 
-    let identity = Identity {
+    let mut identity = Identity {
         id: identity_id.clone(),
         owner: caller_principal,
         did,
         internet_identity_anchor,
         credentials: initial_credentials.clone(),
         verification_status: VerificationStatus::Pending,
-        reputation_score: 50.0,
+        reputation_score: BASE_REPUTATION,
         reputation_history: vec![ReputationEvent {
             event_type: ReputationEventType::SystemAction,
             score_change: 50.0,
             timestamp: current_time,
             reason: "Initial identity creation".to_string(),
             verified_by: Some(caller_principal),
+            source: None,
+            reference: None,
         }],
         privacy_settings: privacy_settings.clone(),
         linked_wallets: Vec::new(),
@@ -1191,20 +1888,25 @@ async fn create_identity(
             last_updated: current_time,
             jurisdiction: "global".to_string(),
             compliance_documents: Vec::new(),
+            kyc_evidence: None,
+            kyc_expires_at: None,
+            aml_expires_at: None,
+            sanctions_expires_at: None,
         },
         risk_assessment: RiskAssessment {
-            overall_risk_score: 0.3, // Low initial risk
-            fraud_risk: 0.2,
-            compliance_risk: 0.4,
-            operational_risk: 0.3,
+            overall_risk_score: 0.0,
+            fraud_risk: 0.0,
+            compliance_risk: 0.0,
+            operational_risk: 0.0,
             risk_factors: Vec::new(),
             last_assessment: current_time,
-            assessment_model_version: "v1.0.0".to_string(),
+            assessment_model_version: RISK_MODEL_VERSION.to_string(),
         },
         created_at: current_time,
         updated_at: current_time,
         last_activity: current_time,
     };
+    reassess_risk(&mut identity, current_time);
 
     IDENTITIES.with(|identities| {
         identities
@@ -1218,11 +1920,10 @@ async fn create_identity(
         identity_id.clone(),
         "identity".to_string(),
         AuditDetails {
-            operation_specific_data: format!(
-                "{{\"credentials_count\":{},\"ii_anchor\":{}}}",
-                initial_credentials.len(),
-                internet_identity_anchor.map_or("null".to_string(), |a| a.to_string())
-            ),
+            payload: AuditPayload::IdentityCreated {
+                credentials_count: initial_credentials.len() as u32,
+                ii_anchor: internet_identity_anchor,
+            },
             sensitive_data_redacted: true,
             related_entities: vec![],
             compliance_notes: Some("New identity created".to_string()),
@@ -1242,7 +1943,7 @@ async fn create_identity(
 #[update]
 async fn add_credential(identity_id: String, credential: VerifiableCredential) -> Result<()> {
     emergency_pause_check()?;
-    check_rate_limit("add_credential")?;
+    check_rate_limit(RateLimitedOperation::AddCredential)?;
     validate_identity_id(&identity_id)?;
 
     let caller = caller();
@@ -1258,6 +1959,7 @@ async fn add_credential(identity_id: String, credential: VerifiableCredential) -
             identity.updated_at = time();
             identity.last_activity = time();
 
+            reassess_risk(&mut identity, time());
             identities_map.insert(identity_id.clone(), identity);
             Ok(())
         } else {
@@ -1270,7 +1972,7 @@ async fn add_credential(identity_id: String, credential: VerifiableCredential) -
         identity_id.clone(),
         "credential".to_string(),
         AuditDetails {
-            operation_specific_data: "{\"credential_added\":true}".to_string(),
+            payload: AuditPayload::CredentialAdded,
             sensitive_data_redacted: true,
             related_entities: vec![],
             compliance_notes: Some("Credential added to identity".to_string()),
@@ -1287,9 +1989,10 @@ async fn link_wallet(
     wallet_address: String,
 ) -> Result<()> {
     emergency_pause_check()?;
-    check_rate_limit("link_wallet")?;
+    check_rate_limit(RateLimitedOperation::LinkWallet)?;
     validate_identity_id(&identity_id)?;
     validate_wallet_address(&wallet_address, &chain_type)?;
+    screen_wallet_address(&identity_id, &chain_type, &wallet_address)?;
 
     let caller = caller();
 
@@ -1299,6 +2002,13 @@ async fn link_wallet(
             if identity.owner != caller {
                 return Err(Error::Unauthorized);
             }
+            enforce_jurisdiction_policy(
+                &identity_id,
+                &identity,
+                &PolicyAction::LinkWallet {
+                    chain_type: chain_type.clone(),
+                },
+            )?;
 
             // Check if wallet is already linked
             if identity
@@ -1320,6 +2030,7 @@ async fn link_wallet(
             identity.updated_at = time();
             identity.last_activity = time();
 
+            reassess_risk(&mut identity, time());
             identities_map.insert(identity_id.clone(), identity);
 
             // Create audit entry
@@ -1328,10 +2039,11 @@ async fn link_wallet(
                 identity_id,
                 "wallet_linked".to_string(),
                 AuditDetails {
-                    operation_specific_data: format!(
-                        "{{\"chain_type\":\"{:?}\",\"address\":\"{}\"}}",
-                        chain_type, wallet_address
-                    ),
+                    payload: AuditPayload::WalletLinked {
+                        chain_type: chain_type.clone(),
+                        address: wallet_address.clone(),
+                        signature_verified: false,
+                    },
                     sensitive_data_redacted: false,
                     related_entities: vec![wallet_address],
                     compliance_notes: Some("Wallet linked to identity".to_string()),
@@ -1354,9 +2066,10 @@ async fn link_wallet_verified(
     signature: String,
     message: String,
 ) -> Result<()> {
-    check_rate_limit("link_wallet")?;
+    check_rate_limit(RateLimitedOperation::LinkWallet)?;
     validate_identity_id(&identity_id)?;
     validate_wallet_address(&wallet_address, &chain_type)?;
+    screen_wallet_address(&identity_id, &chain_type, &wallet_address)?;
 
     // Verify wallet ownership through signature
     let signature_valid =
@@ -1368,10 +2081,11 @@ async fn link_wallet_verified(
             identity_id.clone(),
             "wallet_link_failed".to_string(),
             AuditDetails {
-                operation_specific_data: format!(
-                    "{{\"chain_type\":\"{:?}\",\"address\":\"{}\",\"reason\":\"Invalid signature\"}}",
-                    chain_type, wallet_address
-                ),
+                payload: AuditPayload::WalletLinkRejected {
+                    chain_type: chain_type.clone(),
+                    address: wallet_address.clone(),
+                    reason: "Invalid signature".to_string(),
+                },
                 sensitive_data_redacted: false,
                 related_entities: vec![wallet_address.clone()],
                 compliance_notes: Some("Signature verification failed".to_string()),
@@ -1391,6 +2105,13 @@ async fn link_wallet_verified(
             if identity.owner != caller {
                 return Err(Error::Unauthorized);
             }
+            enforce_jurisdiction_policy(
+                &identity_id,
+                &identity,
+                &PolicyAction::LinkWallet {
+                    chain_type: chain_type.clone(),
+                },
+            )?;
 
             // Check if wallet is already linked
             if identity
@@ -1429,30 +2150,21 @@ async fn link_wallet_verified(
             identity.updated_at = time();
             identity.last_activity = time();
 
+            reassess_risk(&mut identity, time());
             identities_map.insert(identity_id.clone(), identity);
 
-            // Update reputation for successful wallet verification
-            let identity_id_clone = identity_id.clone();
-            ic_cdk::spawn(async move {
-                let _ = update_reputation_score(
-                    &identity_id_clone,
-                    2.0,
-                    "Wallet successfully verified and linked".to_string(),
-                )
-                .await;
-            });
-
             create_audit_entry(
                 AuditOperation::LinkWallet,
-                identity_id,
+                identity_id.clone(),
                 "wallet_verified_linked".to_string(),
                 AuditDetails {
-                    operation_specific_data: format!(
-                        "{{\"chain_type\":\"{:?}\",\"address\":\"{}\"}}",
-                        chain_type, wallet_address
-                    ),
+                    payload: AuditPayload::WalletLinked {
+                        chain_type: chain_type.clone(),
+                        address: wallet_address.clone(),
+                        signature_verified: true,
+                    },
                     sensitive_data_redacted: false,
-                    related_entities: vec![wallet_address],
+                    related_entities: vec![wallet_address.clone()],
                     compliance_notes: Some("Wallet verified and linked successfully".to_string()),
                 },
                 OperationResult::Success,
@@ -1462,13 +2174,22 @@ async fn link_wallet_verified(
         } else {
             Err(Error::NotFound("Identity not found".to_string()))
         }
-    })
+    })?;
+
+    // A wallet counts once, even if unlinked and linked again
+    let _ = record_reputation_event(
+        &identity_id,
+        ReputationSource::WalletVerified,
+        "Wallet successfully verified and linked".to_string(),
+        Some(sanctions_address_key(&chain_type, &wallet_address)),
+    );
+    Ok(())
 }
 
 #[update]
-async fn link_asset(identity_id: String, asset_id: String) -> Result<()> {
+async fn link_asset(identity_id: String, asset_id: String, asset_type: String) -> Result<()> {
     emergency_pause_check()?;
-    check_rate_limit("link_asset")?;
+    check_rate_limit(RateLimitedOperation::LinkAsset)?;
     validate_identity_id(&identity_id)?;
 
     let caller = caller();
@@ -1479,6 +2200,13 @@ async fn link_asset(identity_id: String, asset_id: String) -> Result<()> {
             if identity.owner != caller {
                 return Err(Error::Unauthorized);
             }
+            enforce_jurisdiction_policy(
+                &identity_id,
+                &identity,
+                &PolicyAction::LinkAsset {
+                    asset_type: Some(asset_type),
+                },
+            )?;
 
             // Check if asset is already linked
             if identity.linked_assets.contains(&asset_id) {
@@ -1489,6 +2217,7 @@ async fn link_asset(identity_id: String, asset_id: String) -> Result<()> {
             identity.updated_at = time();
             identity.last_activity = time();
 
+            reassess_risk(&mut identity, time());
             identities_map.insert(identity_id.clone(), identity);
 
             // Create audit entry
@@ -1497,7 +2226,10 @@ async fn link_asset(identity_id: String, asset_id: String) -> Result<()> {
                 identity_id,
                 "asset_linked".to_string(),
                 AuditDetails {
-                    operation_specific_data: format!("{{\"asset_id\":\"{}\"}}", asset_id),
+                    payload: AuditPayload::AssetLinked {
+                        asset_id: asset_id.clone(),
+                        ai_request_id: None,
+                    },
                     sensitive_data_redacted: false,
                     related_entities: vec![asset_id],
                     compliance_notes: Some("Asset linked to identity".to_string()),
@@ -1512,42 +2244,6 @@ async fn link_asset(identity_id: String, asset_id: String) -> Result<()> {
     })
 }
 
-#[update]
-async fn update_reputation(identity_id: String, score_change: f64, reason: String) -> Result<()> {
-    emergency_pause_check()?;
-    validate_identity_id(&identity_id)?;
-    validate_asset_value(score_change.abs())?;
-
-    IDENTITIES.with(|identities| {
-        let mut identities_map = identities.borrow_mut();
-        if let Some(mut identity) = identities_map.get(&identity_id) {
-            identity.reputation_score = (identity.reputation_score + score_change)
-                .max(0.0)
-                .min(100.0);
-
-            let reputation_event = ReputationEvent {
-                event_type: if score_change > 0.0 {
-                    ReputationEventType::SystemAction
-                } else {
-                    ReputationEventType::FraudReported
-                },
-                score_change,
-                timestamp: time(),
-                reason,
-                verified_by: Some(caller()),
-            };
-
-            identity.reputation_history.push(reputation_event);
-            identity.updated_at = time();
-
-            identities_map.insert(identity_id, identity);
-            Ok(())
-        } else {
-            Err(Error::NotFound("Identity not found".to_string()))
-        }
-    })
-}
-
 #[query]
 fn get_identity(identity_id: String) -> Result<Identity> {
     validate_identity_id(&identity_id)?;
@@ -1633,25 +2329,171 @@ fn get_audit_trail(
     let limit = limit.unwrap_or(50).min(100) as usize;
     let offset = offset.unwrap_or(0) as usize;
 
-    let mut audit_entries: Vec<AuditEntry> = AUDIT_TRAIL.with(|trail| {
-        trail
-            .borrow()
-            .iter()
-            .filter(|(_, entry)| entry.resource_id == identity_id)
-            .map(|(_, entry)| entry.clone())
-            .collect()
-    });
+    Ok(AUDIT_BY_RESOURCE.with(|index| audit_page(&index.borrow(), identity_id, offset, limit)))
+}
+
+// Newest-first audit entries recorded for calls made by `principal`.
+// Principals may read their own; admins may read anyone's.
+#[query]
+fn get_principal_audit_trail(
+    principal: Principal,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<AuditEntry>> {
+    if principal != caller() {
+        is_admin()?;
+    }
 
-    // Sort by timestamp descending
-    audit_entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    let limit = limit.unwrap_or(50).min(100) as usize;
+    let offset = offset.unwrap_or(0) as usize;
 
-    // Apply pagination
-    let end = (offset + limit).min(audit_entries.len());
-    if offset < audit_entries.len() {
-        Ok(audit_entries[offset..end].to_vec())
-    } else {
-        Ok(Vec::new())
+    Ok(AUDIT_BY_PRINCIPAL
+        .with(|index| audit_page(&index.borrow(), principal.to_text(), offset, limit)))
+}
+
+// The audit head plus the certificate over it; verify off-chain with
+// `verify_entries_against_checkpoint`.
+#[query]
+fn get_audit_checkpoint() -> AuditCheckpoint {
+    let (length, head_hash) = audit_head();
+    AuditCheckpoint {
+        length,
+        head_hash,
+        certificate: ic_cdk::api::data_certificate(),
+    }
+}
+
+// Admins act as compliance officers; everyone else needs an audit role.
+fn audit_role() -> Result<AuditRole> {
+    if is_admin().is_ok() {
+        return Ok(AuditRole::ComplianceOfficer);
+    }
+    AUDIT_ROLES
+        .with(|roles| roles.borrow().get(&caller()))
+        .ok_or(Error::Unauthorized)
+}
+
+// Newest-first entries matching `filter` with sequence below `cursor`,
+// examining at most MAX_AUDIT_SCAN entries. A principal filter is served from
+// the principal index; other filters scan the log.
+fn search_audit_entries(
+    filter: &AuditSearchFilter,
+    cursor: Option<u64>,
+    limit: usize,
+) -> (Vec<AuditEntry>, Option<u64>) {
+    let end = cursor.unwrap_or(u64::MAX);
+    let candidates: Vec<u64> = match filter.principal {
+        Some(principal) => AUDIT_BY_PRINCIPAL.with(|index| {
+            let key = principal.to_text();
+            index
+                .borrow()
+                .keys_range(AuditIndexKey::new(&key, 0)..AuditIndexKey::new(&key, end))
+                .rev()
+                .take(MAX_AUDIT_SCAN as usize)
+                .map(|key| key.sequence)
+                .collect()
+        }),
+        None => {
+            let end = end.min(AUDIT_LOG.with(|log| log.borrow().len()));
+            (end.saturating_sub(MAX_AUDIT_SCAN)..end).rev().collect()
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut next_cursor = None;
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        for &sequence in &candidates {
+            let Some(entry) = log.get(sequence) else {
+                continue;
+            };
+            if filter.matches(&entry) {
+                entries.push(entry);
+                if entries.len() == limit {
+                    next_cursor = Some(sequence);
+                    break;
+                }
+            }
+        }
+    });
+
+    // Scan budget exhausted before reaching the start of the log
+    if next_cursor.is_none() && candidates.len() as u64 == MAX_AUDIT_SCAN {
+        next_cursor = candidates.last().copied().filter(|&sequence| sequence > 0);
     }
+    (entries, next_cursor)
+}
+
+#[query]
+fn search_audit_log(
+    filter: AuditSearchFilter,
+    cursor: Option<u64>,
+    limit: Option<u32>,
+) -> Result<AuditSearchPage> {
+    let role = audit_role()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_AUDIT_PAGE_SIZE)
+        .clamp(1, MAX_AUDIT_PAGE_SIZE) as usize;
+
+    let (entries, next_cursor) = search_audit_entries(&filter, cursor, limit);
+    Ok(AuditSearchPage {
+        entries: entries
+            .into_iter()
+            .map(|entry| redact_for_role(entry, role))
+            .collect(),
+        next_cursor,
+    })
+}
+
+// One chunk of the matching entries, rendered as CSV or JSON Lines. Pass
+// `next_cursor` back until it is None to export the full result.
+#[query]
+fn export_audit_log(
+    filter: AuditSearchFilter,
+    format: AuditExportFormat,
+    cursor: Option<u64>,
+    chunk_size: Option<u32>,
+) -> Result<AuditExportChunk> {
+    let role = audit_role()?;
+    let limit = chunk_size
+        .unwrap_or(DEFAULT_AUDIT_EXPORT_CHUNK)
+        .clamp(1, MAX_AUDIT_EXPORT_CHUNK) as usize;
+
+    let (entries, next_cursor) = search_audit_entries(&filter, cursor, limit);
+    let entries: Vec<AuditEntry> = entries
+        .into_iter()
+        .map(|entry| redact_for_role(entry, role))
+        .collect();
+
+    Ok(AuditExportChunk {
+        data: render_export(&entries, format, cursor.is_none()),
+        entry_count: entries.len() as u32,
+        next_cursor,
+    })
+}
+
+// Grants or (with None) revokes access to the whole audit log.
+#[update]
+fn set_audit_role(principal: Principal, role: Option<AuditRole>) -> Result<()> {
+    is_admin()?;
+    AUDIT_ROLES.with(|roles| {
+        let mut roles = roles.borrow_mut();
+        match role {
+            Some(role) => roles.insert(principal, role),
+            None => roles.remove(&principal),
+        }
+    });
+    Ok(())
+}
+
+// Recomputes the hash links of entries `from..=to`. Reveals only hashes, so
+// anyone may check the log's integrity.
+#[query]
+fn verify_audit_chain(from: u64, to: u64) -> Result<AuditChainVerification, String> {
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        verify_chain(from, to, log.len(), |sequence| log.get(sequence))
+    })
 }
 
 #[query]
@@ -1702,7 +2544,7 @@ async fn link_asset_with_verification(
     asset_type: String,
     asset_data: String, // JSON encoded asset details
 ) -> Result<String> {
-    check_rate_limit("link_asset")?;
+    check_rate_limit(RateLimitedOperation::LinkAsset)?;
     validate_identity_id(&identity_id)?;
 
     let caller = caller();
@@ -1714,6 +2556,13 @@ async fn link_asset_with_verification(
             if identity.owner != caller {
                 return Err(Error::Unauthorized);
             }
+            enforce_jurisdiction_policy(
+                &identity_id,
+                &identity,
+                &PolicyAction::LinkAsset {
+                    asset_type: Some(asset_type.clone()),
+                },
+            )?;
 
             // Check if asset is already linked
             if identity.linked_assets.contains(&asset_id) {
@@ -1724,6 +2573,7 @@ async fn link_asset_with_verification(
             identity.updated_at = time();
             identity.last_activity = time();
 
+            reassess_risk(&mut identity, time());
             identities_map.insert(identity_id.clone(), identity);
             Ok(())
         } else {
@@ -1746,10 +2596,10 @@ async fn link_asset_with_verification(
         identity_id,
         "asset_linked_with_verification".to_string(),
         AuditDetails {
-            operation_specific_data: format!(
-                "{{\"asset_id\":\"{}\",\"ai_request_id\":\"{}\"}}",
-                asset_id, ai_request_id
-            ),
+            payload: AuditPayload::AssetLinked {
+                asset_id: asset_id.clone(),
+                ai_request_id: Some(ai_request_id.clone()),
+            },
             sensitive_data_redacted: false,
             related_entities: vec![asset_id, ai_request_id.clone()],
             compliance_notes: Some("Asset linked and AI verification initiated".to_string()),
@@ -1816,26 +2666,27 @@ async fn update_asset_verification_result(asset_id: String) -> Result<AssetVerif
                         .borrow_mut()
                         .insert(asset_id.clone(), verification.clone());
                 });
+                let _ = refresh_stored_risk(&verification.identity_id);
 
                 // Update identity reputation based on verification results
-                let reputation_change = if ai_result.fraud_score < 0.3 {
-                    3.0 // Good asset verification
+                let reputation_source = if ai_result.fraud_score < 0.3 {
+                    Some(ReputationSource::AssetVerified)
                 } else if ai_result.fraud_score < 0.7 {
-                    0.0 // Neutral
+                    None // Neutral
                 } else {
-                    -5.0 // Bad asset verification
+                    Some(ReputationSource::AssetRejected)
                 };
 
-                if reputation_change != 0.0 {
-                    let _ = update_reputation_score(
+                if let Some(source) = reputation_source {
+                    let _ = record_reputation_event(
                         &verification.identity_id,
-                        reputation_change,
+                        source,
                         format!(
                             "Asset verification completed: fraud_score={:.2}",
                             ai_result.fraud_score
                         ),
-                    )
-                    .await;
+                        Some(asset_id.clone()),
+                    );
                 }
 
                 // Create audit entry
@@ -1844,10 +2695,11 @@ async fn update_asset_verification_result(asset_id: String) -> Result<AssetVerif
                     verification.identity_id.clone(),
                     "asset_verification_completed".to_string(),
                     AuditDetails {
-                        operation_specific_data: format!(
-                            "{{\"asset_id\":\"{}\",\"fraud_score\":{:.2},\"confidence\":{:.2}}}",
-                            asset_id, ai_result.fraud_score, ai_result.confidence_level
-                        ),
+                        payload: AuditPayload::AssetVerified {
+                            asset_id: asset_id.clone(),
+                            fraud_score: ai_result.fraud_score,
+                            confidence: ai_result.confidence_level,
+                        },
                         sensitive_data_redacted: false,
                         related_entities: vec![asset_id],
                         compliance_notes: Some(
@@ -1868,26 +2720,1482 @@ async fn update_asset_verification_result(asset_id: String) -> Result<AssetVerif
 }
 
 //=============================================================================
-// CROSS-CHAIN BRIDGE FUNCTIONS
+// COMPLIANCE WORKFLOW
 //=============================================================================
 
-#[update]
-async fn initiate_cross_chain_bridge(
-    from_chain: ChainType,
-    to_chain: ChainType,
-    asset_type: String,
-    amount: u64,
-    from_address: String,
-    to_address: String,
-) -> Result<String, String> {
-    let caller = caller();
-
-    BRIDGE_SERVICE.with(|service| {
+// Compliance officers, including admins.
+fn is_compliance_officer() -> Result<()> {
+    if audit_role().is_ok_and(|role| role.is_privileged()) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+// Compliance officers and registered KYC providers. Providers may only
+// submit evidence and KYC, AML and sanctions results.
+fn is_kyc_submitter() -> Result<()> {
+    if is_compliance_officer().is_ok() {
+        return Ok(());
+    }
+    let caller = caller();
+    if COMPLIANCE_POLICY.with(|policy| policy.borrow().get().is_kyc_provider(&caller)) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+// Applies `change` to the identity's compliance status and records the
+// payload it returns as a ComplianceUpdate audit entry.
+fn update_compliance_status(
+    identity_id: &str,
+    sensitive: bool,
+    change: impl FnOnce(&mut ComplianceStatus, u64) -> Result<AuditPayload>,
+) -> Result<()> {
+    validate_identity_id(identity_id)?;
+    let now = time();
+
+    let payload = IDENTITIES.with(|identities| {
+        let mut identities = identities.borrow_mut();
+        let mut identity = identities
+            .get(&identity_id.to_string())
+            .ok_or(Error::NotFound("Identity not found".to_string()))?;
+
+        let payload = change(&mut identity.compliance_status, now)?;
+        identity.compliance_status.last_updated = now;
+        identity.updated_at = now;
+        reassess_risk(&mut identity, now);
+        identities.insert(identity_id.to_string(), identity);
+        Ok::<_, Error>(payload)
+    })?;
+
+    create_audit_entry(
+        AuditOperation::ComplianceUpdate,
+        identity_id.to_string(),
+        "compliance_status".to_string(),
+        AuditDetails {
+            payload,
+            sensitive_data_redacted: sensitive,
+            related_entities: Vec::new(),
+            compliance_notes: None,
+        },
+        OperationResult::Success,
+    );
+    Ok(())
+}
+
+#[update]
+fn submit_kyc_evidence(
+    identity_id: String,
+    evidence_type: String,
+    reference: String,
+) -> Result<()> {
+    is_kyc_submitter()?;
+    validate_kyc_evidence(&reference, &evidence_type).map_err(Error::InvalidInput)?;
+
+    update_compliance_status(&identity_id, true, |status, now| {
+        let evidence = status.kyc_evidence.get_or_insert_with(Vec::new);
+        if evidence.len() >= MAX_KYC_EVIDENCE {
+            return Err(Error::InvalidInput(format!(
+                "At most {} evidence references per identity",
+                MAX_KYC_EVIDENCE
+            )));
+        }
+        evidence.push(KycEvidence {
+            reference: reference.clone(),
+            evidence_type: evidence_type.clone(),
+            submitted_by: caller(),
+            submitted_at: now,
+        });
+        Ok(AuditPayload::KycEvidenceSubmitted {
+            evidence_type,
+            reference,
+        })
+    })
+}
+
+// Sets the KYC level, valid for the policy's KYC validity unless
+// `valid_for_ns` is given. Any level above None requires evidence on file.
+#[update]
+fn update_kyc_level(identity_id: String, level: KYCLevel, valid_for_ns: Option<u64>) -> Result<()> {
+    is_kyc_submitter()?;
+    let policy = COMPLIANCE_POLICY.with(|policy| policy.borrow().get().clone());
+
+    update_compliance_status(&identity_id, false, |status, now| {
+        if level > KYCLevel::None && status.kyc_evidence().is_empty() {
+            return Err(Error::InvalidInput(
+                "KYC evidence must be submitted before raising the level".to_string(),
+            ));
+        }
+
+        let from = status.effective(now).kyc_level;
+        let expires_at = (level > KYCLevel::None)
+            .then(|| now.saturating_add(valid_for_ns.unwrap_or(policy.kyc_validity_ns)));
+        status.kyc_level = level.clone();
+        status.kyc_expires_at = expires_at;
+        Ok(AuditPayload::KycLevelChanged {
+            from,
+            to: level,
+            expires_at,
+        })
+    })
+}
+
+#[update]
+fn record_aml_screening(
+    identity_id: String,
+    aml_status: AMLStatus,
+    valid_for_ns: Option<u64>,
+) -> Result<()> {
+    is_kyc_submitter()?;
+    let policy = COMPLIANCE_POLICY.with(|policy| policy.borrow().get().clone());
+
+    update_compliance_status(&identity_id, false, |status, now| {
+        let expires_at = now.saturating_add(valid_for_ns.unwrap_or(policy.aml_validity_ns));
+        status.aml_status = aml_status.clone();
+        status.aml_expires_at = Some(expires_at);
+        Ok(AuditPayload::AmlScreened {
+            status: aml_status,
+            expires_at: Some(expires_at),
+        })
+    })
+}
+
+#[update]
+fn record_sanctions_check(
+    identity_id: String,
+    sanctions_status: SanctionsStatus,
+    valid_for_ns: Option<u64>,
+) -> Result<()> {
+    is_kyc_submitter()?;
+    let policy = COMPLIANCE_POLICY.with(|policy| policy.borrow().get().clone());
+
+    update_compliance_status(&identity_id, false, |status, now| {
+        let expires_at = now.saturating_add(valid_for_ns.unwrap_or(policy.sanctions_validity_ns));
+        status.sanctions_check = sanctions_status.clone();
+        status.sanctions_expires_at = Some(expires_at);
+        Ok(AuditPayload::SanctionsChecked {
+            status: sanctions_status,
+            expires_at: Some(expires_at),
+        })
+    })
+}
+
+// For downstream canisters gating on compliance. Carries no personal data, so
+// any caller may request it.
+#[query]
+fn get_compliance_attestation(identity_id: String) -> Result<ComplianceAttestation> {
+    validate_identity_id(&identity_id)?;
+    let now = time();
+
+    let identity = IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id))
+        .ok_or(Error::NotFound("Identity not found".to_string()))?;
+    let status = identity.compliance_status.effective(now);
+
+    Ok(ComplianceAttestation {
+        identity_id,
+        did: identity.did,
+        kyc_level: status.kyc_level.clone(),
+        aml_status: status.aml_status.clone(),
+        sanctions_status: status.sanctions_check.clone(),
+        jurisdiction: status.jurisdiction.clone(),
+        valid_until: status.valid_until(now),
+        issued_at: now,
+    })
+}
+
+// Identities with a check that has expired or expires within the policy's
+// notice period, for compliance officers to screen again.
+#[query]
+fn get_rescreening_queue(limit: Option<u32>) -> Result<Vec<RescreeningItem>> {
+    is_compliance_officer()?;
+    let now = time();
+    let notice = COMPLIANCE_POLICY.with(|policy| policy.borrow().get().rescreening_notice_ns);
+    let limit = limit.unwrap_or(50).min(500) as usize;
+
+    Ok(IDENTITIES.with(|identities| {
+        identities
+            .borrow()
+            .iter()
+            .filter(|(_, identity)| identity.compliance_status.needs_rescreening(now, notice))
+            .take(limit)
+            .map(|(identity_id, identity)| RescreeningItem {
+                identity_id,
+                kyc_expires_at: identity.compliance_status.kyc_expires_at,
+                aml_expires_at: identity.compliance_status.aml_expires_at,
+                sanctions_expires_at: identity.compliance_status.sanctions_expires_at,
+            })
+            .collect()
+    }))
+}
+
+#[update]
+fn set_kyc_provider(provider: Principal, registered: bool) -> Result<()> {
+    is_admin()?;
+    update_compliance_policy(|policy| {
+        policy.kyc_providers.retain(|p| *p != provider);
+        if registered {
+            policy.kyc_providers.push(provider);
+        }
+    });
+    Ok(())
+}
+
+#[update]
+fn set_compliance_validity(
+    kyc_validity_ns: u64,
+    aml_validity_ns: u64,
+    sanctions_validity_ns: u64,
+    rescreening_notice_ns: u64,
+) -> Result<()> {
+    is_admin()?;
+    if kyc_validity_ns == 0 || aml_validity_ns == 0 || sanctions_validity_ns == 0 {
+        return Err(Error::InvalidInput(
+            "Validity periods must be non-zero".to_string(),
+        ));
+    }
+    update_compliance_policy(|policy| {
+        policy.kyc_validity_ns = kyc_validity_ns;
+        policy.aml_validity_ns = aml_validity_ns;
+        policy.sanctions_validity_ns = sanctions_validity_ns;
+        policy.rescreening_notice_ns = rescreening_notice_ns;
+    });
+    Ok(())
+}
+
+fn update_compliance_policy(f: impl FnOnce(&mut CompliancePolicy)) {
+    COMPLIANCE_POLICY.with(|cell| {
+        let mut policy = cell.borrow().get().clone();
+        f(&mut policy);
+        let _ = cell.borrow_mut().set(policy);
+    });
+}
+
+//=============================================================================
+// SANCTIONS SCREENING
+//=============================================================================
+
+// Rejects a sanctioned address. A hit on an identity the caller owns marks it
+// Sanctioned and AML-blocked; every hit is audited either way.
+fn screen_wallet_address(identity_id: &str, chain: &ChainType, address: &str) -> Result<()> {
+    let key = sanctions_address_key(chain, address);
+    let Some(listing) = SANCTIONED_ADDRESSES.with(|addresses| addresses.borrow().get(&key)) else {
+        return Ok(());
+    };
+
+    let owned = IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id.to_string()))
+        .is_some_and(|identity| identity.owner == caller());
+    record_sanctions_hit(
+        identity_id,
+        owned,
+        AuditPayload::SanctionsAddressHit {
+            chain_type: chain.clone(),
+            address: address.to_string(),
+            list: listing.list.clone(),
+        },
+        false,
+    );
+
+    Err(Error::VerificationFailed(format!(
+        "Address appears on sanctions list {}",
+        listing.list
+    )))
+}
+
+// Listed names whose similarity to `name` reaches the configured threshold,
+// best match first.
+fn match_sanctioned_names(name: &str) -> Vec<NameMatch> {
+    let normalized = normalize_name(name);
+    if normalized.is_empty() {
+        return Vec::new();
+    }
+    let threshold = SANCTIONS_CONFIG.with(|config| config.borrow().get().name_match_threshold);
+
+    let mut matches: Vec<NameMatch> = SANCTIONED_NAMES.with(|names| {
+        names
+            .borrow()
+            .iter()
+            .filter_map(|(listed_name, listing)| {
+                let score = name_similarity(&normalized, &listed_name);
+                (score >= threshold).then_some(NameMatch {
+                    listed_name,
+                    list: listing.list,
+                    score,
+                })
+            })
+            .collect()
+    });
+    matches.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    matches
+}
+
+// Audits a hit against `resource_id` and, if `block` is set, marks that
+// identity Sanctioned and AML-blocked until a reviewer clears it.
+fn record_sanctions_hit(resource_id: &str, block: bool, payload: AuditPayload, sensitive: bool) {
+    if block {
+        IDENTITIES.with(|identities| {
+            let mut identities = identities.borrow_mut();
+            if let Some(mut identity) = identities.get(&resource_id.to_string()) {
+                let status = &mut identity.compliance_status;
+                status.sanctions_check = SanctionsStatus::Sanctioned;
+                status.sanctions_expires_at = None;
+                status.aml_status = AMLStatus::Blocked;
+                status.aml_expires_at = None;
+                status.last_updated = time();
+                reassess_risk(&mut identity, time());
+                identities.insert(resource_id.to_string(), identity);
+            }
+        });
+    }
+
+    create_audit_entry(
+        AuditOperation::SanctionsScreening,
+        resource_id.to_string(),
+        "sanctions_hit".to_string(),
+        AuditDetails {
+            payload,
+            sensitive_data_redacted: sensitive,
+            related_entities: Vec::new(),
+            compliance_notes: Some(if block {
+                "Sanctions list hit; identity blocked".to_string()
+            } else {
+                "Sanctions list hit".to_string()
+            }),
+        },
+        OperationResult::SecurityBlocked("Sanctions list hit".to_string()),
+    );
+}
+
+// Checks a free-form name against the name lists. Hits are audited against
+// the resource "name_screening".
+#[update]
+fn screen_name(name: String) -> Result<Vec<NameMatch>> {
+    is_compliance_officer()?;
+    let matches = match_sanctioned_names(&name);
+    for hit in &matches {
+        record_sanctions_hit(
+            "name_screening",
+            false,
+            AuditPayload::SanctionsNameHit {
+                name: name.clone(),
+                listed_name: hit.listed_name.clone(),
+                list: hit.list.clone(),
+                score: hit.score,
+            },
+            true,
+        );
+    }
+    Ok(matches)
+}
+
+// Screens an identity's claimed names and linked wallets, blocking it on any
+// hit. Returns whether it was found sanctioned.
+#[update]
+fn screen_identity(identity_id: String) -> Result<bool> {
+    is_compliance_officer()?;
+    validate_identity_id(&identity_id)?;
+    let identity = IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id))
+        .ok_or(Error::NotFound("Identity not found".to_string()))?;
+
+    let mut hits = Vec::new();
+    for wallet in &identity.linked_wallets {
+        let key = sanctions_address_key(&wallet.chain_type, &wallet.address);
+        if let Some(listing) = SANCTIONED_ADDRESSES.with(|addresses| addresses.borrow().get(&key)) {
+            hits.push((
+                AuditPayload::SanctionsAddressHit {
+                    chain_type: wallet.chain_type.clone(),
+                    address: wallet.address.clone(),
+                    list: listing.list,
+                },
+                false,
+            ));
+        }
+    }
+    for name in claimed_names(&identity) {
+        for hit in match_sanctioned_names(&name) {
+            hits.push((
+                AuditPayload::SanctionsNameHit {
+                    name: name.clone(),
+                    listed_name: hit.listed_name,
+                    list: hit.list,
+                    score: hit.score,
+                },
+                true,
+            ));
+        }
+    }
+
+    let sanctioned = !hits.is_empty();
+    for (payload, sensitive) in hits {
+        record_sanctions_hit(&identity_id, true, payload, sensitive);
+    }
+    Ok(sanctioned)
+}
+
+// Values of public name claims on the identity's credentials.
+fn claimed_names(identity: &Identity) -> Vec<String> {
+    identity
+        .credentials
+        .iter()
+        .filter_map(|credential| match &credential.claims {
+            CredentialClaims::Public(claims) => Some(claims),
+            _ => None,
+        })
+        .flatten()
+        .filter(|claim| {
+            matches!(
+                claim.claim_type.to_ascii_lowercase().as_str(),
+                "name" | "full_name" | "legal_name"
+            )
+        })
+        .map(|claim| claim.claim_value.clone())
+        .collect()
+}
+
+fn validate_sanctions_batch(len: usize) -> Result<()> {
+    if len == 0 || len > MAX_SANCTIONS_BATCH {
+        return Err(Error::InvalidInput(format!(
+            "Batches must hold 1-{} entries",
+            MAX_SANCTIONS_BATCH
+        )));
+    }
+    Ok(())
+}
+
+// Returns the number of entries added.
+#[update]
+fn add_sanctioned_addresses(list: String, addresses: Vec<SanctionedAddress>) -> Result<u32> {
+    is_admin()?;
+    validate_sanctions_list_name(&list).map_err(Error::InvalidInput)?;
+    validate_sanctions_batch(addresses.len())?;
+    for entry in &addresses {
+        validate_wallet_address(&entry.address, &entry.chain_type)?;
+    }
+
+    let now = time();
+    SANCTIONED_ADDRESSES.with(|stored| {
+        let mut stored = stored.borrow_mut();
+        let mut added = 0;
+        for entry in addresses {
+            let listing = SanctionsListing {
+                list: list.clone(),
+                added_at: now,
+            };
+            let key = sanctions_address_key(&entry.chain_type, &entry.address);
+            if stored.insert(key, listing).is_none() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    })
+}
+
+#[update]
+fn remove_sanctioned_addresses(addresses: Vec<SanctionedAddress>) -> Result<u32> {
+    is_admin()?;
+    validate_sanctions_batch(addresses.len())?;
+    SANCTIONED_ADDRESSES.with(|stored| {
+        let mut stored = stored.borrow_mut();
+        Ok(addresses
+            .iter()
+            .filter(|entry| {
+                stored
+                    .remove(&sanctions_address_key(&entry.chain_type, &entry.address))
+                    .is_some()
+            })
+            .count() as u32)
+    })
+}
+
+#[update]
+fn add_sanctioned_names(list: String, names: Vec<String>) -> Result<u32> {
+    is_admin()?;
+    validate_sanctions_list_name(&list).map_err(Error::InvalidInput)?;
+    validate_sanctions_batch(names.len())?;
+    if names
+        .iter()
+        .any(|name| name.len() > MAX_SANCTIONED_NAME_LENGTH || normalize_name(name).is_empty())
+    {
+        return Err(Error::InvalidInput(format!(
+            "Names must be 1-{} characters",
+            MAX_SANCTIONED_NAME_LENGTH
+        )));
+    }
+
+    let now = time();
+    SANCTIONED_NAMES.with(|stored| {
+        let mut stored = stored.borrow_mut();
+        let mut added = 0;
+        for name in names {
+            let listing = SanctionsListing {
+                list: list.clone(),
+                added_at: now,
+            };
+            if stored.insert(normalize_name(&name), listing).is_none() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    })
+}
+
+#[update]
+fn remove_sanctioned_names(names: Vec<String>) -> Result<u32> {
+    is_admin()?;
+    validate_sanctions_batch(names.len())?;
+    SANCTIONED_NAMES.with(|stored| {
+        let mut stored = stored.borrow_mut();
+        Ok(names
+            .iter()
+            .filter(|name| stored.remove(&normalize_name(name)).is_some())
+            .count() as u32)
+    })
+}
+
+#[update]
+fn set_sanctions_name_threshold(threshold: u8) -> Result<()> {
+    is_admin()?;
+    if !(50..=100).contains(&threshold) {
+        return Err(Error::InvalidInput(
+            "Threshold must be between 50 and 100".to_string(),
+        ));
+    }
+    SANCTIONS_CONFIG.with(|config| {
+        let _ = config.borrow_mut().set(SanctionsConfig {
+            name_match_threshold: threshold,
+        });
+    });
+    Ok(())
+}
+
+#[query]
+fn get_sanctions_list_stats() -> Result<SanctionsListStats> {
+    is_admin()?;
+    Ok(SanctionsListStats {
+        addresses: SANCTIONED_ADDRESSES.with(|addresses| addresses.borrow().len()),
+        names: SANCTIONED_NAMES.with(|names| names.borrow().len()),
+        name_match_threshold: SANCTIONS_CONFIG
+            .with(|config| config.borrow().get().name_match_threshold),
+    })
+}
+
+//=============================================================================
+// JURISDICTION POLICY
+//=============================================================================
+
+// The identity's own jurisdiction policy, else the global one.
+fn jurisdiction_policy_for(jurisdiction: &str) -> Option<JurisdictionPolicy> {
+    JURISDICTION_POLICIES.with(|policies| {
+        let policies = policies.borrow();
+        policies
+            .get(&jurisdiction.to_string())
+            .or_else(|| policies.get(&DEFAULT_JURISDICTION.to_string()))
+    })
+}
+
+fn evaluate_jurisdiction_policy(
+    identity: &Identity,
+    action: &PolicyAction,
+) -> Result<(), PolicyViolation> {
+    match jurisdiction_policy_for(&identity.compliance_status.jurisdiction) {
+        Some(policy) => policy.evaluate(action, identity, time()),
+        None => Ok(()),
+    }
+}
+
+// Like `evaluate_jurisdiction_policy`, but audits the violation.
+fn enforce_jurisdiction_policy(
+    identity_id: &str,
+    identity: &Identity,
+    action: &PolicyAction,
+) -> Result<()> {
+    let Err(violation) = evaluate_jurisdiction_policy(identity, action) else {
+        return Ok(());
+    };
+
+    create_audit_entry(
+        AuditOperation::PolicyEnforcement,
+        identity_id.to_string(),
+        "jurisdiction_policy".to_string(),
+        AuditDetails {
+            payload: AuditPayload::PolicyViolated {
+                jurisdiction: violation.jurisdiction.clone(),
+                rule: violation.rule.clone(),
+                message: violation.message.clone(),
+            },
+            sensitive_data_redacted: false,
+            related_entities: Vec::new(),
+            compliance_notes: None,
+        },
+        OperationResult::SecurityBlocked(violation.to_string()),
+    );
+    Err(Error::PolicyViolation(violation))
+}
+
+#[update]
+fn set_jurisdiction_policy(policy: JurisdictionPolicy) -> Result<()> {
+    is_admin()?;
+    validate_jurisdiction(&policy.jurisdiction).map_err(Error::InvalidInput)?;
+    if policy
+        .max_bridge_amounts
+        .iter()
+        .any(|limit| limit.asset_type.trim().is_empty())
+    {
+        return Err(Error::InvalidInput(
+            "Bridge limits need an asset type".to_string(),
+        ));
+    }
+    JURISDICTION_POLICIES.with(|policies| {
+        policies
+            .borrow_mut()
+            .insert(policy.jurisdiction.clone(), policy)
+    });
+    Ok(())
+}
+
+#[update]
+fn remove_jurisdiction_policy(jurisdiction: String) -> Result<()> {
+    is_admin()?;
+    JURISDICTION_POLICIES
+        .with(|policies| policies.borrow_mut().remove(&jurisdiction))
+        .map(|_| ())
+        .ok_or(Error::NotFound("Jurisdiction policy not found".to_string()))
+}
+
+#[query]
+fn get_jurisdiction_policies() -> Vec<JurisdictionPolicy> {
+    JURISDICTION_POLICIES
+        .with(|policies| policies.borrow().iter().map(|(_, policy)| policy).collect())
+}
+
+#[update]
+fn set_identity_jurisdiction(identity_id: String, jurisdiction: String) -> Result<()> {
+    is_compliance_officer()?;
+    validate_jurisdiction(&jurisdiction).map_err(Error::InvalidInput)?;
+
+    update_compliance_status(&identity_id, false, |status, _| {
+        let from = std::mem::replace(&mut status.jurisdiction, jurisdiction.clone());
+        Ok(AuditPayload::JurisdictionChanged {
+            from,
+            to: jurisdiction,
+        })
+    })
+}
+
+// Lets other canisters (e.g. the marketplace before a real estate listing)
+// check an action against the identity's rules without performing it. A
+// real estate seller must own the identity the listing is checked against.
+#[query]
+fn check_jurisdiction_policy(identity_id: String, action: PolicyAction) -> Result<()> {
+    validate_identity_id(&identity_id)?;
+    let identity = IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id))
+        .ok_or(Error::NotFound("Identity not found".to_string()))?;
+    if let PolicyAction::ListRealEstate { seller } = &action {
+        if identity.owner != *seller {
+            return Err(Error::Unauthorized);
+        }
+    }
+    evaluate_jurisdiction_policy(&identity, &action).map_err(Error::PolicyViolation)
+}
+
+//=============================================================================
+// RISK ASSESSMENT
+//=============================================================================
+
+fn risk_inputs(identity: &Identity, now: u64) -> RiskInputs {
+    let mut inputs = RiskInputs::default();
+    ASSET_VERIFICATIONS.with(|verifications| {
+        let verifications = verifications.borrow();
+        for asset_id in &identity.linked_assets {
+            match verifications.get(asset_id).and_then(|v| v.fraud_score) {
+                Some(fraud_score) => inputs.asset_fraud_scores.push(fraud_score),
+                None => inputs.unverified_assets += 1,
+            }
+        }
+    });
+    inputs.recent_rate_limit_violations = RATE_LIMIT_VIOLATIONS
+        .with(|violations| violations.borrow().get(&identity.owner.to_text()))
+        .map_or(0, |record| recent_violations(&record, now));
+    inputs
+}
+
+// Recomputes the identity's risk assessment in place; callers store it.
+fn reassess_risk(identity: &mut Identity, now: u64) {
+    let inputs = risk_inputs(identity, now);
+    identity.risk_assessment = assess_risk(identity, &inputs, now);
+}
+
+fn refresh_stored_risk(identity_id: &str) -> Result<RiskAssessment> {
+    IDENTITIES.with(|identities| {
+        let mut identities = identities.borrow_mut();
+        let mut identity = identities
+            .get(&identity_id.to_string())
+            .ok_or(Error::NotFound("Identity not found".to_string()))?;
+        reassess_risk(&mut identity, time());
+        let assessment = identity.risk_assessment.clone();
+        identities.insert(identity_id.to_string(), identity);
+        Ok(assessment)
+    })
+}
+
+// Time-dependent factors (account age, expiring checks) only change the
+// stored assessment when something triggers a reassessment; this lets the
+// owner or a compliance officer force one.
+#[update]
+fn refresh_risk_assessment(identity_id: String) -> Result<RiskAssessment> {
+    validate_identity_id(&identity_id)?;
+    let owner = IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id))
+        .map(|identity| identity.owner)
+        .ok_or(Error::NotFound("Identity not found".to_string()))?;
+    if owner != caller() {
+        is_compliance_officer()?;
+    }
+    refresh_stored_risk(&identity_id)
+}
+
+//=============================================================================
+// REPUTATION
+//=============================================================================
+
+// Types the awards recorded before reputation sources existed and re-derives
+// the scores of the identities that had any.
+fn migrate_legacy_reputation_events() {
+    let now = time();
+    IDENTITIES.with(|identities| {
+        let mut identities = identities.borrow_mut();
+        let migrated: Vec<(String, Identity)> = identities
+            .iter()
+            .filter_map(|(identity_id, mut identity)| {
+                if !migrate_legacy_reputation(&mut identity.reputation_history) {
+                    return None;
+                }
+                identity.reputation_score = explain_reputation(&identity, now).score;
+                Some((identity_id, identity))
+            })
+            .collect();
+        for (identity_id, identity) in migrated {
+            identities.insert(identity_id, identity);
+        }
+    });
+}
+
+// Registered submitters (marketplace, lending) and compliance officers.
+fn is_reputation_submitter() -> Result<()> {
+    let caller = caller();
+    if REPUTATION_CONFIG.with(|config| config.borrow().get().trusted_submitters.contains(&caller)) {
+        return Ok(());
+    }
+    is_compliance_officer()
+}
+
+// Appends a typed event, re-derives the score from the whole history and
+// audits the change. A `reference` (trade, loan or asset ID) may be used only
+// once per source, so retried submissions are not counted twice.
+fn record_reputation_event(
+    identity_id: &str,
+    source: ReputationSource,
+    reason: String,
+    reference: Option<String>,
+) -> Result<f64> {
+    validate_reputation_event(&reason, reference.as_deref()).map_err(Error::InvalidInput)?;
+    let now = time();
+
+    let (old_score, new_score) = IDENTITIES.with(|identities| {
+        let mut identities = identities.borrow_mut();
+        let mut identity = identities
+            .get(&identity_id.to_string())
+            .ok_or(Error::NotFound("Identity not found".to_string()))?;
+
+        if reference.is_some()
+            && identity
+                .reputation_history
+                .iter()
+                .any(|event| event.source == Some(source) && event.reference == reference)
+        {
+            return Err(Error::AlreadyRecorded);
+        }
+
+        let old_score = identity.reputation_score;
+        identity.reputation_history.push(ReputationEvent {
+            event_type: source.event_type(),
+            score_change: source.profile().points,
+            timestamp: now,
+            reason: reason.clone(),
+            verified_by: Some(caller()),
+            source: Some(source),
+            reference: reference.clone(),
+        });
+        prune_reputation_history(&mut identity.reputation_history, now);
+        identity.reputation_score = explain_reputation(&identity, now).score;
+        identity.updated_at = now;
+
+        let new_score = identity.reputation_score;
+        identities.insert(identity_id.to_string(), identity);
+        Ok((old_score, new_score))
+    })?;
+
+    create_audit_entry(
+        AuditOperation::UpdateIdentity,
+        identity_id.to_string(),
+        "reputation_update".to_string(),
+        AuditDetails {
+            payload: AuditPayload::ReputationUpdated {
+                old_score,
+                new_score,
+                change: new_score - old_score,
+            },
+            sensitive_data_redacted: false,
+            related_entities: reference.into_iter().collect(),
+            compliance_notes: Some(reason),
+        },
+        OperationResult::Success,
+    );
+    Ok(new_score)
+}
+
+// Removes the event recorded for `reference`, e.g. when the report behind a
+// penalty is overturned, and re-derives the score.
+fn retract_reputation_event(
+    identity_id: &str,
+    source: ReputationSource,
+    reference: String,
+    reason: String,
+) -> Result<f64> {
+    let now = time();
+
+    let (old_score, new_score) = IDENTITIES.with(|identities| {
+        let mut identities = identities.borrow_mut();
+        let mut identity = identities
+            .get(&identity_id.to_string())
+            .ok_or(Error::NotFound("Identity not found".to_string()))?;
+
+        let old_score = identity.reputation_score;
+        identity.reputation_history.retain(|event| {
+            event.source != Some(source) || event.reference.as_deref() != Some(reference.as_str())
+        });
+        identity.reputation_score = explain_reputation(&identity, now).score;
+        identity.updated_at = now;
+
+        let new_score = identity.reputation_score;
+        identities.insert(identity_id.to_string(), identity);
+        Ok((old_score, new_score))
+    })?;
+
+    create_audit_entry(
+        AuditOperation::UpdateIdentity,
+        identity_id.to_string(),
+        "reputation_update".to_string(),
+        AuditDetails {
+            payload: AuditPayload::ReputationUpdated {
+                old_score,
+                new_score,
+                change: new_score - old_score,
+            },
+            sensitive_data_redacted: false,
+            related_entities: vec![reference],
+            compliance_notes: Some(reason),
+        },
+        OperationResult::Success,
+    );
+    Ok(new_score)
+}
+
+// Returns the identity's new score.
+#[update]
+fn submit_reputation_event(
+    identity_id: String,
+    source: ReputationSource,
+    reason: String,
+    reference: Option<String>,
+) -> Result<f64> {
+    emergency_pause_check()?;
+    validate_identity_id(&identity_id)?;
+    is_reputation_submitter()?;
+    record_reputation_event(&identity_id, source, reason, reference)
+}
+
+// The score as of now, with each source's decayed total and cap. The stored
+// `reputation_score` is only re-derived when an event is recorded.
+#[query]
+fn get_reputation_explanation(identity_id: String) -> Result<ReputationExplanation> {
+    validate_identity_id(&identity_id)?;
+    IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id))
+        .map(|identity| explain_reputation(&identity, time()))
+        .ok_or(Error::NotFound("Identity not found".to_string()))
+}
+
+#[update]
+fn set_reputation_submitter(principal: Principal, trusted: bool) -> Result<()> {
+    is_admin()?;
+    REPUTATION_CONFIG.with(|config| {
+        let mut config = config.borrow_mut();
+        let mut updated = config.get().clone();
+        updated.trusted_submitters.retain(|p| *p != principal);
+        if trusted {
+            updated.trusted_submitters.push(principal);
+        }
+        let _ = config.set(updated);
+    });
+    Ok(())
+}
+
+// Applies a marketplace or lending outcome to the parties' identities and
+// returns how many were updated. Only the configured canister may report
+// each kind of outcome. Already-applied outcomes are skipped, so reporters
+// can safely retry.
+#[update]
+fn report_transaction_outcome(outcome: TransactionOutcome) -> Result<u32, String> {
+    let config = REPUTATION_CONFIG.with(|config| config.borrow().get().clone());
+    if outcome.reporter(&config) != Some(caller()) {
+        return Err("Caller is not the reporting canister for this outcome".to_string());
+    }
+
+    let mut applied = 0;
+    for (party, source, reason, reference) in outcome.reputation_events() {
+        let identity_ids: Vec<String> = IDENTITIES.with(|identities| {
+            identities
+                .borrow()
+                .iter()
+                .filter(|(_, identity)| identity.owner == party)
+                .map(|(id, _)| id)
+                .collect()
+        });
+        for identity_id in identity_ids {
+            match record_reputation_event(
+                &identity_id,
+                source,
+                reason.clone(),
+                Some(reference.clone()),
+            ) {
+                Ok(_) => applied += 1,
+                Err(Error::AlreadyRecorded) => {}
+                Err(e) => return Err(format!("{:?}", e)),
+            }
+        }
+    }
+    Ok(applied)
+}
+
+#[update]
+fn set_outcome_reporters(
+    marketplace_canister: Option<Principal>,
+    lending_canister: Option<Principal>,
+) -> Result<()> {
+    is_admin()?;
+    REPUTATION_CONFIG.with(|config| {
+        let mut config = config.borrow_mut();
+        let mut updated = config.get().clone();
+        updated.marketplace_canister = marketplace_canister;
+        updated.lending_canister = lending_canister;
+        let _ = config.set(updated);
+    });
+    Ok(())
+}
+
+//=============================================================================
+// IDENTITY REPORTS
+//=============================================================================
+
+fn get_identity_report(report_id: u64) -> Result<IdentityReport> {
+    IDENTITY_REPORTS
+        .with(|reports| reports.borrow().get(&report_id))
+        .ok_or(Error::NotFound("Report not found".to_string()))
+}
+
+fn audit_report(report: &IdentityReport, payload: AuditPayload, notes: Option<String>) {
+    create_audit_entry(
+        AuditOperation::IdentityReport,
+        report.identity_id.clone(),
+        "identity_report".to_string(),
+        AuditDetails {
+            payload,
+            sensitive_data_redacted: false,
+            related_entities: vec![report.reputation_reference()],
+            compliance_notes: notes,
+        },
+        OperationResult::Success,
+    );
+}
+
+// Replaces the identity's verification status and returns the previous one.
+fn set_verification_status(
+    identity_id: &str,
+    status: VerificationStatus,
+    reason: String,
+) -> Result<VerificationStatus> {
+    let now = time();
+    let previous = IDENTITIES.with(|identities| {
+        let mut identities = identities.borrow_mut();
+        let mut identity = identities
+            .get(&identity_id.to_string())
+            .ok_or(Error::NotFound("Identity not found".to_string()))?;
+        let previous = std::mem::replace(&mut identity.verification_status, status.clone());
+        identity.updated_at = now;
+        identities.insert(identity_id.to_string(), identity);
+        Ok(previous)
+    })?;
+
+    create_audit_entry(
+        AuditOperation::UpdateIdentity,
+        identity_id.to_string(),
+        "identity".to_string(),
+        AuditDetails {
+            payload: AuditPayload::VerificationStatusChanged {
+                from: previous.clone(),
+                to: status,
+            },
+            sensitive_data_redacted: false,
+            related_entities: vec![],
+            compliance_notes: Some(reason),
+        },
+        OperationResult::Success,
+    );
+    Ok(previous)
+}
+
+// Lets a counterparty flag an identity for compliance review. Reporters must
+// own an identity of their own, cannot report themselves, and may keep one
+// open report per identity and MAX_OPEN_REPORTS_PER_REPORTER overall.
+// Evidence must be files the reporter uploaded. Returns the report ID.
+#[update]
+fn report_identity(
+    identity_id: String,
+    category: ReportCategory,
+    evidence_file_ids: Vec<String>,
+    description: String,
+) -> Result<u64> {
+    emergency_pause_check()?;
+    check_rate_limit(RateLimitedOperation::ReportIdentity)?;
+    validate_identity_id(&identity_id)?;
+    validate_report(&description, &evidence_file_ids).map_err(Error::InvalidInput)?;
+
+    let reporter = caller();
+    if reporter == Principal::anonymous() {
+        return Err(Error::Unauthorized);
+    }
+
+    let (target_owner, reporter_has_identity) = IDENTITIES.with(|identities| {
+        let identities = identities.borrow();
+        let target_owner = identities.get(&identity_id).map(|identity| identity.owner);
+        let reporter_has_identity = identities
+            .iter()
+            .any(|(_, identity)| identity.owner == reporter);
+        (target_owner, reporter_has_identity)
+    });
+    let target_owner = target_owner.ok_or(Error::NotFound("Identity not found".to_string()))?;
+    if target_owner == reporter {
+        return Err(Error::InvalidInput(
+            "Cannot report your own identity".to_string(),
+        ));
+    }
+    if !reporter_has_identity {
+        return Err(Error::Unauthorized);
+    }
+
+    for (i, file_id) in evidence_file_ids.iter().enumerate() {
+        if evidence_file_ids[..i].contains(file_id) {
+            return Err(Error::InvalidInput(format!(
+                "Evidence file {} listed twice",
+                file_id
+            )));
+        }
+        let uploaded_by_reporter = FILE_STORAGE.with(|storage| {
+            storage
+                .borrow()
+                .files
+                .get(file_id)
+                .is_some_and(|file| file.metadata.uploaded_by == reporter)
+        });
+        if !uploaded_by_reporter {
+            return Err(Error::InvalidInput(format!(
+                "Evidence file {} not found",
+                file_id
+            )));
+        }
+    }
+
+    let now = time();
+    let report = IDENTITY_REPORTS.with(|reports| {
+        let mut reports = reports.borrow_mut();
+        let open: Vec<IdentityReport> = reports
+            .iter()
+            .map(|(_, report)| report)
+            .filter(|report| report.reporter == reporter && report.status == ReportStatus::Open)
+            .collect();
+        if open.iter().any(|report| report.identity_id == identity_id) {
+            return Err(Error::InvalidInput(
+                "You already have an open report against this identity".to_string(),
+            ));
+        }
+        if open.len() >= MAX_OPEN_REPORTS_PER_REPORTER {
+            return Err(Error::InvalidInput(format!(
+                "At most {} reports may await review at once",
+                MAX_OPEN_REPORTS_PER_REPORTER
+            )));
+        }
+
+        let id = reports.last_key_value().map_or(1, |(id, _)| id + 1);
+        let report = IdentityReport {
+            id,
+            identity_id: identity_id.clone(),
+            reporter,
+            category,
+            evidence_file_ids,
+            description,
+            created_at: now,
+            status: ReportStatus::Open,
+            resolution: None,
+            appeal: None,
+        };
+        reports.insert(id, report.clone());
+        Ok(report)
+    })?;
+
+    audit_report(
+        &report,
+        AuditPayload::IdentityReported {
+            report_id: report.id,
+            category,
+            evidence_files: report.evidence_file_ids.len() as u32,
+        },
+        None,
+    );
+    Ok(report.id)
+}
+
+// Open reports and appeals awaiting a decision, oldest first.
+#[query]
+fn get_report_queue() -> Result<Vec<IdentityReport>> {
+    is_compliance_officer()?;
+    Ok(IDENTITY_REPORTS.with(|reports| {
+        reports
+            .borrow()
+            .iter()
+            .map(|(_, report)| report)
+            .filter(|report| {
+                matches!(
+                    report.status,
+                    ReportStatus::Open | ReportStatus::UnderAppeal
+                )
+            })
+            .collect()
+    }))
+}
+
+// Every report against the identity, for reviewers.
+#[query]
+fn get_identity_reports(identity_id: String) -> Result<Vec<IdentityReport>> {
+    is_compliance_officer()?;
+    Ok(IDENTITY_REPORTS.with(|reports| {
+        reports
+            .borrow()
+            .iter()
+            .map(|(_, report)| report)
+            .filter(|report| report.identity_id == identity_id)
+            .collect()
+    }))
+}
+
+// Reports filed by the caller.
+#[query]
+fn get_my_reports() -> Vec<IdentityReport> {
+    let caller = caller();
+    IDENTITY_REPORTS.with(|reports| {
+        reports
+            .borrow()
+            .iter()
+            .map(|(_, report)| report)
+            .filter(|report| report.reporter == caller)
+            .collect()
+    })
+}
+
+// Reports that were upheld against the caller's identity, so the owner can
+// see the decision and appeal it. Open and dismissed reports are not shown.
+#[query]
+fn get_reports_against(identity_id: String) -> Result<Vec<ReportNotice>> {
+    validate_identity_id(&identity_id)?;
+    if !caller_owns_identity(&identity_id) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(IDENTITY_REPORTS.with(|reports| {
+        reports
+            .borrow()
+            .iter()
+            .map(|(_, report)| report)
+            .filter(|report| {
+                report.identity_id == identity_id
+                    && !matches!(report.status, ReportStatus::Open | ReportStatus::Dismissed)
+            })
+            .map(|report| report.notice())
+            .collect()
+    }))
+}
+
+// Evidence files belong to the reporter, so reviewers fetch them through the
+// report rather than with download_file.
+#[query]
+fn download_report_evidence(report_id: u64, file_id: String) -> Result<Vec<u8>, String> {
+    is_compliance_officer().map_err(|_| "Access denied".to_string())?;
+    let report = get_identity_report(report_id).map_err(|_| "Report not found".to_string())?;
+    if !report.evidence_file_ids.contains(&file_id) {
+        return Err("File is not evidence for this report".to_string());
+    }
+    FILE_STORAGE.with(|storage| storage.borrow().get_file(&file_id, report.reporter))
+}
+
+// Upheld reports add a FraudReport reputation penalty; Suspended also sets
+// the identity's verification status to Suspended. Reviewers cannot decide
+// reports they filed or that concern their own identity.
+#[update]
+fn resolve_report(report_id: u64, outcome: ReportOutcome, note: String) -> Result<IdentityReport> {
+    emergency_pause_check()?;
+    is_compliance_officer()?;
+    validate_report_note(&note).map_err(Error::InvalidInput)?;
+
+    let mut report = get_identity_report(report_id)?;
+    if report.status != ReportStatus::Open {
+        return Err(Error::InvalidInput("Report is not open".to_string()));
+    }
+    let reviewer = caller();
+    if report.reporter == reviewer || caller_owns_identity(&report.identity_id) {
+        return Err(Error::Unauthorized);
+    }
+
+    let mut previous_status = None;
+    if outcome != ReportOutcome::Dismissed {
+        record_reputation_event(
+            &report.identity_id,
+            ReputationSource::FraudReport,
+            format!("Report {} upheld: {:?}", report.id, report.category),
+            Some(report.reputation_reference()),
+        )?;
+    }
+    if outcome == ReportOutcome::Suspended {
+        previous_status = Some(set_verification_status(
+            &report.identity_id,
+            VerificationStatus::Suspended,
+            format!("Suspended after report {}", report.id),
+        )?);
+    }
+
+    report.status = outcome.into();
+    report.resolution = Some(ReportResolution {
+        outcome,
+        note: note.clone(),
+        resolved_by: reviewer,
+        resolved_at: time(),
+        previous_status,
+    });
+    IDENTITY_REPORTS.with(|reports| reports.borrow_mut().insert(report.id, report.clone()));
+
+    audit_report(
+        &report,
+        AuditPayload::ReportResolved {
+            report_id: report.id,
+            outcome,
+        },
+        Some(note),
+    );
+    Ok(report)
+}
+
+// The reported owner may appeal an upheld report once, within
+// APPEAL_WINDOW_NS of the decision.
+#[update]
+fn appeal_report(report_id: u64, statement: String) -> Result<()> {
+    emergency_pause_check()?;
+    validate_report_note(&statement).map_err(Error::InvalidInput)?;
+
+    let mut report = get_identity_report(report_id)?;
+    if !caller_owns_identity(&report.identity_id) {
+        return Err(Error::Unauthorized);
+    }
+    let deadline = report
+        .appeal_deadline()
+        .ok_or(Error::InvalidInput("Report cannot be appealed".to_string()))?;
+    let now = time();
+    if now > deadline {
+        return Err(Error::OperationExpired);
+    }
+
+    report.status = ReportStatus::UnderAppeal;
+    report.appeal = Some(ReportAppeal {
+        statement,
+        filed_at: now,
+        granted: None,
+        note: None,
+        decided_by: None,
+        decided_at: None,
+    });
+    IDENTITY_REPORTS.with(|reports| reports.borrow_mut().insert(report.id, report.clone()));
+
+    audit_report(
+        &report,
+        AuditPayload::ReportAppealed {
+            report_id: report.id,
+        },
+        None,
+    );
+    Ok(())
+}
+
+// Appeals are decided by a different reviewer than the one who upheld the
+// report. Granting removes the penalty and lifts a suspension the report
+// imposed, restoring the status it replaced.
+#[update]
+fn resolve_appeal(report_id: u64, granted: bool, note: String) -> Result<IdentityReport> {
+    emergency_pause_check()?;
+    is_compliance_officer()?;
+    validate_report_note(&note).map_err(Error::InvalidInput)?;
+
+    let mut report = get_identity_report(report_id)?;
+    let (Some(resolution), Some(appeal)) = (report.resolution.clone(), report.appeal.as_mut())
+    else {
+        return Err(Error::InvalidInput("Report has no appeal".to_string()));
+    };
+    if report.status != ReportStatus::UnderAppeal {
+        return Err(Error::InvalidInput("Appeal already decided".to_string()));
+    }
+    let reviewer = caller();
+    if resolution.resolved_by == reviewer
+        || report.reporter == reviewer
+        || caller_owns_identity(&report.identity_id)
+    {
+        return Err(Error::Unauthorized);
+    }
+
+    let now = time();
+    appeal.granted = Some(granted);
+    appeal.note = Some(note.clone());
+    appeal.decided_by = Some(reviewer);
+    appeal.decided_at = Some(now);
+
+    if granted {
+        retract_reputation_event(
+            &report.identity_id,
+            ReputationSource::FraudReport,
+            report.reputation_reference(),
+            format!("Report {} overturned on appeal", report.id),
+        )?;
+        // Another report that still stands keeps the identity suspended
+        let suspended_by_other = IDENTITY_REPORTS.with(|reports| {
+            reports.borrow().iter().any(|(id, other)| {
+                id != report.id
+                    && other.identity_id == report.identity_id
+                    && matches!(
+                        other.status,
+                        ReportStatus::Suspended | ReportStatus::UnderAppeal
+                    )
+                    && other
+                        .resolution
+                        .as_ref()
+                        .is_some_and(|r| r.outcome == ReportOutcome::Suspended)
+            })
+        });
+        let suspended = IDENTITIES
+            .with(|identities| identities.borrow().get(&report.identity_id))
+            .is_some_and(|identity| {
+                matches!(identity.verification_status, VerificationStatus::Suspended)
+            });
+        if let (ReportOutcome::Suspended, Some(previous), true, false) = (
+            resolution.outcome,
+            resolution.previous_status,
+            suspended,
+            suspended_by_other,
+        ) {
+            set_verification_status(
+                &report.identity_id,
+                previous,
+                format!("Suspension lifted on appeal of report {}", report.id),
+            )?;
+        }
+        report.status = ReportStatus::Overturned;
+    } else {
+        report.status = resolution.outcome.into();
+    }
+    IDENTITY_REPORTS.with(|reports| reports.borrow_mut().insert(report.id, report.clone()));
+
+    audit_report(
+        &report,
+        AuditPayload::ReportAppealDecided {
+            report_id: report.id,
+            granted,
+        },
+        Some(note),
+    );
+    Ok(report)
+}
+
+//=============================================================================
+// CROSS-CHAIN BRIDGE FUNCTIONS
+//=============================================================================
+
+#[query]
+fn quote_bridge(
+    from_chain: ChainType,
+    to_chain: ChainType,
+    asset_type: String,
+    amount: u128,
+) -> Result<BridgeQuote, String> {
+    bridge_pause_check()?;
+    let secret = QUOTE_SECRET
+        .with(|secret| *secret.borrow())
+        .ok_or("Bridge quotes are not available yet")?;
+
+    let mut quote = BRIDGE_SERVICE.with(|service| {
+        service.borrow().quote_bridge(
+            &from_chain,
+            &to_chain,
+            &asset_type,
+            amount,
+            caller(),
+            time(),
+        )
+    })?;
+    quote.quote_id = quote_digest(&secret, &quote)?;
+    Ok(quote)
+}
+
+// Opens a request on the terms of a quote from `quote_bridge`. The quote is
+// accepted only if its ID still authenticates every field.
+#[update]
+async fn initiate_cross_chain_bridge(
+    identity_id: String,
+    quote: BridgeQuote,
+    from_address: String,
+    to_address: String,
+) -> Result<String, String> {
+    bridge_pause_check()?;
+    let caller = caller();
+
+    authorize_bridge_parties(&identity_id, &quote, &from_address, &to_address)?;
+
+    let secret = QUOTE_SECRET
+        .with(|secret| *secret.borrow())
+        .ok_or("Bridge quotes are not available yet")?;
+    if quote_digest(&secret, &quote)? != quote.quote_id {
+        return Err("Invalid bridge quote".to_string());
+    }
+
+    let request_id = generate_unique_id("bridge", |id| {
+        BRIDGE_SERVICE.with(|service| service.borrow().has_request(id))
+    })
+    .await
+    .map_err(|e| format!("{:?}", e))?;
+
+    BRIDGE_SERVICE.with(|service| {
         service.borrow_mut().initiate_bridge_request(
-            from_chain,
-            to_chain,
-            asset_type,
-            amount,
+            request_id,
+            identity_id,
+            &quote,
             from_address,
             to_address,
             caller,
@@ -1895,39 +4203,825 @@ async fn initiate_cross_chain_bridge(
     })
 }
 
-#[query]
-fn get_bridge_request(request_id: String) -> Result<BridgeRequest, String> {
-    BRIDGE_SERVICE.with(
-        |service| match service.borrow().get_bridge_request(&request_id) {
-            Some(request) => Ok(request.clone()),
-            None => Err("Bridge request not found".to_string()),
-        },
+// The caller must own a non-sanctioned identity with the required KYC level
+// whose verified wallets (or the admin allow-list) cover both addresses.
+fn authorize_bridge_parties(
+    identity_id: &str,
+    quote: &BridgeQuote,
+    from_address: &str,
+    to_address: &str,
+) -> Result<(), String> {
+    validate_wallet_address(from_address, &quote.from_chain)
+        .map_err(|e| format!("Invalid source address: {:?}", e))?;
+    validate_wallet_address(to_address, &quote.to_chain)
+        .map_err(|e| format!("Invalid destination address: {:?}", e))?;
+
+    let identity = IDENTITIES
+        .with(|identities| identities.borrow().get(&identity_id.to_string()))
+        .filter(|identity| identity.owner == caller())
+        .ok_or("Identity not found or not owned by caller")?;
+
+    for (chain, address) in [
+        (&quote.from_chain, from_address),
+        (&quote.to_chain, to_address),
+    ] {
+        screen_wallet_address(identity_id, chain, address).map_err(|e| match e {
+            Error::VerificationFailed(reason) => reason,
+            other => format!("{:?}", other),
+        })?;
+    }
+
+    let compliance = identity.compliance_status.effective(time());
+    if matches!(compliance.sanctions_check, SanctionsStatus::Sanctioned) {
+        return Err("Sanctioned identities cannot use the bridge".to_string());
+    }
+
+    enforce_jurisdiction_policy(
+        identity_id,
+        &identity,
+        &PolicyAction::Bridge {
+            from_chain: quote.from_chain.clone(),
+            to_chain: quote.to_chain.clone(),
+            asset_type: quote.asset_type.clone(),
+            amount: quote.amount,
+        },
+    )
+    .map_err(|e| match e {
+        Error::PolicyViolation(violation) => violation.to_string(),
+        other => format!("{:?}", other),
+    })?;
+
+    BRIDGE_SERVICE.with(|service| {
+        let service = service.borrow();
+        let min_kyc_level = service.min_kyc_level();
+        if compliance.kyc_level < min_kyc_level {
+            return Err(format!("Bridge requires KYC level {:?}", min_kyc_level));
+        }
+
+        for (chain, address, role) in [
+            (&quote.from_chain, from_address, "Source"),
+            (&quote.to_chain, to_address, "Destination"),
+        ] {
+            let linked = identity.linked_wallets.iter().any(|wallet| {
+                wallet.chain_type == *chain
+                    && matches!(
+                        wallet.verification_status,
+                        WalletVerificationStatus::Verified
+                    )
+                    && same_address(chain, &wallet.address, address)
+            });
+            if !linked && !service.is_address_allowed(chain, address) {
+                return Err(format!(
+                    "{} address is not a verified wallet of this identity",
+                    role
+                ));
+            }
+        }
+        Ok(())
+    })
+}
+
+// Keyed hash over every quote field except the ID itself.
+fn quote_digest(secret: &[u8; 32], quote: &BridgeQuote) -> Result<String, String> {
+    let terms = Encode!(
+        &quote.user_principal,
+        &quote.from_chain,
+        &quote.to_chain,
+        &quote.asset_type,
+        &quote.amount,
+        &quote.fee,
+        &quote.destination_gas_fee,
+        &quote.receive_amount,
+        &quote.source_decimals,
+        &quote.destination_decimals,
+        &quote.expires_at
+    )
+    .map_err(|e| format!("Failed to encode quote: {}", e))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(secret);
+    hasher.update(&terms);
+    Ok(format!("quote_{}", hex::encode(hasher.finalize())))
+}
+
+async fn load_quote_secret() {
+    match raw_rand().await {
+        Ok((bytes,)) if bytes.len() >= 32 => {
+            let mut secret = [0u8; 32];
+            secret.copy_from_slice(&bytes[..32]);
+            QUOTE_SECRET.with(|s| *s.borrow_mut() = Some(secret));
+        }
+        _ => ic_cdk::println!("Failed to load bridge quote secret"),
+    }
+}
+
+fn start_quote_service() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || ic_cdk::spawn(load_quote_secret()));
+}
+
+#[query]
+fn get_bridge_request(request_id: String) -> Result<BridgeRequest, String> {
+    BRIDGE_SERVICE.with(
+        |service| match service.borrow().get_bridge_request(&request_id) {
+            Some(request) => Ok(request.public_view()),
+            None => Err("Bridge request not found".to_string()),
+        },
+    )
+}
+
+#[query]
+fn get_user_bridge_history(
+    filter: BridgeHistoryFilter,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<BridgeRequestPage, String> {
+    let caller = caller();
+    let limit = limit.map_or(DEFAULT_PAGE_SIZE, |l| l as usize);
+    BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow()
+            .get_user_bridge_history(caller, &filter, cursor.as_deref(), limit)
+    })
+}
+
+#[query]
+fn list_bridge_requests(
+    filter: BridgeHistoryFilter,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<BridgeRequestPage> {
+    is_admin()?;
+    let limit = limit.map_or(DEFAULT_PAGE_SIZE, |l| l as usize);
+    BRIDGE_SERVICE
+        .with(|service| {
+            service
+                .borrow()
+                .list_bridge_requests(&filter, cursor.as_deref(), limit)
+        })
+        .map_err(Error::InvalidInput)
+}
+
+#[query]
+fn get_bridge_route_stats() -> Result<Vec<RouteStats>> {
+    is_admin()?;
+    Ok(BRIDGE_SERVICE.with(|service| service.borrow().route_stats()))
+}
+
+fn audit_bridge_transition(request_id: &str, transition: &BridgeStatusTransition) {
+    create_audit_entry(
+        AuditOperation::BridgeStatusChange,
+        request_id.to_string(),
+        "bridge_request".to_string(),
+        AuditDetails {
+            payload: AuditPayload::BridgeStatusChanged {
+                from: transition.from.clone(),
+                to: transition.to.clone(),
+                transaction_hash: transition.transaction_hash.clone(),
+            },
+            sensitive_data_redacted: false,
+            related_entities: transition.transaction_hash.iter().cloned().collect(),
+            compliance_notes: Some("Bridge request status changed".to_string()),
+        },
+        OperationResult::Success,
+    );
+}
+
+#[update]
+async fn update_bridge_status(
+    request_id: String,
+    status: BridgeStatus,
+    transaction_hash: Option<String>,
+) -> Result<(), String> {
+    bridge_pause_check()?;
+    let caller = caller();
+
+    let transition = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .update_bridge_status(&request_id, status, transaction_hash, caller)
+    })?;
+
+    audit_bridge_transition(&request_id, &transition);
+    Ok(())
+}
+
+// Cancellation stays available during an emergency pause so users can back
+// out of requests that have not locked funds.
+#[update]
+async fn cancel_bridge_request(request_id: String) -> Result<(), String> {
+    let caller = caller();
+
+    let transition = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .cancel_bridge_request(&request_id, caller)
+    })?;
+
+    audit_bridge_transition(&request_id, &transition);
+    Ok(())
+}
+
+fn bridge_pause_check() -> Result<(), String> {
+    emergency_pause_check().map_err(|e| format!("{:?}", e))
+}
+
+fn audit_circuit_breaker(route: &BridgeRoute, trip: &CircuitBreakerTrip) {
+    create_audit_entry(
+        AuditOperation::BridgeCircuitBreaker,
+        format!("{:?}->{:?}", route.from_chain, route.to_chain),
+        "bridge_route".to_string(),
+        AuditDetails {
+            payload: AuditPayload::CircuitBreakerTripped {
+                reason: trip.reason.clone(),
+                tripped_by: trip.tripped_by,
+            },
+            sensitive_data_redacted: false,
+            related_entities: vec![trip.tripped_by.to_string()],
+            compliance_notes: Some("Bridge route circuit breaker tripped".to_string()),
+        },
+        OperationResult::Success,
+    );
+}
+
+#[update]
+async fn report_bridge_mismatch(request_id: String, details: String) -> Result<(), String> {
+    let caller = caller();
+
+    let (route, trip) = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .report_mismatch(&request_id, &details, caller)
+    })?;
+
+    audit_circuit_breaker(&route, &trip);
+    Ok(())
+}
+
+#[query]
+fn get_bridge_route_statuses() -> Result<Vec<RouteStatus>> {
+    is_admin()?;
+    Ok(BRIDGE_SERVICE.with(|service| service.borrow().route_statuses()))
+}
+
+#[update]
+fn reset_bridge_route(route: BridgeRoute) -> Result<()> {
+    is_admin()?;
+    let trip = BRIDGE_SERVICE.with(|service| service.borrow_mut().reset_route(&route));
+
+    create_audit_entry(
+        AuditOperation::BridgeCircuitBreaker,
+        format!("{:?}->{:?}", route.from_chain, route.to_chain),
+        "bridge_route".to_string(),
+        AuditDetails {
+            payload: AuditPayload::CircuitBreakerReset {
+                was_tripped: trip.is_some(),
+            },
+            sensitive_data_redacted: false,
+            related_entities: Vec::new(),
+            compliance_notes: Some("Bridge route reset by admin".to_string()),
+        },
+        OperationResult::Success,
+    );
+    Ok(())
+}
+
+#[update]
+fn set_bridge_limits(limits: BridgeLimits) -> Result<()> {
+    is_admin()?;
+    if limits.max_daily_requests == 0 {
+        return Err(Error::InvalidInput(
+            "Daily request limit must be positive".to_string(),
+        ));
+    }
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().set_limits(limits));
+    Ok(())
+}
+
+#[update]
+fn set_bridge_min_kyc_level(level: KYCLevel) -> Result<()> {
+    is_admin()?;
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().set_min_kyc_level(level));
+    Ok(())
+}
+
+// Allow-listed addresses (e.g. exchange or custody wallets) may be used
+// without being linked to the caller's identity.
+#[update]
+fn add_bridge_allowed_address(chain_type: ChainType, address: String) -> Result<()> {
+    is_admin()?;
+    validate_wallet_address(&address, &chain_type)?;
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().allow_address(chain_type, address));
+    Ok(())
+}
+
+#[update]
+fn remove_bridge_allowed_address(chain_type: ChainType, address: String) -> Result<()> {
+    is_admin()?;
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().disallow_address(&chain_type, &address));
+    Ok(())
+}
+
+#[update]
+fn add_bridge_relayer(relayer: Principal) -> Result<()> {
+    is_admin()?;
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().add_relayer(relayer));
+    Ok(())
+}
+
+#[update]
+fn remove_bridge_relayer(relayer: Principal) -> Result<()> {
+    is_admin()?;
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().remove_relayer(&relayer));
+    Ok(())
+}
+
+#[update]
+async fn submit_bridge_deposit(request_id: String, tx_hash: String) -> Result<(), String> {
+    bridge_pause_check()?;
+    let caller = caller();
+
+    BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .submit_deposit_transaction(&request_id, caller, tx_hash)
+    })
+}
+
+// Canonicalises chain RPC responses so that all replicas agree on them.
+#[query]
+fn transform_chain_rpc(args: TransformArgs) -> HttpResponse {
+    transform_rpc_response(args.response, &args.context)
+}
+
+const BRIDGE_WATCH_INTERVAL: Duration = Duration::from_secs(60);
+const BRIDGE_WATCH_BATCH_SIZE: usize = 10;
+
+fn start_bridge_watcher() {
+    ic_cdk_timers::set_timer_interval(BRIDGE_WATCH_INTERVAL, || {
+        ic_cdk::spawn(watch_bridge_deposits())
+    });
+}
+
+// Clears BRIDGE_WATCHER_RUNNING when a watcher run ends, including runs
+// that trap after an await.
+struct BridgeWatcherGuard;
+
+impl Drop for BridgeWatcherGuard {
+    fn drop(&mut self) {
+        BRIDGE_WATCHER_RUNNING.with(|running| *running.borrow_mut() = false);
+    }
+}
+
+// Advances Initiated requests whose deposit reached the configured
+// confirmation depth to SourceLocked, and fails those whose deposit does not
+// match the request or cannot be found. Then handles timeouts and payout
+// broadcasts.
+async fn watch_bridge_deposits() {
+    if emergency_pause_check().is_err() {
+        return;
+    }
+    if BRIDGE_WATCHER_RUNNING.with(|running| running.replace(true)) {
+        return;
+    }
+    let _guard = BridgeWatcherGuard;
+
+    let pending = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .pending_deposit_checks(time(), BRIDGE_WATCH_BATCH_SIZE)
+    });
+
+    for (request, config) in pending {
+        let next_status = match observe_deposit(&request, &config).await {
+            Ok(DepositCheck::Confirmed) => BridgeStatus::SourceLocked,
+            Ok(DepositCheck::Mismatch(reason)) => BridgeStatus::Failed { reason },
+            Ok(DepositCheck::Pending) => {
+                BRIDGE_SERVICE.with(|service| {
+                    service
+                        .borrow_mut()
+                        .record_deposit_seen(&request.request_id)
+                });
+                continue;
+            }
+            Ok(DepositCheck::NotFound) => {
+                let transition = BRIDGE_SERVICE.with(|service| {
+                    service
+                        .borrow_mut()
+                        .record_deposit_miss(&request.request_id, time(), id())
+                });
+                if let Some(transition) = transition {
+                    audit_bridge_transition(&request.request_id, &transition);
+                }
+                continue;
+            }
+            Err(e) => {
+                ic_cdk::println!("Deposit check for {} failed: {}", request.request_id, e);
+                continue;
+            }
+        };
+
+        let result = BRIDGE_SERVICE.with(|service| {
+            service
+                .borrow_mut()
+                .record_deposit_outcome(&request.request_id, next_status, id())
+        });
+        if let Ok(transition) = result {
+            audit_bridge_transition(&request.request_id, &transition);
+        }
+    }
+
+    process_bridge_payouts().await;
+}
+
+// Times out stalled requests, (re)broadcasts signed releases and refunds
+// whose backoff has elapsed, and completes or refunds releases once their
+// chain shows the outcome.
+async fn process_bridge_payouts() {
+    let now = time();
+    let expired =
+        BRIDGE_SERVICE.with(|service| service.borrow_mut().expire_stalled_requests(now, id()));
+    for (request_id, transition) in expired {
+        audit_bridge_transition(&request_id, &transition);
+    }
+
+    let due = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow()
+            .pending_broadcasts(now, BRIDGE_WATCH_BATCH_SIZE)
+    });
+    for (request_id, kind, transaction, config) in due {
+        let outcome = match broadcast_request(&config, &transaction.raw_transaction) {
+            Ok(request) => match rpc_outcall(request).await {
+                Ok(RpcOutcome::Broadcast) => Ok(()),
+                Ok(RpcOutcome::Error(e)) | Err(e) => Err(e),
+                Ok(_) => Err("Unexpected RPC outcome".to_string()),
+            },
+            Err(e) => Err(e),
+        };
+
+        let transition = BRIDGE_SERVICE.with(|service| {
+            service
+                .borrow_mut()
+                .record_broadcast(&request_id, kind, outcome, time(), id())
+        });
+        if let Some(transition) = transition {
+            audit_bridge_transition(&request_id, &transition);
+        }
+    }
+
+    let stalled = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow()
+            .pending_release_checks(now, BRIDGE_WATCH_BATCH_SIZE)
+    });
+    for (request_id, release, config) in stalled {
+        let check = match check_release(&config, &release).await {
+            Ok(check) => check,
+            Err(e) => {
+                ic_cdk::println!("Release check for {} failed: {}", request_id, e);
+                ReleaseCheck::Pending
+            }
+        };
+
+        let transition = BRIDGE_SERVICE.with(|service| {
+            service
+                .borrow_mut()
+                .record_release_check(&request_id, check, time(), id())
+        });
+        if let Some(transition) = transition {
+            audit_bridge_transition(&request_id, &transition);
+        }
+    }
+}
+
+// Whether a signed release can still confirm. On Bitcoin every input is
+// looked up until one shows the outcome.
+async fn check_release(
+    config: &ChainConfig,
+    release: &SignedRelease,
+) -> Result<ReleaseCheck, String> {
+    for request in release_check_requests(config, release)? {
+        match rpc_outcall(request).await? {
+            RpcOutcome::Release(ReleaseCheck::Pending) => continue,
+            RpcOutcome::Release(check) => return Ok(check),
+            RpcOutcome::Error(e) => return Err(e),
+            _ => return Err("Unexpected RPC outcome".to_string()),
+        }
+    }
+    Ok(ReleaseCheck::Pending)
+}
+
+async fn observe_deposit(
+    request: &BridgeRequest,
+    config: &ChainConfig,
+) -> Result<DepositCheck, String> {
+    let tx_hash = request
+        .source_tx_hash
+        .as_ref()
+        .ok_or("No deposit transaction submitted")?;
+
+    let observation = match rpc_outcall(deposit_lookup_request(config, tx_hash)?).await? {
+        RpcOutcome::Deposit(observation) => observation,
+        RpcOutcome::NotFound => return Ok(DepositCheck::NotFound),
+        RpcOutcome::Error(e) => return Err(e),
+        _ => return Err("Unexpected RPC outcome".to_string()),
+    };
+    let Some(block_height) = observation.block_height else {
+        return Ok(DepositCheck::Pending);
+    };
+
+    // Depth is decided after consensus: the deposit is confirmed once the
+    // block giving it the configured number of confirmations exists
+    let height = confirmation_height(block_height, config.confirmation_blocks as u64);
+    match rpc_outcall(block_probe_request(config, height)?).await? {
+        RpcOutcome::BlockReached(true) => Ok(check_deposit(request, &observation)),
+        RpcOutcome::BlockReached(false) => Ok(DepositCheck::Pending),
+        RpcOutcome::Error(e) => Err(e),
+        _ => Err("Unexpected RPC outcome".to_string()),
+    }
+}
+
+async fn rpc_outcall(
+    request: ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument,
+) -> Result<RpcOutcome, String> {
+    let cycles = outcall_cycles(&request);
+    let (response,) = http_request(request, cycles)
+        .await
+        .map_err(|(code, msg)| format!("HTTPS outcall failed: {:?} - {}", code, msg))?;
+
+    serde_json::from_slice(&response.body).map_err(|e| format!("Invalid RPC outcome: {}", e))
+}
+
+async fn bridge_public_key(chain: &ChainType) -> Result<Vec<u8>, String> {
+    let key_name = THRESHOLD_KEY_NAME.with(|k| k.borrow().get().clone());
+    let derivation_path = derivation_path(chain);
+
+    let result = match signature_scheme(chain)? {
+        SignatureScheme::EcdsaSecp256k1 => ecdsa_public_key(EcdsaPublicKeyArgument {
+            canister_id: None,
+            derivation_path,
+            key_id: EcdsaKeyId {
+                curve: EcdsaCurve::Secp256k1,
+                name: key_name,
+            },
+        })
+        .await
+        .map(|(response,)| response.public_key),
+        SignatureScheme::SchnorrEd25519 => schnorr_public_key(SchnorrPublicKeyArgument {
+            canister_id: None,
+            derivation_path,
+            key_id: SchnorrKeyId {
+                algorithm: SchnorrAlgorithm::Ed25519,
+                name: key_name,
+            },
+        })
+        .await
+        .map(|(response,)| response.public_key),
+    };
+
+    result.map_err(|(code, msg)| format!("Failed to fetch bridge public key: {:?} - {}", code, msg))
+}
+
+async fn threshold_sign(
+    chain: &ChainType,
+    scheme: SignatureScheme,
+    payload: Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let key_name = THRESHOLD_KEY_NAME.with(|k| k.borrow().get().clone());
+    let derivation_path = derivation_path(chain);
+
+    let result = match scheme {
+        SignatureScheme::EcdsaSecp256k1 => sign_with_ecdsa(SignWithEcdsaArgument {
+            message_hash: payload,
+            derivation_path,
+            key_id: EcdsaKeyId {
+                curve: EcdsaCurve::Secp256k1,
+                name: key_name,
+            },
+        })
+        .await
+        .map(|(response,)| response.signature),
+        SignatureScheme::SchnorrEd25519 => sign_with_schnorr(SignWithSchnorrArgument {
+            message: payload,
+            derivation_path,
+            key_id: SchnorrKeyId {
+                algorithm: SchnorrAlgorithm::Ed25519,
+                name: key_name,
+            },
+        })
+        .await
+        .map(|(response,)| response.signature),
+    };
+
+    result.map_err(|(code, msg)| format!("Threshold signing failed: {:?} - {}", code, msg))
+}
+
+// Address the bridge releases funds from on `chain`; relayers fund it and
+// look up its nonce, UTXOs or blockhash for `sign_bridge_release`.
+#[update]
+async fn get_bridge_release_address(chain: ChainType) -> Result<String, String> {
+    let public_key = bridge_public_key(&chain).await?;
+
+    match chain {
+        ChainType::Ethereum | ChainType::Polygon | ChainType::Avalanche => {
+            evm_address_from_public_key(&public_key)
+        }
+        ChainType::Solana => Ok(bs58::encode(public_key).into_string()),
+        _ => Ok(hex::encode(public_key)),
+    }
+}
+
+// Builds the destination-chain release for a SourceLocked request, signs it
+// with the canister's threshold key and moves the request to TargetMinting.
+#[update]
+async fn sign_bridge_release(request_id: String, params: ReleaseParams) -> Result<String, String> {
+    bridge_pause_check()?;
+    let caller = caller();
+
+    let (request, kind, amount) = BRIDGE_SERVICE.with(|service| {
+        let service = service.borrow();
+        if !service.is_relayer(&caller) {
+            return Err("Only bridge relayers can sign releases".to_string());
+        }
+        let request = service
+            .get_bridge_request(&request_id)
+            .ok_or("Bridge request not found")?;
+        if !matches!(request.status, BridgeStatus::SourceLocked) {
+            return Err("Only SourceLocked requests can be released".to_string());
+        }
+        service.ensure_route_open(&request.from_chain, &request.to_chain)?;
+        let kind = service.release_kind(&request)?;
+        let amount = service.release_amount(&request);
+        Ok((request, kind, amount))
+    })?;
+
+    let release = sign_payout(
+        &request.to_chain,
+        &request.to_address,
+        &kind,
+        amount,
+        &params,
     )
+    .await?;
+    let tx_hash = release.tx_hash.clone();
+
+    let outflow = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .check_release_outflow(&request_id, caller)
+    });
+    if let Err(trip) = outflow {
+        audit_circuit_breaker(&BridgeRoute::of(&request), &trip);
+        return Err(format!("Bridge route is paused: {}", trip.reason));
+    }
+
+    let transition = BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow_mut()
+            .record_release(&request_id, release, caller)
+    })?;
+    audit_bridge_transition(&request_id, &transition);
+
+    Ok(tx_hash)
 }
 
-#[query]
-fn get_user_bridge_history() -> Vec<BridgeRequest> {
+// Builds and threshold-signs a payout of `amount` to `to_address` on `chain`.
+async fn sign_payout(
+    chain: &ChainType,
+    to_address: &str,
+    kind: &ReleaseKind,
+    amount: u128,
+    params: &ReleaseParams,
+) -> Result<SignedRelease, String> {
+    let config = BRIDGE_SERVICE
+        .with(|service| service.borrow().chain_config_for(chain))
+        .ok_or("Chain is not supported")?;
+    check_release_params(params, &config)?;
+
+    let public_key = bridge_public_key(chain).await?;
+    let unsigned = build_release(chain, to_address, kind, amount, params, &public_key)?;
+
+    let scheme = unsigned.chain_scheme();
+    let mut signatures = Vec::new();
+    for payload in unsigned.signing_payloads() {
+        signatures.push(threshold_sign(chain, scheme, payload).await?);
+    }
+    let (raw_transaction, tx_hash) = unsigned.finalize(&signatures, &public_key)?;
+
+    Ok(SignedRelease {
+        chain: chain.clone(),
+        raw_transaction,
+        tx_hash,
+        signed_at: time(),
+        guard: unsigned.guard(&public_key)?,
+    })
+}
+
+// Asks for the refund of a RefundPending request. A relayer then signs it
+// with `sign_bridge_refund`.
+#[update]
+fn claim_refund(request_id: String) -> Result<(), String> {
+    bridge_pause_check()?;
     let caller = caller();
-    BRIDGE_SERVICE.with(|service| service.borrow().get_user_bridge_history(caller))
+    let now = time();
+    BRIDGE_SERVICE.with(|service| service.borrow_mut().claim_refund(&request_id, caller, now))?;
+
+    create_audit_entry(
+        AuditOperation::BridgeRefund,
+        request_id,
+        "bridge_request".to_string(),
+        AuditDetails {
+            payload: AuditPayload::RefundClaimed { claimed_at: now },
+            sensitive_data_redacted: false,
+            related_entities: vec![caller.to_text()],
+            compliance_notes: Some("Bridge refund claimed by owner".to_string()),
+        },
+        OperationResult::Success,
+    );
+    Ok(())
 }
 
+// Signs a claimed refund back to the request's `from_address` on the source
+// chain. The relayer supplies the chain state; fees are capped and the chain
+// id must match the configuration, as for releases. The watcher broadcasts
+// it and marks the request Refunded.
 #[update]
-async fn update_bridge_status(
-    request_id: String,
-    status: BridgeStatus,
-    transaction_hash: Option<String>,
-) -> Result<(), String> {
-    // TODO: Add admin authorization check
+async fn sign_bridge_refund(request_id: String, params: ReleaseParams) -> Result<String, String> {
+    bridge_pause_check()?;
+    let caller = caller();
+
+    let (request, kind, amount) = BRIDGE_SERVICE.with(|service| {
+        let service = service.borrow();
+        let request = service.refund_to_sign(&request_id, caller)?;
+        let (kind, amount) = service.refund_payout(&request)?;
+        Ok::<_, String>((request, kind, amount))
+    })?;
+
+    let refund = sign_payout(
+        &request.from_chain,
+        &request.from_address,
+        &kind,
+        amount,
+        &params,
+    )
+    .await?;
+    let tx_hash = refund.tx_hash.clone();
+
     BRIDGE_SERVICE.with(|service| {
         service
             .borrow_mut()
-            .update_bridge_status(&request_id, status, transaction_hash)
-    })
+            .record_refund(&request_id, refund, caller)
+    })?;
+
+    create_audit_entry(
+        AuditOperation::BridgeRefund,
+        request_id,
+        "bridge_request".to_string(),
+        AuditDetails {
+            payload: AuditPayload::RefundSigned {
+                chain: request.from_chain.clone(),
+                to_address: request.from_address.clone(),
+                amount,
+                transaction_hash: tx_hash.clone(),
+            },
+            sensitive_data_redacted: false,
+            related_entities: vec![caller.to_text(), tx_hash.clone()],
+            compliance_notes: Some("Bridge refund signed".to_string()),
+        },
+        OperationResult::Success,
+    );
+
+    Ok(tx_hash)
+}
+
+#[update]
+fn set_bridge_route_timeouts(route: BridgeRoute, timeouts: RouteTimeouts) -> Result<()> {
+    is_admin()?;
+    BRIDGE_SERVICE
+        .with(|service| service.borrow_mut().set_route_timeouts(route, timeouts))
+        .map_err(Error::InvalidInput)
+}
+
+#[update]
+fn set_threshold_key_name(key_name: String) -> Result<()> {
+    is_admin()?;
+    THRESHOLD_KEY_NAME.with(|k| {
+        let _ = k.borrow_mut().set(key_name);
+    });
+    Ok(())
 }
 
 #[query]
-fn calculate_bridge_fee(from_chain: ChainType, amount: u64) -> BridgeFee {
-    BRIDGE_SERVICE.with(|service| service.borrow().calculate_bridge_fee(&from_chain, amount))
+fn calculate_bridge_fee(
+    from_chain: ChainType,
+    asset_type: String,
+    amount: u128,
+) -> Result<BridgeFee, String> {
+    BRIDGE_SERVICE.with(|service| {
+        service
+            .borrow()
+            .calculate_bridge_fee(&from_chain, &asset_type, amount)
+    })
 }
 
 #[query]
@@ -1935,15 +5029,197 @@ fn get_supported_chains() -> Vec<ChainConfig> {
     BRIDGE_SERVICE.with(|service| service.borrow().get_supported_chains())
 }
 
+// Chain configuration changes are validated up front and applied once the
+// admin threshold has signed the multi-sig operation.
+#[update]
+async fn add_chain_config(config: ChainConfig) -> Result<String> {
+    is_admin()?;
+    validate_chain_config(&config).map_err(Error::InvalidInput)?;
+    let exists =
+        BRIDGE_SERVICE.with(|service| service.borrow().has_chain_config(&config.chain_type));
+    if exists {
+        return Err(Error::InvalidInput(
+            "Chain is already configured".to_string(),
+        ));
+    }
+
+    let operation_data =
+        serde_json::to_string(&config).map_err(|e| Error::InvalidInput(e.to_string()))?;
+    create_multi_sig_operation("add_chain_config".to_string(), operation_data).await
+}
+
+#[update]
+async fn update_chain_config(config: ChainConfig) -> Result<String> {
+    is_admin()?;
+    validate_chain_config(&config).map_err(Error::InvalidInput)?;
+    let exists =
+        BRIDGE_SERVICE.with(|service| service.borrow().has_chain_config(&config.chain_type));
+    if !exists {
+        return Err(Error::NotFound("Chain configuration not found".to_string()));
+    }
+
+    let operation_data =
+        serde_json::to_string(&config).map_err(|e| Error::InvalidInput(e.to_string()))?;
+    create_multi_sig_operation("update_chain_config".to_string(), operation_data).await
+}
+
+#[update]
+async fn disable_chain(chain: ChainType) -> Result<String> {
+    is_admin()?;
+    let exists = BRIDGE_SERVICE.with(|service| service.borrow().has_chain_config(&chain));
+    if !exists {
+        return Err(Error::NotFound("Chain configuration not found".to_string()));
+    }
+
+    let operation_data =
+        serde_json::to_string(&chain).map_err(|e| Error::InvalidInput(e.to_string()))?;
+    create_multi_sig_operation("disable_chain".to_string(), operation_data).await
+}
+
+//=============================================================================
+// RATE LIMIT FUNCTIONS
+//=============================================================================
+
+// The caller's current standing: any ban, recent violations and the quota
+// left under each per-operation limit and the combined limit.
+#[query]
+fn get_rate_limit_status() -> RateLimitStatus {
+    let caller = caller();
+    let now = time();
+
+    let config = RATE_LIMIT_CONFIG.with(|config| config.borrow().get().clone());
+    let policy = RATE_LIMIT_POLICY.with(|policy| policy.borrow().get().clone());
+
+    RATE_LIMITS.with(|limits| {
+        let limits_map = limits.borrow();
+
+        let levels = RateLimitedOperation::ALL
+            .iter()
+            .map(|op| (Some(*op), op.default_limit(&config)))
+            .chain(std::iter::once((None, policy.max_operations_per_hour)));
+        let quotas = levels
+            .map(|(operation, default)| {
+                let limit = policy.limit_for(&caller, operation, default);
+                let used = limits_map
+                    .get(&tracker_key(&caller, operation))
+                    .map_or(0, |tracker| sliding_window_usage(&tracker, now));
+                OperationQuota {
+                    operation,
+                    limit,
+                    used,
+                    remaining: limit.saturating_sub(used),
+                }
+            })
+            .collect();
+
+        let violations =
+            RATE_LIMIT_VIOLATIONS.with(|violations| violations.borrow().get(&caller.to_text()));
+        RateLimitStatus {
+            allow_listed: policy.is_allow_listed(&caller),
+            banned_until: violations
+                .as_ref()
+                .and_then(|record| record.banned_until)
+                .filter(|until| now < *until),
+            recent_violations: violations.map_or(0, |record| recent_violations(&record, now)),
+            quotas,
+        }
+    })
+}
+
+#[update]
+fn set_rate_limit_thresholds(
+    max_operations_per_hour: u32,
+    ban_threshold: u32,
+    base_ban_ns: u64,
+    max_ban_ns: u64,
+) -> Result<()> {
+    is_admin()?;
+    if ban_threshold == 0 || base_ban_ns == 0 || max_ban_ns < base_ban_ns {
+        return Err(Error::InvalidInput(
+            "Ban threshold and base ban must be non-zero and the maximum ban at least the base"
+                .to_string(),
+        ));
+    }
+
+    update_rate_limit_policy(|policy| {
+        policy.max_operations_per_hour = max_operations_per_hour;
+        policy.ban_threshold = ban_threshold;
+        policy.base_ban_ns = base_ban_ns;
+        policy.max_ban_ns = max_ban_ns;
+    });
+    Ok(())
+}
+
+// Sets (or with `max_per_hour: None`, removes) the hourly limit for one
+// principal; `operation: None` targets the combined limit.
+#[update]
+fn set_rate_limit_override(
+    principal: Principal,
+    operation: Option<RateLimitedOperation>,
+    max_per_hour: Option<u32>,
+) -> Result<()> {
+    is_admin()?;
+    update_rate_limit_policy(|policy| {
+        policy
+            .overrides
+            .retain(|o| !(o.principal == principal && o.operation == operation));
+        if let Some(max_per_hour) = max_per_hour {
+            policy.overrides.push(RateLimitOverride {
+                principal,
+                operation,
+                max_per_hour,
+            });
+        }
+    });
+    Ok(())
+}
+
+#[update]
+fn set_rate_limit_allow_listed(principal: Principal, allow_listed: bool) -> Result<()> {
+    is_admin()?;
+    update_rate_limit_policy(|policy| {
+        policy.allow_list.retain(|p| *p != principal);
+        if allow_listed {
+            policy.allow_list.push(principal);
+        }
+    });
+    Ok(())
+}
+
+// Lifts a ban and forgets the principal's violations.
+#[update]
+fn clear_rate_limit_ban(principal: Principal) -> Result<()> {
+    is_admin()?;
+    RATE_LIMIT_VIOLATIONS.with(|violations| violations.borrow_mut().remove(&principal.to_text()));
+    Ok(())
+}
+
+fn update_rate_limit_policy(f: impl FnOnce(&mut RateLimitPolicy)) {
+    RATE_LIMIT_POLICY.with(|cell| {
+        let mut policy = cell.borrow().get().clone();
+        f(&mut policy);
+        let _ = cell.borrow_mut().set(policy);
+    });
+}
+
 //=============================================================================
 // FILE STORAGE FUNCTIONS
 //=============================================================================
 
+async fn generate_file_id() -> Result<String, String> {
+    generate_unique_id("file", |id| {
+        FILE_STORAGE.with(|storage| storage.borrow().files.contains_key(id))
+    })
+    .await
+    .map_err(|e| format!("{:?}", e))
+}
+
 #[update]
 async fn upload_file(request: FileUploadRequest) -> Result<FileUploadResponse, String> {
     let caller = caller();
+    let file_id = generate_file_id().await?;
 
-    FILE_STORAGE.with(|storage| storage.borrow_mut().upload_file(request, caller))
+    FILE_STORAGE.with(|storage| storage.borrow_mut().upload_file(file_id, request, caller))
 }
 
 #[query]
@@ -1981,6 +5257,234 @@ fn download_file(file_id: String) -> Result<Vec<u8>, String> {
     FILE_STORAGE.with(|storage| storage.borrow().get_file(&file_id, caller))
 }
 
+//=============================================================================
+// ENCRYPTED DOCUMENT VAULT FUNCTIONS
+//=============================================================================
+
+fn caller_owns_identity(identity_id: &str) -> bool {
+    let caller = caller();
+    IDENTITIES.with(|identities| {
+        identities
+            .borrow()
+            .get(&identity_id.to_string())
+            .is_some_and(|identity| identity.owner == caller)
+    })
+}
+
+#[update]
+async fn get_vault_public_key() -> Result<Vec<u8>, String> {
+    let config = VETKD_CONFIG.with(|c| c.borrow().get().clone());
+    vault_public_key(config).await
+}
+
+// Derives the caller's own document key before upload. Per-file keys are
+// bound to the caller; identity keys require ownership of the identity.
+#[update]
+async fn derive_vault_key(
+    key_scope: KeyScope,
+    transport_public_key: Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let caller = caller();
+
+    if let KeyScope::Identity { identity_id } = &key_scope {
+        if !caller_owns_identity(identity_id) {
+            return Err("Access denied".to_string());
+        }
+    }
+
+    let config = VETKD_CONFIG.with(|c| c.borrow().get().clone());
+    let input = vault_derivation_input(caller, &key_scope);
+    derive_encrypted_key(config, input, transport_public_key).await
+}
+
+#[update]
+async fn upload_encrypted_file(
+    request: EncryptedFileUploadRequest,
+) -> Result<FileUploadResponse, String> {
+    let caller = caller();
+
+    if let Some(identity_id) = &request.identity_id {
+        if !caller_owns_identity(identity_id) {
+            return Err("Access denied".to_string());
+        }
+    }
+
+    let file_id = generate_file_id().await?;
+    let key_name = VETKD_CONFIG.with(|c| c.borrow().get().key_name.clone());
+    FILE_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .upload_encrypted_file(file_id, request, caller, key_name)
+    })
+}
+
+// Returns the document key encrypted under the caller's transport key, for
+// the owner and principals the owner granted access to.
+#[update]
+async fn get_file_decryption_key(
+    file_id: String,
+    transport_public_key: Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    let caller = caller();
+
+    let (input, key_name) =
+        FILE_STORAGE.with(|storage| storage.borrow().key_derivation_input(&file_id, caller))?;
+
+    // Derive from the master key the document was encrypted under, which
+    // may differ from the one currently configured for new uploads
+    let mode = VETKD_CONFIG.with(|c| c.borrow().get().mode.clone());
+    let config = VetKdConfig { key_name, mode };
+    derive_encrypted_key(config, input, transport_public_key).await
+}
+
+#[update]
+async fn share_encrypted_file(file_id: String, grantee: Principal) -> Result<(), String> {
+    let caller = caller();
+
+    FILE_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .grant_key_access(&file_id, caller, grantee)
+    })
+}
+
+#[update]
+async fn revoke_encrypted_file_access(file_id: String, grantee: Principal) -> Result<(), String> {
+    let caller = caller();
+
+    FILE_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .revoke_key_access(&file_id, caller, grantee)
+    })
+}
+
+#[update]
+fn set_vetkd_config(config: VetKdConfig) -> Result<()> {
+    is_admin()?;
+    VETKD_CONFIG.with(|c| {
+        let _ = c.borrow_mut().set(config);
+    });
+    Ok(())
+}
+
+//=============================================================================
+// STORAGE MAINTENANCE
+//=============================================================================
+
+const JANITOR_INTERVAL: Duration = Duration::from_secs(300);
+const JANITOR_BATCH_SIZE: usize = 100; // Entries scanned per map per tick
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JanitorMetrics {
+    pub runs: u64,
+    pub last_run_at: u64,
+    pub last_run_removed: u64,
+    pub rate_limit_trackers_removed: u64,
+    pub violation_records_removed: u64,
+    pub multisig_operations_removed: u64,
+}
+
+// Each map is scanned in key order from where the previous tick stopped, so
+// a full pass over a large map is spread across several ticks.
+#[derive(Default)]
+struct JanitorState {
+    metrics: JanitorMetrics,
+    rate_limit_cursor: Option<String>,
+    violation_cursor: Option<String>,
+    multisig_cursor: Option<String>,
+}
+
+fn start_janitor() {
+    ic_cdk_timers::set_timer_interval(JANITOR_INTERVAL, run_janitor);
+}
+
+// Removes rate limit trackers and violation records that no longer affect
+// any decision and multi-sig operations that have expired or already executed.
+fn run_janitor() {
+    let now = time();
+    let (rate_limit_cursor, violation_cursor, multisig_cursor) = JANITOR_STATE.with(|state| {
+        let state = state.borrow();
+        (
+            state.rate_limit_cursor.clone(),
+            state.violation_cursor.clone(),
+            state.multisig_cursor.clone(),
+        )
+    });
+
+    let (trackers_removed, rate_limit_cursor) = RATE_LIMITS.with(|limits| {
+        prune_batch(&mut limits.borrow_mut(), rate_limit_cursor, |tracker| {
+            is_stale_tracker(tracker, now)
+        })
+    });
+    let (violations_removed, violation_cursor) = RATE_LIMIT_VIOLATIONS.with(|violations| {
+        prune_batch(&mut violations.borrow_mut(), violation_cursor, |record| {
+            is_stale_violation(record, now)
+        })
+    });
+    let (operations_removed, multisig_cursor) = MULTI_SIG_PENDING.with(|pending| {
+        prune_batch(&mut pending.borrow_mut(), multisig_cursor, |operation| {
+            operation.executed || now > operation.expires_at
+        })
+    });
+
+    JANITOR_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.rate_limit_cursor = rate_limit_cursor;
+        state.violation_cursor = violation_cursor;
+        state.multisig_cursor = multisig_cursor;
+
+        let metrics = &mut state.metrics;
+        metrics.runs += 1;
+        metrics.last_run_at = now;
+        metrics.last_run_removed = trackers_removed + violations_removed + operations_removed;
+        metrics.rate_limit_trackers_removed += trackers_removed;
+        metrics.violation_records_removed += violations_removed;
+        metrics.multisig_operations_removed += operations_removed;
+    });
+}
+
+// Scans up to JANITOR_BATCH_SIZE entries after `cursor` and removes the
+// stale ones. Returns the number removed and the cursor for the next batch,
+// which is None once the end of the map was reached.
+fn prune_batch<V: Storable>(
+    map: &mut StableBTreeMap<String, V, Memory>,
+    cursor: Option<String>,
+    is_stale: impl Fn(&V) -> bool,
+) -> (u64, Option<String>) {
+    let batch: Vec<(String, V)> = match cursor {
+        Some(cursor) => map
+            .range((
+                std::ops::Bound::Excluded(cursor),
+                std::ops::Bound::Unbounded,
+            ))
+            .take(JANITOR_BATCH_SIZE)
+            .collect(),
+        None => map.iter().take(JANITOR_BATCH_SIZE).collect(),
+    };
+
+    let next_cursor = if batch.len() < JANITOR_BATCH_SIZE {
+        None
+    } else {
+        batch.last().map(|(key, _)| key.clone())
+    };
+
+    let mut removed = 0;
+    for (key, value) in batch {
+        if is_stale(&value) {
+            map.remove(&key);
+            removed += 1;
+        }
+    }
+    (removed, next_cursor)
+}
+
+#[query]
+fn get_janitor_metrics() -> Result<JanitorMetrics> {
+    is_admin()?;
+    Ok(JANITOR_STATE.with(|state| state.borrow().metrics.clone()))
+}
+
 //=============================================================================
 // INITIALIZATION & UPGRADE HOOKS
 //=============================================================================
@@ -1996,6 +5500,17 @@ fn init() {
         config.admin = deployer;
         let _ = config_cell.borrow_mut().set(config);
     });
+    ADMIN_SET.with(|admin_set| {
+        let _ = admin_set.borrow_mut().set(AdminSet {
+            admins: vec![deployer],
+            threshold: 1,
+        });
+    });
+    start_bridge_watcher();
+    start_quote_service();
+    start_janitor();
+    certify_audit_head();
+
     ic_cdk::println!(
         "Enhanced Identity Canister initialized. Admin set to: {}",
         deployer
@@ -2009,6 +5524,12 @@ fn pre_upgrade() {
 
 #[post_upgrade]
 fn post_upgrade() {
+    migrate_legacy_audit_trail();
+    migrate_legacy_reputation_events();
+    certify_audit_head();
+    start_bridge_watcher();
+    start_quote_service();
+    start_janitor();
     ic_cdk::println!("Enhanced Identity Canister upgrade completed successfully");
 }
 
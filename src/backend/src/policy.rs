@@ -0,0 +1,196 @@
+use crate::{ChainType, CredentialStatus, CredentialType, Identity, KYCLevel};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Policy used for identities whose jurisdiction has none of its own.
+pub const DEFAULT_JURISDICTION: &str = "global";
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BridgeAmountLimit {
+    pub asset_type: String,
+    pub max_amount: u128, // In the asset's smallest unit on the source chain
+}
+
+// Rules applied to identities in one jurisdiction. Empty lists and None
+// leave the corresponding operation unrestricted.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct JurisdictionPolicy {
+    pub jurisdiction: String,
+    pub min_kyc_for_asset_linking: Option<KYCLevel>,
+    pub max_bridge_amounts: Vec<BridgeAmountLimit>,
+    pub blocked_chains: Vec<ChainType>,
+    pub real_estate_required_credentials: Vec<CredentialType>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PolicyRule {
+    MinKycForAssetLinking,
+    MaxBridgeAmount,
+    BlockedChain,
+    RealEstateCredentials,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct PolicyViolation {
+    pub jurisdiction: String,
+    pub rule: PolicyRule,
+    pub message: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Policy violation ({:?}, {}): {}",
+            self.rule, self.jurisdiction, self.message
+        )
+    }
+}
+
+// Operations subject to jurisdiction rules. ListRealEstate is evaluated on
+// behalf of the marketplace canister for the listing's seller.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum PolicyAction {
+    LinkWallet {
+        chain_type: ChainType,
+    },
+    LinkAsset {
+        asset_type: Option<String>,
+    },
+    Bridge {
+        from_chain: ChainType,
+        to_chain: ChainType,
+        asset_type: String,
+        amount: u128,
+    },
+    ListRealEstate {
+        seller: Principal,
+    },
+}
+
+pub fn is_real_estate(asset_type: &str) -> bool {
+    matches!(
+        asset_type
+            .to_ascii_lowercase()
+            .replace(['-', ' '], "_")
+            .as_str(),
+        "real_estate" | "realestate" | "property"
+    )
+}
+
+impl JurisdictionPolicy {
+    pub fn evaluate(
+        &self,
+        action: &PolicyAction,
+        identity: &Identity,
+        now: u64,
+    ) -> Result<(), PolicyViolation> {
+        let violation = |rule: PolicyRule, message: String| PolicyViolation {
+            jurisdiction: self.jurisdiction.clone(),
+            rule,
+            message,
+        };
+
+        match action {
+            PolicyAction::LinkWallet { chain_type } => self.check_chain(chain_type, &violation),
+            PolicyAction::LinkAsset { asset_type } => {
+                if let Some(required) = &self.min_kyc_for_asset_linking {
+                    let level = identity.compliance_status.effective(now).kyc_level;
+                    if level < *required {
+                        return Err(violation(
+                            PolicyRule::MinKycForAssetLinking,
+                            format!("Linking assets requires KYC level {:?}", required),
+                        ));
+                    }
+                }
+                match asset_type {
+                    Some(asset_type) if is_real_estate(asset_type) => {
+                        self.check_real_estate_credentials(identity, now, &violation)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            PolicyAction::Bridge {
+                from_chain,
+                to_chain,
+                asset_type,
+                amount,
+            } => {
+                self.check_chain(from_chain, &violation)?;
+                self.check_chain(to_chain, &violation)?;
+                let limit = self
+                    .max_bridge_amounts
+                    .iter()
+                    .find(|limit| limit.asset_type == *asset_type);
+                match limit {
+                    Some(limit) if *amount > limit.max_amount => Err(violation(
+                        PolicyRule::MaxBridgeAmount,
+                        format!(
+                            "Bridging more than {} {} is not permitted",
+                            limit.max_amount, asset_type
+                        ),
+                    )),
+                    _ => Ok(()),
+                }
+            }
+            PolicyAction::ListRealEstate { .. } => {
+                self.check_real_estate_credentials(identity, now, &violation)
+            }
+        }
+    }
+
+    fn check_chain(
+        &self,
+        chain: &ChainType,
+        violation: &impl Fn(PolicyRule, String) -> PolicyViolation,
+    ) -> Result<(), PolicyViolation> {
+        if self.blocked_chains.contains(chain) {
+            return Err(violation(
+                PolicyRule::BlockedChain,
+                format!("{:?} is not available in this jurisdiction", chain),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_real_estate_credentials(
+        &self,
+        identity: &Identity,
+        now: u64,
+        violation: &impl Fn(PolicyRule, String) -> PolicyViolation,
+    ) -> Result<(), PolicyViolation> {
+        let missing: Vec<&CredentialType> = self
+            .real_estate_required_credentials
+            .iter()
+            .filter(|required| {
+                !identity.credentials.iter().any(|credential| {
+                    credential.credential_type == **required
+                        && matches!(credential.status, CredentialStatus::Active)
+                        && credential.expiration_date.is_none_or(|at| at > now)
+                })
+            })
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(violation(
+                PolicyRule::RealEstateCredentials,
+                format!("Real estate requires active credentials: {:?}", missing),
+            ))
+        }
+    }
+}
+
+pub fn validate_jurisdiction(jurisdiction: &str) -> Result<(), String> {
+    if jurisdiction.is_empty()
+        || jurisdiction.len() > 32
+        || !jurisdiction
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Jurisdiction must be 1-32 letters, digits, '-' or '_'".to_string());
+    }
+    Ok(())
+}
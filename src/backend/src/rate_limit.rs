@@ -0,0 +1,244 @@
+use crate::{RateLimitConfig, RateLimitTracker, MAX_REPORTS_PER_HOUR};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+pub const RATE_LIMIT_WINDOW_NS: u64 = 3600 * 1_000_000_000; // 1 hour
+pub const VIOLATION_DECAY_NS: u64 = 24 * 3600 * 1_000_000_000; // 24 hours
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RateLimitedOperation {
+    CreateIdentity,
+    AddCredential,
+    LinkWallet,
+    LinkAsset,
+    VerificationRequest,
+    ReportIdentity,
+}
+
+impl RateLimitedOperation {
+    pub const ALL: [RateLimitedOperation; 6] = [
+        RateLimitedOperation::CreateIdentity,
+        RateLimitedOperation::AddCredential,
+        RateLimitedOperation::LinkWallet,
+        RateLimitedOperation::LinkAsset,
+        RateLimitedOperation::VerificationRequest,
+        RateLimitedOperation::ReportIdentity,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RateLimitedOperation::CreateIdentity => "create_identity",
+            RateLimitedOperation::AddCredential => "add_credential",
+            RateLimitedOperation::LinkWallet => "link_wallet",
+            RateLimitedOperation::LinkAsset => "link_asset",
+            RateLimitedOperation::VerificationRequest => "verification_request",
+            RateLimitedOperation::ReportIdentity => "report_identity",
+        }
+    }
+
+    pub fn default_limit(&self, config: &RateLimitConfig) -> u32 {
+        match self {
+            RateLimitedOperation::CreateIdentity => config.max_identity_creates_per_hour,
+            RateLimitedOperation::AddCredential => config.max_credential_adds_per_hour,
+            RateLimitedOperation::LinkWallet => config.max_wallet_links_per_hour,
+            RateLimitedOperation::LinkAsset => config.max_asset_links_per_hour,
+            RateLimitedOperation::VerificationRequest => config.max_verification_requests_per_hour,
+            RateLimitedOperation::ReportIdentity => MAX_REPORTS_PER_HOUR,
+        }
+    }
+}
+
+// Replaces the hourly limit for one principal, either for a single
+// operation or (with `operation: None`) for all operations combined.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimitOverride {
+    pub principal: Principal,
+    pub operation: Option<RateLimitedOperation>,
+    pub max_per_hour: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimitPolicy {
+    pub max_operations_per_hour: u32, // All operations combined, per principal
+    pub ban_threshold: u32,           // Violations within 24h before bans start
+    pub base_ban_ns: u64,             // Doubles with every further violation
+    pub max_ban_ns: u64,
+    pub overrides: Vec<RateLimitOverride>,
+    pub allow_list: Vec<Principal>, // Never rate limited
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            max_operations_per_hour: 60,
+            ban_threshold: 3,
+            base_ban_ns: 60 * 1_000_000_000,       // 1 minute
+            max_ban_ns: 24 * 3600 * 1_000_000_000, // 24 hours
+            overrides: Vec::new(),
+            allow_list: Vec::new(),
+        }
+    }
+}
+
+impl RateLimitPolicy {
+    pub fn limit_for(
+        &self,
+        principal: &Principal,
+        operation: Option<RateLimitedOperation>,
+        default: u32,
+    ) -> u32 {
+        self.overrides
+            .iter()
+            .find(|o| o.principal == *principal && o.operation == operation)
+            .map_or(default, |o| o.max_per_hour)
+    }
+
+    pub fn is_allow_listed(&self, principal: &Principal) -> bool {
+        self.allow_list.contains(principal)
+    }
+
+    // 0 below the threshold, then base, 2x base, 4x base, ... up to the cap.
+    pub fn ban_duration(&self, violations: u32) -> u64 {
+        if violations < self.ban_threshold {
+            return 0;
+        }
+        let doublings = (violations - self.ban_threshold).min(32);
+        self.base_ban_ns
+            .saturating_mul(1u64 << doublings)
+            .min(self.max_ban_ns)
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct OperationQuota {
+    pub operation: Option<RateLimitedOperation>, // None for the combined limit
+    pub limit: u32,
+    pub used: u32,
+    pub remaining: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimitStatus {
+    pub allow_listed: bool,
+    pub banned_until: Option<u64>,
+    pub recent_violations: u32,
+    pub quotas: Vec<OperationQuota>,
+}
+
+// A principal's recent rate limit violations and the ban they earned. Kept
+// apart from the operation trackers, keyed by the principal's text.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ViolationRecord {
+    pub principal: Principal,
+    pub count: u32,
+    pub last_violation: u64,
+    pub banned_until: Option<u64>,
+}
+
+impl ViolationRecord {
+    pub fn new(principal: Principal) -> Self {
+        Self {
+            principal,
+            count: 0,
+            last_violation: 0,
+            banned_until: None,
+        }
+    }
+
+    pub fn is_banned(&self, now: u64) -> bool {
+        self.banned_until.is_some_and(|until| now < until)
+    }
+}
+
+pub fn tracker_key(principal: &Principal, operation: Option<RateLimitedOperation>) -> String {
+    match operation {
+        Some(operation) => format!("{}:{}", principal, operation.label()),
+        None => format!("{}:all", principal),
+    }
+}
+
+pub fn new_tracker(principal: Principal, operation_type: &str, now: u64) -> RateLimitTracker {
+    RateLimitTracker {
+        principal,
+        operation_type: operation_type.to_string(),
+        count: 0,
+        window_start: now - now % RATE_LIMIT_WINDOW_NS,
+        last_operation: now,
+        previous_count: Some(0),
+    }
+}
+
+// Rolls the tracker's fixed windows forward so that `window_start` is the
+// start of the window containing `now`.
+fn advance_window(tracker: &mut RateLimitTracker, now: u64) {
+    let current_start = now - now % RATE_LIMIT_WINDOW_NS;
+    if current_start <= tracker.window_start {
+        return;
+    }
+    let previous = if current_start - tracker.window_start == RATE_LIMIT_WINDOW_NS {
+        tracker.count
+    } else {
+        0
+    };
+    tracker.previous_count = Some(previous);
+    tracker.count = 0;
+    tracker.window_start = current_start;
+}
+
+// Sliding-window estimate of operations in the last hour: the previous
+// window's count weighted by how much of it still overlaps, plus the current
+// window's count.
+pub fn sliding_window_usage(tracker: &RateLimitTracker, now: u64) -> u32 {
+    let mut tracker = tracker.clone();
+    advance_window(&mut tracker, now);
+
+    let elapsed = now - tracker.window_start;
+    let previous = tracker.previous_count.unwrap_or(0) as u64;
+    let carried = previous * (RATE_LIMIT_WINDOW_NS - elapsed) / RATE_LIMIT_WINDOW_NS;
+    (carried + tracker.count as u64).min(u32::MAX as u64) as u32
+}
+
+// Admits one operation if it fits under `limit`.
+pub fn try_consume(tracker: &mut RateLimitTracker, limit: u32, now: u64) -> bool {
+    if sliding_window_usage(tracker, now) >= limit {
+        return false;
+    }
+    advance_window(tracker, now);
+    tracker.count += 1;
+    tracker.last_operation = now;
+    true
+}
+
+// Records a violation and applies the ban it earns, if any. Violations older
+// than a day are forgotten.
+pub fn record_violation(record: &mut ViolationRecord, policy: &RateLimitPolicy, now: u64) {
+    if now.saturating_sub(record.last_violation) > VIOLATION_DECAY_NS {
+        record.count = 0;
+    }
+    record.count += 1;
+    record.last_violation = now;
+
+    let ban = policy.ban_duration(record.count);
+    if ban > 0 {
+        record.banned_until = Some(now + ban);
+    }
+}
+
+pub fn recent_violations(record: &ViolationRecord, now: u64) -> u32 {
+    if now.saturating_sub(record.last_violation) > VIOLATION_DECAY_NS {
+        0
+    } else {
+        record.count
+    }
+}
+
+// An operation tracker can be deleted once both sliding windows are empty.
+pub fn is_stale_tracker(tracker: &RateLimitTracker, now: u64) -> bool {
+    now.saturating_sub(tracker.last_operation) > 2 * RATE_LIMIT_WINDOW_NS
+}
+
+// A violation record can be deleted once its violations have decayed and
+// any ban has ended.
+pub fn is_stale_violation(record: &ViolationRecord, now: u64) -> bool {
+    !record.is_banned(now) && now.saturating_sub(record.last_violation) > VIOLATION_DECAY_NS
+}
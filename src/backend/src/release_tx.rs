@@ -0,0 +1,1100 @@
+//! Release and refund payout transactions for the bridge.
+//!
+//! Building and finalising transactions is pure so it can be exercised
+//! without a replica; the threshold signing calls live in `lib.rs`.
+
+use crate::{ChainConfig, ChainType};
+use candid::CandidType;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+const BITCOIN_DUST_LIMIT: u64 = 546;
+const RBF_SEQUENCE: u32 = 0xffff_fffd;
+const SIGHASH_ALL: u8 = 0x01;
+// keccak256("mint(address,uint256)")[..4]
+const EVM_MINT_SELECTOR: [u8; 4] = [0x40, 0xc1, 0x0f, 0x19];
+const BITCOIN_TX_VERSION: u32 = 2;
+
+// Ceilings on relayer-supplied fees, so a faulty relayer cannot spend the
+// bridge's balance on gas.
+const MAX_EVM_GAS_LIMIT: u64 = 500_000;
+const MAX_EVM_FEE_PER_GAS: u128 = 1_000_000_000_000; // 1,000 gwei
+const MAX_BITCOIN_FEE_SATOSHIS: u64 = 1_000_000; // 0.01 BTC
+const MAX_BITCOIN_UTXOS: usize = 100;
+
+/// Chain state a relayer supplies so the canister can build a release.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum ReleaseParams {
+    Evm {
+        chain_id: u64,
+        nonce: u64,
+        max_priority_fee_per_gas: u128,
+        max_fee_per_gas: u128,
+        gas_limit: u64,
+    },
+    Bitcoin {
+        utxos: Vec<BitcoinUtxo>,
+        fee_satoshis: u64,
+    },
+    Solana {
+        recent_blockhash: String,
+    },
+}
+
+/// A bridge-owned output; `txid` is in internal (little-endian) byte order.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BitcoinUtxo {
+    pub txid: Vec<u8>,
+    pub vout: u32,
+    pub value: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SignatureScheme {
+    EcdsaSecp256k1,
+    SchnorrEd25519,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SignedRelease {
+    pub chain: ChainType,
+    pub raw_transaction: Vec<u8>,
+    pub tx_hash: String,
+    pub signed_at: u64,
+    pub guard: ReleaseGuard,
+}
+
+/// The chain state a signed payout consumes. Once something else consumes
+/// it the payout can never confirm, which is what makes a refund safe.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum ReleaseGuard {
+    EvmNonce { sender: String, nonce: u64 },
+    BitcoinInputs { utxos: Vec<BitcoinUtxo> },
+    SolanaBlockhash { recent_blockhash: String },
+}
+
+/// What the release transfers on the destination chain.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReleaseKind {
+    /// Pay out the destination chain's native asset.
+    Native,
+    /// Ask the destination bridge contract to mint a wrapped asset.
+    Mint { contract: String },
+}
+
+#[derive(Clone, Debug)]
+pub enum UnsignedRelease {
+    Evm(UnsignedEvmTx),
+    Bitcoin(UnsignedBitcoinTx),
+    Solana(UnsignedSolanaTx),
+}
+
+#[derive(Clone, Debug)]
+pub struct UnsignedEvmTx {
+    chain_id: u64,
+    nonce: u64,
+    max_priority_fee_per_gas: u128,
+    max_fee_per_gas: u128,
+    gas_limit: u64,
+    to: [u8; 20],
+    value: u128,
+    data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct UnsignedBitcoinTx {
+    inputs: Vec<BitcoinUtxo>,
+    outputs: Vec<(u64, Vec<u8>)>, // (value, scriptPubKey)
+    public_key: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct UnsignedSolanaTx {
+    message: Vec<u8>,
+    recent_blockhash: String,
+}
+
+/// Threshold key derivation path for the bridge's account on `chain`.
+pub fn derivation_path(chain: &ChainType) -> Vec<Vec<u8>> {
+    let chain_label = match chain {
+        ChainType::Bitcoin => "bitcoin".to_string(),
+        ChainType::Ethereum => "ethereum".to_string(),
+        ChainType::Solana => "solana".to_string(),
+        ChainType::ICP => "icp".to_string(),
+        ChainType::Polygon => "polygon".to_string(),
+        ChainType::Avalanche => "avalanche".to_string(),
+        ChainType::Custom { name, chain_id } => format!("custom:{}:{}", name, chain_id),
+    };
+    vec![b"bridge_release".to_vec(), chain_label.into_bytes()]
+}
+
+pub fn signature_scheme(chain: &ChainType) -> Result<SignatureScheme, String> {
+    match chain {
+        ChainType::Bitcoin | ChainType::Ethereum | ChainType::Polygon | ChainType::Avalanche => {
+            Ok(SignatureScheme::EcdsaSecp256k1)
+        }
+        ChainType::Solana => Ok(SignatureScheme::SchnorrEd25519),
+        _ => Err("Releases to this chain are not supported".to_string()),
+    }
+}
+
+/// Builds the unsigned payout of `amount` to `to_address` on `chain`: the
+/// destination chain for releases, the source chain for refunds.
+/// `public_key` is the bridge's derived key on that chain (SEC1-compressed
+/// for ECDSA, 32 bytes for Ed25519).
+pub fn build_release(
+    chain: &ChainType,
+    to_address: &str,
+    kind: &ReleaseKind,
+    amount: u128,
+    params: &ReleaseParams,
+    public_key: &[u8],
+) -> Result<UnsignedRelease, String> {
+    match (chain, params) {
+        (
+            ChainType::Ethereum | ChainType::Polygon | ChainType::Avalanche,
+            ReleaseParams::Evm {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit,
+            },
+        ) => {
+            let recipient = parse_evm_address(to_address)?;
+            let (to, value, data) = match kind {
+                ReleaseKind::Native => (recipient, amount, Vec::new()),
+                ReleaseKind::Mint { contract } => {
+                    let mut data = EVM_MINT_SELECTOR.to_vec();
+                    data.extend_from_slice(&[0u8; 12]);
+                    data.extend_from_slice(&recipient);
+                    data.extend_from_slice(&[0u8; 16]);
+                    data.extend_from_slice(&amount.to_be_bytes());
+                    (parse_evm_address(contract)?, 0, data)
+                }
+            };
+
+            Ok(UnsignedRelease::Evm(UnsignedEvmTx {
+                chain_id: *chain_id,
+                nonce: *nonce,
+                max_priority_fee_per_gas: *max_priority_fee_per_gas,
+                max_fee_per_gas: *max_fee_per_gas,
+                gas_limit: *gas_limit,
+                to,
+                value,
+                data,
+            }))
+        }
+        (
+            ChainType::Bitcoin,
+            ReleaseParams::Bitcoin {
+                utxos,
+                fee_satoshis,
+            },
+        ) => {
+            if *kind != ReleaseKind::Native {
+                return Err("Bitcoin releases can only pay out BTC".to_string());
+            }
+            build_bitcoin_release(
+                to_address,
+                native_amount(amount)?,
+                utxos,
+                *fee_satoshis,
+                public_key,
+            )
+            .map(UnsignedRelease::Bitcoin)
+        }
+        (ChainType::Solana, ReleaseParams::Solana { recent_blockhash }) => {
+            if *kind != ReleaseKind::Native {
+                return Err("Solana releases can only pay out SOL".to_string());
+            }
+            build_solana_transfer(
+                to_address,
+                native_amount(amount)?,
+                recent_blockhash,
+                public_key,
+            )
+            .map(UnsignedRelease::Solana)
+        }
+        _ => Err("Release parameters do not match the destination chain".to_string()),
+    }
+}
+
+/// Rejects relayer parameters for the wrong chain or with fees above the
+/// bridge's ceilings.
+pub fn check_release_params(params: &ReleaseParams, config: &ChainConfig) -> Result<(), String> {
+    match params {
+        ReleaseParams::Evm {
+            chain_id,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            gas_limit,
+            ..
+        } => {
+            if config.evm_chain_id != Some(*chain_id) {
+                return Err("Chain id does not match the chain configuration".to_string());
+            }
+            if *gas_limit == 0 || *gas_limit > MAX_EVM_GAS_LIMIT {
+                return Err(format!("Gas limit must be 1-{}", MAX_EVM_GAS_LIMIT));
+            }
+            if *max_fee_per_gas > MAX_EVM_FEE_PER_GAS || max_priority_fee_per_gas > max_fee_per_gas
+            {
+                return Err(format!(
+                    "Fees per gas must satisfy priority fee <= max fee <= {}",
+                    MAX_EVM_FEE_PER_GAS
+                ));
+            }
+        }
+        ReleaseParams::Bitcoin {
+            utxos,
+            fee_satoshis,
+        } => {
+            if *fee_satoshis > MAX_BITCOIN_FEE_SATOSHIS {
+                return Err(format!(
+                    "Fee must not exceed {} satoshis",
+                    MAX_BITCOIN_FEE_SATOSHIS
+                ));
+            }
+            if utxos.len() > MAX_BITCOIN_UTXOS {
+                return Err(format!(
+                    "At most {} UTXOs may be supplied",
+                    MAX_BITCOIN_UTXOS
+                ));
+            }
+        }
+        ReleaseParams::Solana { .. } => {}
+    }
+    Ok(())
+}
+
+// Bitcoin and Solana amounts are 64-bit on chain.
+fn native_amount(amount: u128) -> Result<u64, String> {
+    u64::try_from(amount).map_err(|_| "Release amount exceeds chain limits".to_string())
+}
+
+impl UnsignedRelease {
+    pub fn chain_scheme(&self) -> SignatureScheme {
+        match self {
+            UnsignedRelease::Evm(_) | UnsignedRelease::Bitcoin(_) => {
+                SignatureScheme::EcdsaSecp256k1
+            }
+            UnsignedRelease::Solana(_) => SignatureScheme::SchnorrEd25519,
+        }
+    }
+
+    /// Messages to sign: 32-byte digests for ECDSA, the raw message for Ed25519.
+    pub fn signing_payloads(&self) -> Vec<Vec<u8>> {
+        match self {
+            UnsignedRelease::Evm(tx) => vec![keccak256(&tx.typed_payload(None)).to_vec()],
+            UnsignedRelease::Bitcoin(tx) => (0..tx.inputs.len())
+                .map(|i| tx.sighash(i).to_vec())
+                .collect(),
+            UnsignedRelease::Solana(tx) => vec![tx.message.clone()],
+        }
+    }
+
+    /// Chain state this payout consumes once signed with `public_key`.
+    /// Bitcoin guards only cover the inputs the transaction spends.
+    pub fn guard(&self, public_key: &[u8]) -> Result<ReleaseGuard, String> {
+        Ok(match self {
+            UnsignedRelease::Evm(tx) => ReleaseGuard::EvmNonce {
+                sender: evm_address_from_public_key(public_key)?,
+                nonce: tx.nonce,
+            },
+            UnsignedRelease::Bitcoin(tx) => ReleaseGuard::BitcoinInputs {
+                utxos: tx.inputs.clone(),
+            },
+            UnsignedRelease::Solana(tx) => ReleaseGuard::SolanaBlockhash {
+                recent_blockhash: tx.recent_blockhash.clone(),
+            },
+        })
+    }
+
+    /// Assembles the signed transaction from one signature per payload.
+    pub fn finalize(
+        &self,
+        signatures: &[Vec<u8>],
+        public_key: &[u8],
+    ) -> Result<(Vec<u8>, String), String> {
+        if signatures.len() != self.signing_payloads().len() {
+            return Err("Signature count does not match signing payloads".to_string());
+        }
+
+        match self {
+            UnsignedRelease::Evm(tx) => tx.finalize(&signatures[0], public_key),
+            UnsignedRelease::Bitcoin(tx) => tx.finalize(signatures),
+            UnsignedRelease::Solana(tx) => {
+                let signature = &signatures[0];
+                if signature.len() != 64 {
+                    return Err("Ed25519 signature must be 64 bytes".to_string());
+                }
+                let mut raw = encode_compact_u16(1);
+                raw.extend_from_slice(signature);
+                raw.extend_from_slice(&tx.message);
+                Ok((raw, bs58::encode(signature).into_string()))
+            }
+        }
+    }
+}
+
+//=============================================================================
+// EVM (EIP-1559)
+//=============================================================================
+
+impl UnsignedEvmTx {
+    fn typed_payload(&self, signature: Option<(u8, &[u8], &[u8])>) -> Vec<u8> {
+        let mut fields = vec![
+            rlp_uint(self.chain_id as u128),
+            rlp_uint(self.nonce as u128),
+            rlp_uint(self.max_priority_fee_per_gas),
+            rlp_uint(self.max_fee_per_gas),
+            rlp_uint(self.gas_limit as u128),
+            rlp_bytes(&self.to),
+            rlp_uint(self.value),
+            rlp_bytes(&self.data),
+            rlp_list(&[]), // access list
+        ];
+        if let Some((y_parity, r, s)) = signature {
+            fields.push(rlp_uint(y_parity as u128));
+            fields.push(rlp_bytes(strip_leading_zeros(r)));
+            fields.push(rlp_bytes(strip_leading_zeros(s)));
+        }
+
+        let mut payload = vec![0x02];
+        payload.extend(rlp_list(&fields));
+        payload
+    }
+
+    fn finalize(&self, signature: &[u8], public_key: &[u8]) -> Result<(Vec<u8>, String), String> {
+        let signature = normalized_signature(signature)?;
+        let digest = keccak256(&self.typed_payload(None));
+        let expected = VerifyingKey::from_sec1_bytes(public_key)
+            .map_err(|_| "Invalid ECDSA public key".to_string())?;
+
+        let y_parity = (0u8..2)
+            .find(|&id| {
+                RecoveryId::from_byte(id)
+                    .and_then(|rid| {
+                        VerifyingKey::recover_from_prehash(&digest, &signature, rid).ok()
+                    })
+                    .is_some_and(|key| key == expected)
+            })
+            .ok_or("Signature does not match the bridge key")?;
+
+        let bytes = signature.to_bytes();
+        let raw = self.typed_payload(Some((y_parity, &bytes[..32], &bytes[32..])));
+        let tx_hash = format!("0x{}", hex::encode(keccak256(&raw)));
+        Ok((raw, tx_hash))
+    }
+}
+
+pub fn parse_evm_address(address: &str) -> Result<[u8; 20], String> {
+    let digits = address
+        .strip_prefix("0x")
+        .ok_or("EVM address must start with 0x")?;
+    let bytes = hex::decode(digits).map_err(|_| "Invalid EVM address")?;
+    bytes
+        .try_into()
+        .map_err(|_| "EVM address must be 20 bytes".to_string())
+}
+
+/// EVM address controlled by an uncompressed or compressed secp256k1 key.
+pub fn evm_address_from_public_key(public_key: &[u8]) -> Result<String, String> {
+    let key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|_| "Invalid ECDSA public key".to_string())?;
+    let uncompressed = key.to_encoded_point(false);
+    let hash = keccak256(&uncompressed.as_bytes()[1..]);
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+fn rlp_uint(value: u128) -> Vec<u8> {
+    rlp_bytes(strip_leading_zeros(&value.to_be_bytes()))
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut out = rlp_length_prefix(bytes.len(), 0x80);
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let body: Vec<u8> = items.concat();
+    let mut out = rlp_length_prefix(body.len(), 0xc0);
+    out.extend(body);
+    out
+}
+
+fn rlp_length_prefix(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        vec![offset + length as u8]
+    } else {
+        let length_bytes = strip_leading_zeros(&length.to_be_bytes()).to_vec();
+        let mut out = vec![offset + 55 + length_bytes.len() as u8];
+        out.extend(length_bytes);
+        out
+    }
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[first..]
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+// Threshold ECDSA may return high-S signatures; Bitcoin and Ethereum require low-S.
+fn normalized_signature(signature: &[u8]) -> Result<Signature, String> {
+    let signature =
+        Signature::from_slice(signature).map_err(|_| "Invalid ECDSA signature".to_string())?;
+    Ok(signature.normalize_s().unwrap_or(signature))
+}
+
+//=============================================================================
+// BITCOIN (P2WPKH)
+//=============================================================================
+
+fn build_bitcoin_release(
+    to_address: &str,
+    amount: u64,
+    utxos: &[BitcoinUtxo],
+    fee_satoshis: u64,
+    public_key: &[u8],
+) -> Result<UnsignedBitcoinTx, String> {
+    if public_key.len() != 33 {
+        return Err("Bitcoin releases need a compressed public key".to_string());
+    }
+    if utxos.iter().any(|u| u.txid.len() != 32) {
+        return Err("UTXO txid must be 32 bytes".to_string());
+    }
+    if amount < BITCOIN_DUST_LIMIT {
+        return Err("Release amount is below the dust limit".to_string());
+    }
+
+    // Spend the largest outputs first until amount + fee is covered
+    let mut sorted = utxos.to_vec();
+    sorted.sort_by_key(|u| std::cmp::Reverse(u.value));
+    let needed = amount
+        .checked_add(fee_satoshis)
+        .ok_or("Release amount overflow")?;
+    let mut inputs = Vec::new();
+    let mut total: u64 = 0;
+    for utxo in sorted {
+        if total >= needed {
+            break;
+        }
+        total = total.saturating_add(utxo.value);
+        inputs.push(utxo);
+    }
+    if total < needed {
+        return Err("Insufficient bridge UTXOs for release".to_string());
+    }
+
+    let mut outputs = vec![(amount, bitcoin_script_pubkey(to_address)?)];
+    let change = total - needed;
+    if change >= BITCOIN_DUST_LIMIT {
+        outputs.push((change, p2wpkh_script(&hash160(public_key))));
+    }
+
+    Ok(UnsignedBitcoinTx {
+        inputs,
+        outputs,
+        public_key: public_key.to_vec(),
+    })
+}
+
+impl UnsignedBitcoinTx {
+    fn sighash(&self, index: usize) -> [u8; 32] {
+        let inputs: Vec<(Vec<u8>, u32)> = self
+            .inputs
+            .iter()
+            .map(|input| (outpoint(input), RBF_SEQUENCE))
+            .collect();
+        p2wpkh_sighash(
+            BITCOIN_TX_VERSION,
+            &inputs,
+            index,
+            &hash160(&self.public_key),
+            self.inputs[index].value,
+            &self.serialized_outputs(),
+            0,
+        )
+    }
+
+    fn serialized_outputs(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (value, script) in &self.outputs {
+            out.extend(value.to_le_bytes());
+            out.extend(encode_varint(script.len() as u64));
+            out.extend(script);
+        }
+        out
+    }
+
+    fn serialize(&self, witnesses: Option<&[Vec<Vec<u8>>]>) -> Vec<u8> {
+        let mut tx = Vec::new();
+        tx.extend(BITCOIN_TX_VERSION.to_le_bytes());
+        if witnesses.is_some() {
+            tx.extend([0x00, 0x01]);
+        }
+        tx.extend(encode_varint(self.inputs.len() as u64));
+        for input in &self.inputs {
+            tx.extend(outpoint(input));
+            tx.push(0x00); // empty scriptSig
+            tx.extend(RBF_SEQUENCE.to_le_bytes());
+        }
+        tx.extend(encode_varint(self.outputs.len() as u64));
+        tx.extend(self.serialized_outputs());
+        if let Some(witnesses) = witnesses {
+            for stack in witnesses {
+                tx.extend(encode_varint(stack.len() as u64));
+                for item in stack {
+                    tx.extend(encode_varint(item.len() as u64));
+                    tx.extend(item);
+                }
+            }
+        }
+        tx.extend(0u32.to_le_bytes());
+        tx
+    }
+
+    fn finalize(&self, signatures: &[Vec<u8>]) -> Result<(Vec<u8>, String), String> {
+        let witnesses = signatures
+            .iter()
+            .map(|signature| {
+                let mut der = normalized_signature(signature)?
+                    .to_der()
+                    .as_bytes()
+                    .to_vec();
+                der.push(SIGHASH_ALL);
+                Ok(vec![der, self.public_key.clone()])
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut txid = sha256d(&self.serialize(None));
+        txid.reverse();
+        Ok((self.serialize(Some(&witnesses)), hex::encode(txid)))
+    }
+}
+
+// BIP-143 SIGHASH_ALL signature hash for the P2WPKH input `index`, given
+// every input's (outpoint, sequence) and the serialized outputs.
+fn p2wpkh_sighash(
+    version: u32,
+    inputs: &[(Vec<u8>, u32)],
+    index: usize,
+    pubkey_hash: &[u8; 20],
+    value: u64,
+    serialized_outputs: &[u8],
+    lock_time: u32,
+) -> [u8; 32] {
+    let mut prevouts = Vec::new();
+    let mut sequences = Vec::new();
+    for (outpoint, sequence) in inputs {
+        prevouts.extend(outpoint);
+        sequences.extend(sequence.to_le_bytes());
+    }
+
+    let mut script_code = vec![0x19, 0x76, 0xa9, 0x14];
+    script_code.extend_from_slice(pubkey_hash);
+    script_code.extend_from_slice(&[0x88, 0xac]);
+
+    let (outpoint, sequence) = &inputs[index];
+    let mut preimage = Vec::new();
+    preimage.extend(version.to_le_bytes());
+    preimage.extend(sha256d(&prevouts));
+    preimage.extend(sha256d(&sequences));
+    preimage.extend(outpoint);
+    preimage.extend(script_code);
+    preimage.extend(value.to_le_bytes());
+    preimage.extend(sequence.to_le_bytes());
+    preimage.extend(sha256d(serialized_outputs));
+    preimage.extend(lock_time.to_le_bytes());
+    preimage.extend((SIGHASH_ALL as u32).to_le_bytes());
+    sha256d(&preimage)
+}
+
+/// scriptPubKey for a P2PKH, P2SH, P2WPKH or P2WSH address.
+pub fn bitcoin_script_pubkey(address: &str) -> Result<Vec<u8>, String> {
+    let lower = address.to_ascii_lowercase();
+    if lower.starts_with("bc1") || lower.starts_with("tb1") || lower.starts_with("bcrt1") {
+        let (version, program) = decode_segwit_address(&lower)?;
+        if version != 0 || !(program.len() == 20 || program.len() == 32) {
+            return Err("Only v0 segwit addresses are supported".to_string());
+        }
+        let mut script = vec![0x00, program.len() as u8];
+        script.extend(program);
+        return Ok(script);
+    }
+
+    let decoded = bs58::decode(address)
+        .into_vec()
+        .map_err(|_| "Invalid base58 Bitcoin address")?;
+    if decoded.len() != 25 {
+        return Err("Invalid base58 Bitcoin address length".to_string());
+    }
+    let (payload, checksum) = decoded.split_at(21);
+    if sha256d(payload)[..4] != *checksum {
+        return Err("Invalid Bitcoin address checksum".to_string());
+    }
+
+    let hash = &payload[1..];
+    match payload[0] {
+        0x00 | 0x6f => {
+            let mut script = vec![0x76, 0xa9, 0x14];
+            script.extend_from_slice(hash);
+            script.extend_from_slice(&[0x88, 0xac]);
+            Ok(script)
+        }
+        0x05 | 0xc4 => {
+            let mut script = vec![0xa9, 0x14];
+            script.extend_from_slice(hash);
+            script.push(0x87);
+            Ok(script)
+        }
+        _ => Err("Unsupported Bitcoin address version".to_string()),
+    }
+}
+
+fn p2wpkh_script(pubkey_hash: &[u8; 20]) -> Vec<u8> {
+    let mut script = vec![0x00, 0x14];
+    script.extend_from_slice(pubkey_hash);
+    script
+}
+
+// BIP-173 bech32 decoding of a version 0 segwit address.
+fn decode_segwit_address(address: &str) -> Result<(u8, Vec<u8>), String> {
+    const CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    let separator = address.rfind('1').ok_or("Invalid bech32 address")?;
+    let (hrp, data) = (&address[..separator], &address[separator + 1..]);
+    if data.len() < 7 {
+        return Err("Invalid bech32 address".to_string());
+    }
+
+    let values = data
+        .chars()
+        .map(|c| CHARSET.find(c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or("Invalid bech32 character")?;
+
+    let mut checksum_input: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    checksum_input.push(0);
+    checksum_input.extend(hrp.bytes().map(|b| b & 31));
+    checksum_input.extend(&values);
+    if bech32_polymod(&checksum_input) != 1 {
+        return Err("Invalid bech32 checksum".to_string());
+    }
+
+    let payload = &values[..values.len() - 6];
+    let version = payload[0];
+    let program = convert_bits(&payload[1..], 5, 8)?;
+    Ok((version, program))
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ (*value as u32);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32) -> Result<Vec<u8>, String> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let mut out = Vec::new();
+    let max = (1 << to) - 1;
+    for value in data {
+        acc = (acc << from) | (*value as u32);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return Err("Invalid bech32 padding".to_string());
+    }
+    Ok(out)
+}
+
+fn outpoint(utxo: &BitcoinUtxo) -> Vec<u8> {
+    let mut out = utxo.txid.clone();
+    out.extend(utxo.vout.to_le_bytes());
+    out
+}
+
+fn encode_varint(value: u64) -> Vec<u8> {
+    match value {
+        0..=0xfc => vec![value as u8],
+        0xfd..=0xffff => {
+            let mut out = vec![0xfd];
+            out.extend((value as u16).to_le_bytes());
+            out
+        }
+        0x10000..=0xffff_ffff => {
+            let mut out = vec![0xfe];
+            out.extend((value as u32).to_le_bytes());
+            out
+        }
+        _ => {
+            let mut out = vec![0xff];
+            out.extend(value.to_le_bytes());
+            out
+        }
+    }
+}
+
+fn sha256d(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+//=============================================================================
+// SOLANA (system transfer)
+//=============================================================================
+
+fn build_solana_transfer(
+    to_address: &str,
+    lamports: u64,
+    recent_blockhash: &str,
+    public_key: &[u8],
+) -> Result<UnsignedSolanaTx, String> {
+    let from: [u8; 32] = public_key
+        .try_into()
+        .map_err(|_| "Solana releases need a 32-byte Ed25519 public key".to_string())?;
+    let to = decode_base58_32(to_address, "destination address")?;
+    let blockhash = decode_base58_32(recent_blockhash, "recent blockhash")?;
+    let system_program = [0u8; 32];
+
+    let mut message = vec![
+        1, // required signatures
+        0, // read-only signed accounts
+        1, // read-only unsigned accounts (system program)
+    ];
+    message.extend(encode_compact_u16(3));
+    message.extend_from_slice(&from);
+    message.extend_from_slice(&to);
+    message.extend_from_slice(&system_program);
+    message.extend_from_slice(&blockhash);
+
+    let mut instruction_data = 2u32.to_le_bytes().to_vec(); // SystemInstruction::Transfer
+    instruction_data.extend(lamports.to_le_bytes());
+
+    message.extend(encode_compact_u16(1));
+    message.push(2); // program id index
+    message.extend(encode_compact_u16(2));
+    message.extend([0, 1]);
+    message.extend(encode_compact_u16(instruction_data.len() as u16));
+    message.extend(instruction_data);
+
+    Ok(UnsignedSolanaTx {
+        message,
+        recent_blockhash: recent_blockhash.to_string(),
+    })
+}
+
+fn decode_base58_32(value: &str, what: &str) -> Result<[u8; 32], String> {
+    bs58::decode(value)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("Invalid Solana {}", what))
+}
+
+fn encode_compact_u16(mut value: u16) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if value == 0 {
+            return out;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    fn evm_config(evm_chain_id: u64) -> ChainConfig {
+        ChainConfig {
+            chain_type: ChainType::Ethereum,
+            rpc_url: "https://rpc.example".to_string(),
+            bridge_contract: "0x3535353535353535353535353535353535353535".to_string(),
+            supported_assets: Vec::new(),
+            fee_bps: 0,
+            confirmation_blocks: 12,
+            evm_chain_id: Some(evm_chain_id),
+            enabled: true,
+        }
+    }
+
+    fn evm_params(chain_id: u64, gas_limit: u64, max_fee_per_gas: u128) -> ReleaseParams {
+        ReleaseParams::Evm {
+            chain_id,
+            nonce: 0,
+            max_priority_fee_per_gas: 1_000_000_000,
+            max_fee_per_gas,
+            gas_limit,
+        }
+    }
+
+    // Ethereum wiki RLP examples
+    #[test]
+    fn encodes_rlp_reference_examples() {
+        assert_eq!(hex::encode(rlp_bytes(b"dog")), "83646f67");
+        assert_eq!(
+            hex::encode(rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")])),
+            "c88363617483646f67"
+        );
+        assert_eq!(hex::encode(rlp_bytes(b"")), "80");
+        assert_eq!(hex::encode(rlp_list(&[])), "c0");
+        assert_eq!(hex::encode(rlp_uint(0)), "80");
+        assert_eq!(hex::encode(rlp_uint(15)), "0f");
+        assert_eq!(hex::encode(rlp_uint(1024)), "820400");
+
+        let empty = rlp_list(&[]);
+        let nested = rlp_list(std::slice::from_ref(&empty));
+        assert_eq!(
+            hex::encode(rlp_list(&[
+                empty.clone(),
+                nested.clone(),
+                rlp_list(&[empty, nested])
+            ])),
+            "c7c0c1c0c3c0c1c0"
+        );
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        assert_eq!(rlp_bytes(lorem)[..2], [0xb8, 0x38]);
+        assert_eq!(rlp_bytes(lorem)[2..], lorem[..]);
+    }
+
+    #[test]
+    fn builds_and_signs_eip1559_transfers() {
+        let params = evm_params(1, 21_000, 100_000_000_000);
+        let to = "0x3535353535353535353535353535353535353535";
+        // EIP-155 example key; its published address is 0x9d8a...5a4f
+        let key = SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let public_key = key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec();
+        assert_eq!(
+            evm_address_from_public_key(&public_key).unwrap(),
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+        );
+
+        let unsigned = build_release(
+            &ChainType::Ethereum,
+            to,
+            &ReleaseKind::Native,
+            1_000_000_000_000_000_000,
+            &params,
+            &public_key,
+        )
+        .unwrap();
+        let UnsignedRelease::Evm(tx) = &unsigned else {
+            panic!("expected an EVM release");
+        };
+        assert_eq!(
+            hex::encode(tx.typed_payload(None)),
+            "02f00180843b9aca0085174876e80082520894353535353535353535353535353535353535353588\
+             0de0b6b3a764000080c0"
+        );
+
+        let payload = &unsigned.signing_payloads()[0];
+        let (signature, _) = key.sign_prehash_recoverable(payload).unwrap();
+        let (raw, tx_hash) = unsigned
+            .finalize(&[signature.to_bytes().to_vec()], &public_key)
+            .unwrap();
+        assert_eq!(raw[0], 0x02);
+        assert_eq!(tx_hash, format!("0x{}", hex::encode(keccak256(&raw))));
+
+        let other = SigningKey::from_slice(&[0x47; 32]).unwrap();
+        let (signature, _) = other.sign_prehash_recoverable(payload).unwrap();
+        assert!(unsigned
+            .finalize(&[signature.to_bytes().to_vec()], &public_key)
+            .is_err());
+    }
+
+    // Native P2WPKH example from BIP-143
+    #[test]
+    fn computes_bip143_reference_sighash() {
+        let inputs = [
+            (
+                hex::decode(
+                    "fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000",
+                )
+                .unwrap(),
+                0xffff_ffee,
+            ),
+            (
+                hex::decode(
+                    "ef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a01000000",
+                )
+                .unwrap(),
+                0xffff_ffff,
+            ),
+        ];
+        let public_key =
+            hex::decode("025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357")
+                .unwrap();
+        let outputs = hex::decode(
+            "202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac\
+             9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac",
+        )
+        .unwrap();
+
+        let sighash = p2wpkh_sighash(
+            1,
+            &inputs,
+            1,
+            &hash160(&public_key),
+            600_000_000,
+            &outputs,
+            0x11,
+        );
+        assert_eq!(
+            hex::encode(sighash),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+    }
+
+    // BIP-173 segwit and well-known base58 addresses
+    #[test]
+    fn decodes_reference_bitcoin_addresses() {
+        assert_eq!(
+            hex::encode(
+                bitcoin_script_pubkey("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap()
+            ),
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        assert_eq!(
+            hex::encode(
+                bitcoin_script_pubkey(
+                    "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"
+                )
+                .unwrap()
+            ),
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"
+        );
+        assert_eq!(
+            bitcoin_script_pubkey("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err("Invalid bech32 checksum".to_string())
+        );
+        assert_eq!(
+            hex::encode(bitcoin_script_pubkey("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").unwrap()),
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"
+        );
+    }
+
+    // Solana short_vec examples and the system transfer message layout
+    #[test]
+    fn builds_solana_transfer_messages() {
+        for (value, encoded) in [
+            (0x0, "00"),
+            (0x7f, "7f"),
+            (0x80, "8001"),
+            (0xff, "ff01"),
+            (0x100, "8002"),
+            (0x7fff, "ffff01"),
+            (0xffff, "ffff03"),
+        ] {
+            assert_eq!(hex::encode(encode_compact_u16(value)), encoded);
+        }
+
+        let from = [1u8; 32];
+        let to = [3u8; 32];
+        let blockhash = [2u8; 32];
+        let tx = build_solana_transfer(
+            &bs58::encode(to).into_string(),
+            1_000_000,
+            &bs58::encode(blockhash).into_string(),
+            &from,
+        )
+        .unwrap();
+
+        let mut expected = vec![1, 0, 1, 3];
+        expected.extend(from);
+        expected.extend(to);
+        expected.extend([0u8; 32]);
+        expected.extend(blockhash);
+        expected.extend([1, 2, 2, 0, 1, 12, 2, 0, 0, 0]);
+        expected.extend(1_000_000u64.to_le_bytes());
+        assert_eq!(tx.message, expected);
+    }
+
+    #[test]
+    fn guards_only_the_spent_bitcoin_inputs() {
+        let utxo = |byte: u8, value: u64| BitcoinUtxo {
+            txid: vec![byte; 32],
+            vout: 0,
+            value,
+        };
+        let params = ReleaseParams::Bitcoin {
+            utxos: vec![utxo(1, 2_000), utxo(2, 500_000)],
+            fee_satoshis: 1_000,
+        };
+        let public_key = [2u8; 33];
+        let unsigned = build_release(
+            &ChainType::Bitcoin,
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            &ReleaseKind::Native,
+            100_000,
+            &params,
+            &public_key,
+        )
+        .unwrap();
+
+        match unsigned.guard(&public_key).unwrap() {
+            ReleaseGuard::BitcoinInputs { utxos } => {
+                assert_eq!(utxos.len(), 1);
+                assert_eq!(utxos[0].txid, vec![2u8; 32]);
+            }
+            other => panic!("unexpected guard {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_release_params_outside_bounds() {
+        let config = evm_config(1);
+        assert!(check_release_params(&evm_params(1, 100_000, 50_000_000_000), &config).is_ok());
+        assert_eq!(
+            check_release_params(&evm_params(137, 100_000, 50_000_000_000), &config),
+            Err("Chain id does not match the chain configuration".to_string())
+        );
+        assert!(check_release_params(&evm_params(1, 10_000_000, 50_000_000_000), &config).is_err());
+        assert!(check_release_params(&evm_params(1, 100_000, u128::MAX), &config).is_err());
+        assert!(check_release_params(&evm_params(1, 100_000, 1), &config).is_err());
+
+        let bitcoin = ReleaseParams::Bitcoin {
+            utxos: Vec::new(),
+            fee_satoshis: MAX_BITCOIN_FEE_SATOSHIS + 1,
+        };
+        assert!(check_release_params(&bitcoin, &config).is_err());
+    }
+}
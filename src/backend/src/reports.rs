@@ -0,0 +1,153 @@
+use crate::VerificationStatus;
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+const DAY_NS: u64 = 24 * 3600 * 1_000_000_000;
+
+pub const MAX_REPORT_DESCRIPTION_LENGTH: usize = 2_000;
+pub const MAX_REPORT_NOTE_LENGTH: usize = 1_000;
+pub const MAX_REPORT_EVIDENCE_FILES: usize = 10;
+
+// Hourly report limit; RateLimitConfig has no field for it, but per-principal
+// overrides still apply.
+pub const MAX_REPORTS_PER_HOUR: u32 = 3;
+
+// Reports a principal may have awaiting review at once.
+pub const MAX_OPEN_REPORTS_PER_REPORTER: usize = 5;
+
+// Time the reported owner has to appeal an upheld report.
+pub const APPEAL_WINDOW_NS: u64 = 30 * DAY_NS;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReportCategory {
+    Fraud,
+    Impersonation,
+    MisrepresentedAsset,
+    NonDelivery,
+    Other,
+}
+
+// A reviewer's decision on an open report.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReportOutcome {
+    Dismissed,
+    Upheld,    // Applies a FraudReport reputation penalty
+    Suspended, // Upheld, and the identity's verification status is suspended
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReportStatus {
+    Open,
+    Dismissed,
+    Upheld,
+    Suspended,
+    UnderAppeal,
+    Overturned, // Appeal granted; the penalty and any suspension were reversed
+}
+
+impl From<ReportOutcome> for ReportStatus {
+    fn from(outcome: ReportOutcome) -> Self {
+        match outcome {
+            ReportOutcome::Dismissed => ReportStatus::Dismissed,
+            ReportOutcome::Upheld => ReportStatus::Upheld,
+            ReportOutcome::Suspended => ReportStatus::Suspended,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReportResolution {
+    pub outcome: ReportOutcome,
+    pub note: String,
+    pub resolved_by: Principal,
+    pub resolved_at: u64,
+    pub previous_status: Option<VerificationStatus>, // Restored if a suspension is overturned
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReportAppeal {
+    pub statement: String,
+    pub filed_at: u64,
+    pub granted: Option<bool>, // None while awaiting review
+    pub note: Option<String>,
+    pub decided_by: Option<Principal>,
+    pub decided_at: Option<u64>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct IdentityReport {
+    pub id: u64,
+    pub identity_id: String,
+    pub reporter: Principal,
+    pub category: ReportCategory,
+    pub evidence_file_ids: Vec<String>, // Files uploaded by the reporter
+    pub description: String,
+    pub created_at: u64,
+    pub status: ReportStatus,
+    pub resolution: Option<ReportResolution>,
+    pub appeal: Option<ReportAppeal>, // At most one per report
+}
+
+// What the reported owner sees: the reporter and their evidence are
+// withheld.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReportNotice {
+    pub id: u64,
+    pub identity_id: String,
+    pub category: ReportCategory,
+    pub description: String,
+    pub created_at: u64,
+    pub status: ReportStatus,
+    pub resolution: Option<ReportResolution>,
+    pub appeal: Option<ReportAppeal>,
+    pub appeal_deadline: Option<u64>, // Set while an appeal may still be filed
+}
+
+impl IdentityReport {
+    // Reference of the report's reputation event, so it is applied once and
+    // can be found again if the report is overturned.
+    pub fn reputation_reference(&self) -> String {
+        format!("report:{}", self.id)
+    }
+
+    pub fn appeal_deadline(&self) -> Option<u64> {
+        match (&self.resolution, &self.appeal) {
+            (Some(resolution), None) if resolution.outcome != ReportOutcome::Dismissed => {
+                Some(resolution.resolved_at.saturating_add(APPEAL_WINDOW_NS))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn notice(&self) -> ReportNotice {
+        ReportNotice {
+            id: self.id,
+            identity_id: self.identity_id.clone(),
+            category: self.category,
+            description: self.description.clone(),
+            created_at: self.created_at,
+            status: self.status,
+            resolution: self.resolution.clone(),
+            appeal: self.appeal.clone(),
+            appeal_deadline: self.appeal_deadline(),
+        }
+    }
+}
+
+pub fn validate_report(description: &str, evidence_file_ids: &[String]) -> Result<(), String> {
+    if description.trim().is_empty() || description.len() > MAX_REPORT_DESCRIPTION_LENGTH {
+        return Err("Description must be 1-2000 characters".to_string());
+    }
+    if evidence_file_ids.len() > MAX_REPORT_EVIDENCE_FILES {
+        return Err("At most 10 evidence files may be attached".to_string());
+    }
+    Ok(())
+}
+
+// Resolution notes and appeal statements.
+pub fn validate_report_note(note: &str) -> Result<(), String> {
+    if note.trim().is_empty() || note.len() > MAX_REPORT_NOTE_LENGTH {
+        return Err("Note must be 1-1000 characters".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,308 @@
+use crate::{Identity, ReputationEvent, ReputationEventType};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+const DAY_NS: u64 = 24 * 3600 * 1_000_000_000;
+
+// Score of an identity with no counted events.
+pub const BASE_REPUTATION: f64 = 50.0;
+pub const MAX_REPUTATION: f64 = 100.0;
+
+pub const MAX_REPUTATION_REASON_LENGTH: usize = 500;
+pub const MAX_REPUTATION_REFERENCE_LENGTH: usize = 128;
+
+// What happened to earn or lose reputation. Each source has a fixed impact,
+// so submitters report events rather than choose score changes.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReputationSource {
+    AssetVerified,
+    AssetRejected,
+    CredentialIssued,
+    WalletVerified,
+    IdentityVerified,
+    MarketplaceTrade,
+    LoanRepaid,
+    LoanDefaulted,
+    FraudReport,
+}
+
+pub struct SourceProfile {
+    pub points: f64,       // Impact of one event when fresh
+    pub cap: f64,          // Bound on the source's total contribution, same sign as points
+    pub half_life_ns: u64, // Time for an event's impact to halve
+}
+
+impl ReputationSource {
+    pub const ALL: [ReputationSource; 9] = [
+        ReputationSource::AssetVerified,
+        ReputationSource::AssetRejected,
+        ReputationSource::CredentialIssued,
+        ReputationSource::WalletVerified,
+        ReputationSource::IdentityVerified,
+        ReputationSource::MarketplaceTrade,
+        ReputationSource::LoanRepaid,
+        ReputationSource::LoanDefaulted,
+        ReputationSource::FraudReport,
+    ];
+
+    pub fn event_type(&self) -> ReputationEventType {
+        match self {
+            ReputationSource::AssetVerified => ReputationEventType::AssetVerified,
+            ReputationSource::CredentialIssued => ReputationEventType::CredentialIssued,
+            ReputationSource::MarketplaceTrade | ReputationSource::LoanRepaid => {
+                ReputationEventType::SuccessfulTransaction
+            }
+            ReputationSource::LoanDefaulted => ReputationEventType::ComplianceViolation,
+            ReputationSource::AssetRejected | ReputationSource::FraudReport => {
+                ReputationEventType::FraudReported
+            }
+            ReputationSource::WalletVerified | ReputationSource::IdentityVerified => {
+                ReputationEventType::SystemAction
+            }
+        }
+    }
+
+    // Negative events fade more slowly than positive ones.
+    pub fn profile(&self) -> SourceProfile {
+        let (points, cap, half_life_days) = match self {
+            ReputationSource::AssetVerified => (3.0, 15.0, 180),
+            ReputationSource::AssetRejected => (-5.0, -25.0, 365),
+            ReputationSource::CredentialIssued => (2.0, 10.0, 365),
+            ReputationSource::WalletVerified => (2.0, 6.0, 365),
+            ReputationSource::IdentityVerified => (5.0, 5.0, 365),
+            ReputationSource::MarketplaceTrade => (1.0, 15.0, 90),
+            ReputationSource::LoanRepaid => (4.0, 20.0, 180),
+            ReputationSource::LoanDefaulted => (-15.0, -45.0, 730),
+            ReputationSource::FraudReport => (-20.0, -60.0, 730),
+        };
+        SourceProfile {
+            points,
+            cap,
+            half_life_ns: half_life_days * DAY_NS,
+        }
+    }
+}
+
+// Principals other than reviewers allowed to submit reputation events, and
+// the canisters whose transaction outcomes are accepted.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReputationConfig {
+    pub trusted_submitters: Vec<Principal>,
+    pub marketplace_canister: Option<Principal>,
+    pub lending_canister: Option<Principal>,
+}
+
+// Final state of a marketplace order or loan, reported by the canister that
+// owns it. Parties are principals; the outcome applies to every identity
+// they own.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum TransactionOutcome {
+    OrderCompleted {
+        order_id: u64,
+        buyer: Principal,
+        seller: Principal,
+    },
+    LoanRepaid {
+        loan_id: u64,
+        borrower: Principal,
+    },
+    LoanDefaulted {
+        loan_id: u64,
+        borrower: Principal,
+    },
+    LoanLiquidated {
+        loan_id: u64,
+        borrower: Principal,
+    },
+}
+
+impl TransactionOutcome {
+    // The canister allowed to report this outcome.
+    pub fn reporter(&self, config: &ReputationConfig) -> Option<Principal> {
+        match self {
+            TransactionOutcome::OrderCompleted { .. } => config.marketplace_canister,
+            _ => config.lending_canister,
+        }
+    }
+
+    // (party, source, reason, reference) for each reputation event.
+    pub fn reputation_events(&self) -> Vec<(Principal, ReputationSource, String, String)> {
+        match self {
+            TransactionOutcome::OrderCompleted {
+                order_id,
+                buyer,
+                seller,
+            } => [*buyer, *seller]
+                .into_iter()
+                .map(|party| {
+                    (
+                        party,
+                        ReputationSource::MarketplaceTrade,
+                        format!("Marketplace order {} completed", order_id),
+                        format!("order:{}", order_id),
+                    )
+                })
+                .collect(),
+            TransactionOutcome::LoanRepaid { loan_id, borrower } => vec![(
+                *borrower,
+                ReputationSource::LoanRepaid,
+                format!("Loan {} repaid", loan_id),
+                format!("loan:{}", loan_id),
+            )],
+            TransactionOutcome::LoanDefaulted { loan_id, borrower } => vec![(
+                *borrower,
+                ReputationSource::LoanDefaulted,
+                format!("Loan {} defaulted", loan_id),
+                format!("loan:{}", loan_id),
+            )],
+            // Liquidation means the borrower failed to keep the loan healthy
+            TransactionOutcome::LoanLiquidated { loan_id, borrower } => vec![(
+                *borrower,
+                ReputationSource::LoanDefaulted,
+                format!("Loan {} liquidated", loan_id),
+                format!("loan:{}", loan_id),
+            )],
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReputationSourceBreakdown {
+    pub source: ReputationSource,
+    pub event_count: u32,
+    pub decayed_total: f64, // Sum of decayed event impacts before the cap
+    pub cap: f64,
+    pub contribution: f64, // What the source adds to the score
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReputationExplanation {
+    pub identity_id: String,
+    pub score: f64,
+    pub base_score: f64,
+    pub sources: Vec<ReputationSourceBreakdown>, // Only sources with events
+    pub uncounted_events: u32,                   // Events recorded before sources were typed
+    pub computed_at: u64,
+}
+
+// Impact of `points` after `age_ns` with the given half-life.
+fn decayed(points: f64, age_ns: u64, half_life_ns: u64) -> f64 {
+    points * 0.5f64.powf(age_ns as f64 / half_life_ns as f64)
+}
+
+// Derives the score from the identity's typed events: each source's decayed
+// impacts are summed and capped, then added to the base score.
+pub fn explain_reputation(identity: &Identity, now: u64) -> ReputationExplanation {
+    let mut sources = Vec::new();
+    for source in ReputationSource::ALL {
+        let profile = source.profile();
+        let events: Vec<&ReputationEvent> = identity
+            .reputation_history
+            .iter()
+            .filter(|event| event.source == Some(source))
+            .collect();
+        if events.is_empty() {
+            continue;
+        }
+
+        let decayed_total: f64 = events
+            .iter()
+            .map(|event| {
+                decayed(
+                    profile.points,
+                    now.saturating_sub(event.timestamp),
+                    profile.half_life_ns,
+                )
+            })
+            .sum();
+        let contribution = if profile.cap >= 0.0 {
+            decayed_total.min(profile.cap)
+        } else {
+            decayed_total.max(profile.cap)
+        };
+
+        sources.push(ReputationSourceBreakdown {
+            source,
+            event_count: events.len() as u32,
+            decayed_total,
+            cap: profile.cap,
+            contribution,
+        });
+    }
+
+    let score = sources
+        .iter()
+        .fold(BASE_REPUTATION, |score, source| score + source.contribution)
+        .clamp(0.0, MAX_REPUTATION);
+
+    ReputationExplanation {
+        identity_id: identity.id.clone(),
+        score,
+        base_score: BASE_REPUTATION,
+        sources,
+        uncounted_events: identity
+            .reputation_history
+            .iter()
+            .filter(|event| event.source.is_none())
+            .count() as u32,
+        computed_at: now,
+    }
+}
+
+// Typed source of the awards the canister recorded itself before events
+// carried one. Manual adjustments have no equivalent and stay uncounted.
+fn legacy_source(event: &ReputationEvent) -> Option<ReputationSource> {
+    match event.reason.as_str() {
+        "AI verification completed successfully" => Some(ReputationSource::AssetVerified),
+        "Wallet successfully verified and linked" => Some(ReputationSource::WalletVerified),
+        reason if reason.starts_with("Asset verification completed:") => {
+            if event.score_change < 0.0 {
+                Some(ReputationSource::AssetRejected)
+            } else {
+                Some(ReputationSource::AssetVerified)
+            }
+        }
+        _ => None,
+    }
+}
+
+// Gives recognised untyped events their source so the score derivation
+// counts them. Returns whether any event changed.
+pub fn migrate_legacy_reputation(history: &mut [ReputationEvent]) -> bool {
+    let mut changed = false;
+    for event in history.iter_mut().filter(|event| event.source.is_none()) {
+        if let Some(source) = legacy_source(event) {
+            event.source = Some(source);
+            changed = true;
+        }
+    }
+    changed
+}
+
+// Drops typed events whose impact has decayed below 1% of their original
+// points, keeping the history bounded for long-lived identities. Their
+// references stop blocking resubmission at that point.
+pub fn prune_reputation_history(history: &mut Vec<ReputationEvent>, now: u64) {
+    history.retain(|event| match event.source {
+        Some(source) => {
+            let profile = source.profile();
+            let remaining = decayed(
+                1.0,
+                now.saturating_sub(event.timestamp),
+                profile.half_life_ns,
+            );
+            remaining >= 0.01
+        }
+        None => true,
+    });
+}
+
+pub fn validate_reputation_event(reason: &str, reference: Option<&str>) -> Result<(), String> {
+    if reason.trim().is_empty() || reason.len() > MAX_REPUTATION_REASON_LENGTH {
+        return Err("Reason must be 1-500 characters".to_string());
+    }
+    if reference.is_some_and(|r| r.trim().is_empty() || r.len() > MAX_REPUTATION_REFERENCE_LENGTH) {
+        return Err("Reference must be 1-128 characters".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use crate::{
+    AMLStatus, CredentialStatus, Identity, KYCLevel, RiskAssessment, RiskFactor, SanctionsStatus,
+    WalletVerificationStatus,
+};
+
+pub const RISK_MODEL_VERSION: &str = "v2.0.0";
+
+const DAY_NS: u64 = 24 * 3600 * 1_000_000_000;
+
+// Age at which an account no longer adds operational risk.
+const ESTABLISHED_ACCOUNT_NS: u64 = 90 * DAY_NS;
+
+// Recent rate limit violations at which that factor saturates.
+const MAX_COUNTED_VIOLATIONS: u32 = 5;
+
+// Share of each category in the overall score.
+const FRAUD_WEIGHT: f64 = 0.4;
+const COMPLIANCE_WEIGHT: f64 = 0.35;
+const OPERATIONAL_WEIGHT: f64 = 0.25;
+
+// Floor on the overall score for sanctioned or AML-blocked identities.
+const BLOCKED_RISK_FLOOR: f64 = 0.9;
+
+// Data held outside the identity record that feeds the assessment.
+#[derive(Clone, Debug, Default)]
+pub struct RiskInputs {
+    pub asset_fraud_scores: Vec<f64>, // Completed verifications of linked assets
+    pub unverified_assets: u32,       // Linked assets without a completed verification
+    pub recent_rate_limit_violations: u32,
+}
+
+struct Factor {
+    factor_type: &'static str,
+    weight: f64, // Within its category; each category's weights sum to 1
+    score: f64,
+    description: String,
+    mitigations: &'static [&'static str],
+}
+
+impl From<Factor> for RiskFactor {
+    fn from(factor: Factor) -> Self {
+        RiskFactor {
+            factor_type: factor.factor_type.to_string(),
+            weight: factor.weight,
+            score: factor.score,
+            description: factor.description,
+            mitigation_suggestions: if factor.score > 0.0 {
+                factor.mitigations.iter().map(|m| m.to_string()).collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+fn weighted(factors: &[Factor]) -> f64 {
+    factors.iter().map(|f| f.weight * f.score).sum()
+}
+
+// Scores in [0, 1], higher meaning riskier. The same identity, inputs and
+// time always produce the same assessment.
+pub fn assess_risk(identity: &Identity, inputs: &RiskInputs, now: u64) -> RiskAssessment {
+    let fraud = fraud_factors(identity, inputs);
+    let compliance = compliance_factors(identity, now);
+    let operational = operational_factors(identity, now);
+
+    let fraud_risk = weighted(&fraud);
+    let compliance_risk = weighted(&compliance);
+    let operational_risk = weighted(&operational);
+
+    let mut overall_risk_score = FRAUD_WEIGHT * fraud_risk
+        + COMPLIANCE_WEIGHT * compliance_risk
+        + OPERATIONAL_WEIGHT * operational_risk;
+    let status = identity.compliance_status.effective(now);
+    if matches!(status.sanctions_check, SanctionsStatus::Sanctioned)
+        || matches!(status.aml_status, AMLStatus::Blocked)
+    {
+        overall_risk_score = overall_risk_score.max(BLOCKED_RISK_FLOOR);
+    }
+
+    RiskAssessment {
+        overall_risk_score,
+        fraud_risk,
+        compliance_risk,
+        operational_risk,
+        risk_factors: fraud
+            .into_iter()
+            .chain(compliance)
+            .chain(operational)
+            .map(RiskFactor::from)
+            .collect(),
+        last_assessment: now,
+        assessment_model_version: RISK_MODEL_VERSION.to_string(),
+    }
+}
+
+fn fraud_factors(identity: &Identity, inputs: &RiskInputs) -> Vec<Factor> {
+    let worst_fraud_score = inputs
+        .asset_fraud_scores
+        .iter()
+        .copied()
+        .fold(0.0, f64::max)
+        .clamp(0.0, 1.0);
+    let asset_score = if inputs.unverified_assets > 0 {
+        worst_fraud_score.max(0.5)
+    } else {
+        worst_fraud_score
+    };
+
+    let wallets = identity.linked_wallets.len();
+    let unverified_wallets = identity
+        .linked_wallets
+        .iter()
+        .filter(|wallet| {
+            !matches!(
+                wallet.verification_status,
+                WalletVerificationStatus::Verified
+            )
+        })
+        .count();
+    let wallet_score = match wallets {
+        0 => 0.0,
+        _ => unverified_wallets as f64 / wallets as f64,
+    };
+
+    let violations = inputs
+        .recent_rate_limit_violations
+        .min(MAX_COUNTED_VIOLATIONS);
+
+    vec![
+        Factor {
+            factor_type: "fraud.asset_verification",
+            weight: 0.5,
+            score: asset_score,
+            description: format!(
+                "Highest asset fraud score {:.2} across {} verified assets; {} awaiting verification",
+                worst_fraud_score,
+                inputs.asset_fraud_scores.len(),
+                inputs.unverified_assets
+            ),
+            mitigations: &[
+                "Complete AI verification for every linked asset",
+                "Unlink or provide documentation for assets with high fraud scores",
+            ],
+        },
+        Factor {
+            factor_type: "fraud.wallet_verification",
+            weight: 0.25,
+            score: wallet_score,
+            description: format!(
+                "{} of {} linked wallets are not signature-verified",
+                unverified_wallets, wallets
+            ),
+            mitigations: &["Re-link wallets with link_wallet_verified to prove ownership"],
+        },
+        Factor {
+            factor_type: "fraud.rate_limit_violations",
+            weight: 0.25,
+            score: violations as f64 / MAX_COUNTED_VIOLATIONS as f64,
+            description: format!(
+                "{} rate limit violations by the owner in the last 24 hours",
+                inputs.recent_rate_limit_violations
+            ),
+            mitigations: &["Reduce request frequency to stay within rate limits"],
+        },
+    ]
+}
+
+fn compliance_factors(identity: &Identity, now: u64) -> Vec<Factor> {
+    let status = identity.compliance_status.effective(now);
+
+    let kyc_score = match status.kyc_level {
+        KYCLevel::None => 1.0,
+        KYCLevel::Basic => 0.5,
+        KYCLevel::Enhanced => 0.2,
+        KYCLevel::Premium => 0.0,
+    };
+    let aml_score = match status.aml_status {
+        AMLStatus::Cleared => 0.0,
+        AMLStatus::NotScreened => 0.6,
+        AMLStatus::PendingReview => 0.5,
+        AMLStatus::Flagged => 0.8,
+        AMLStatus::Blocked => 1.0,
+    };
+    let sanctions_score = match status.sanctions_check {
+        SanctionsStatus::Cleared => 0.0,
+        SanctionsStatus::NotChecked => 0.5,
+        SanctionsStatus::UnderReview => 0.7,
+        SanctionsStatus::Sanctioned => 1.0,
+    };
+
+    vec![
+        Factor {
+            factor_type: "compliance.kyc_level",
+            weight: 0.4,
+            score: kyc_score,
+            description: format!("Effective KYC level is {:?}", status.kyc_level),
+            mitigations: &["Submit KYC evidence to a registered provider to raise the level"],
+        },
+        Factor {
+            factor_type: "compliance.aml_status",
+            weight: 0.3,
+            score: aml_score,
+            description: format!("Effective AML status is {:?}", status.aml_status),
+            mitigations: &["Request AML screening from a compliance officer"],
+        },
+        Factor {
+            factor_type: "compliance.sanctions_status",
+            weight: 0.3,
+            score: sanctions_score,
+            description: format!("Effective sanctions status is {:?}", status.sanctions_check),
+            mitigations: &["Request a sanctions check from a compliance officer"],
+        },
+    ]
+}
+
+fn operational_factors(identity: &Identity, now: u64) -> Vec<Factor> {
+    let mut active = 0;
+    let mut revoked_or_suspended = 0;
+    for credential in &identity.credentials {
+        match credential.status {
+            CredentialStatus::Active if credential.expiration_date.is_none_or(|at| at > now) => {
+                active += 1
+            }
+            CredentialStatus::Revoked | CredentialStatus::Suspended => revoked_or_suspended += 1,
+            _ => {}
+        }
+    }
+    let credential_score = match active {
+        0 => 0.8,
+        1 => 0.4,
+        2 => 0.2,
+        _ => 0.0,
+    };
+    let credential_score = (credential_score + 0.2 * revoked_or_suspended as f64).min(1.0);
+
+    let age = now.saturating_sub(identity.created_at);
+    let age_score = 1.0 - age.min(ESTABLISHED_ACCOUNT_NS) as f64 / ESTABLISHED_ACCOUNT_NS as f64;
+
+    vec![
+        Factor {
+            factor_type: "operational.credentials",
+            weight: 0.5,
+            score: credential_score,
+            description: format!(
+                "{} active credentials; {} revoked or suspended",
+                active, revoked_or_suspended
+            ),
+            mitigations: &[
+                "Add credentials from recognised issuers",
+                "Replace revoked or suspended credentials",
+            ],
+        },
+        Factor {
+            factor_type: "operational.account_age",
+            weight: 0.5,
+            score: age_score,
+            description: format!("Identity created {} days ago", age / DAY_NS),
+            mitigations: &["Risk from account age decreases over the first 90 days"],
+        },
+    ]
+}
@@ -0,0 +1,122 @@
+use crate::ChainType;
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SANCTIONS_BATCH: usize = 1_000;
+pub const MAX_SANCTIONED_NAME_LENGTH: usize = 200;
+
+// Where a sanctioned address or name came from, e.g. "OFAC SDN".
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SanctionsListing {
+    pub list: String,
+    pub added_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SanctionedAddress {
+    pub chain_type: ChainType,
+    pub address: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SanctionsConfig {
+    pub name_match_threshold: u8, // Minimum similarity (0-100) that counts as a hit
+}
+
+impl Default for SanctionsConfig {
+    fn default() -> Self {
+        Self {
+            name_match_threshold: 85,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct NameMatch {
+    pub listed_name: String, // Normalised form as stored
+    pub list: String,
+    pub score: u8,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SanctionsListStats {
+    pub addresses: u64,
+    pub names: u64,
+    pub name_match_threshold: u8,
+}
+
+// Key under which an address is listed. EVM addresses are case-insensitive;
+// other chains' encodings are case-sensitive.
+pub fn sanctions_address_key(chain: &ChainType, address: &str) -> String {
+    let address = address.trim();
+    match chain {
+        ChainType::Ethereum | ChainType::Polygon | ChainType::Avalanche => {
+            format!("{:?}:{}", chain, address.to_ascii_lowercase())
+        }
+        _ => format!("{:?}:{}", chain, address),
+    }
+}
+
+// Lowercases, folds common Latin diacritics, drops punctuation and sorts the
+// words, so "MÜLLER, Hans-Peter" and "hans peter muller" normalise alike.
+pub fn normalize_name(name: &str) -> String {
+    let folded: String = name
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(fold_diacritic)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+
+    let mut words: Vec<&str> = folded.split_whitespace().collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+fn fold_diacritic(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' => 'e',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' => 'i',
+        'ñ' | 'ń' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ś' | 'š' => 's',
+        'ź' | 'ż' | 'ž' => 'z',
+        'ł' => 'l',
+        _ => c,
+    }
+}
+
+// Similarity of two normalised names as a percentage, from the Levenshtein
+// distance relative to the longer name.
+pub fn name_similarity(a: &str, b: &str) -> u8 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 100;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let distance = previous[b.len()];
+    (100 * (longest - distance) / longest) as u8
+}
+
+pub fn validate_sanctions_list_name(list: &str) -> Result<(), String> {
+    if list.trim().is_empty() || list.len() > 64 {
+        return Err("List name must be 1-64 characters".to_string());
+    }
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::content::{validate_content, DetectedFileType};
 use candid::{CandidType, Principal};
 use ic_cdk::api::time;
 use serde::{Deserialize, Serialize};
@@ -16,6 +17,29 @@ pub struct FileMetadata {
     pub file_hash: String,           // SHA-256 hash for integrity
     pub is_public: bool,
     pub tags: Vec<String>,
+    pub encryption: Option<EncryptionMetadata>, // Set for vault (client-side encrypted) files
+    pub detected_type: Option<DetectedFileType>, // From content sniffing; None for ciphertext
+    pub metadata_stripped: bool,                // EXIF/GPS removed before storage
+}
+
+/// Which vetKD derivation input protects an encrypted document.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum KeyScope {
+    /// Key unique to one document, bound to the uploader and a client-chosen nonce.
+    File { key_nonce: Vec<u8> },
+    /// Key shared by every document of one identity; only its owner may derive it.
+    Identity { identity_id: String },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct EncryptionMetadata {
+    pub algorithm: String, // Client-side cipher, e.g. "AES-256-GCM"
+    pub key_scope: KeyScope,
+    pub key_name: String, // vetKD master key the document key was derived from
+    pub iv: Vec<u8>,
+    pub plaintext_size: u64,
+    pub authorized_principals: Vec<Principal>, // Besides the owner
+    pub encrypted_at: u64,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -49,6 +73,20 @@ pub struct FileUploadRequest {
     pub tags: Vec<String>,
 }
 
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct EncryptedFileUploadRequest {
+    pub original_name: String,
+    pub mime_type: String, // Type of the plaintext document
+    pub ciphertext: Vec<u8>,
+    pub plaintext_size: u64,
+    pub algorithm: String,
+    pub iv: Vec<u8>,
+    pub key_scope: KeyScope,
+    pub asset_id: Option<String>,
+    pub identity_id: Option<String>,
+    pub tags: Vec<String>,
+}
+
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct FileUploadResponse {
     pub file_id: String,
@@ -80,87 +118,166 @@ impl FileStorageService {
 
     pub fn upload_file(
         &mut self,
+        file_id: String,
         request: FileUploadRequest,
         uploader: Principal,
     ) -> Result<FileUploadResponse, String> {
-        // Validate file size (max 10MB for now)
+        self.ensure_unique_file_id(&file_id)?;
+
+        // Validate content against the declared type instead of trusting it
+        let content = validate_content(&request.mime_type, request.data)?;
+
+        // Create file metadata
+        let metadata = FileMetadata {
+            file_id: file_id.clone(),
+            original_name: request.original_name,
+            mime_type: content.detected_type.mime_type().to_string(),
+            size: content.data.len() as u64,
+            uploaded_by: uploader,
+            uploaded_at: time(),
+            asset_id: request.asset_id,
+            identity_id: request.identity_id,
+            file_hash: self.calculate_file_hash(&content.data),
+            is_public: false, // Default to private
+            tags: request.tags,
+            encryption: None,
+            detected_type: Some(content.detected_type),
+            metadata_stripped: content.metadata_stripped,
+        };
+
+        self.store_file(metadata, &content.data);
+
+        Ok(FileUploadResponse {
+            file_id,
+            url: None, // We can add URL generation later
+        })
+    }
+
+    /// Stores a document that was encrypted client-side with a vetKD-derived
+    /// key. Only the ciphertext and its `EncryptionMetadata` are kept.
+    pub fn upload_encrypted_file(
+        &mut self,
+        file_id: String,
+        request: EncryptedFileUploadRequest,
+        uploader: Principal,
+        key_name: String,
+    ) -> Result<FileUploadResponse, String> {
         const MAX_FILE_SIZE: usize = 10 * 1024 * 1024; // 10MB
-        if request.data.len() > MAX_FILE_SIZE {
+        if request.ciphertext.is_empty() {
+            return Err("Ciphertext is empty".to_string());
+        }
+        if request.ciphertext.len() > MAX_FILE_SIZE {
             return Err("File size exceeds maximum limit (10MB)".to_string());
         }
 
-        // Validate file type
         if !self.is_supported_file_type(&request.mime_type) {
             return Err("Unsupported file type".to_string());
         }
 
-        // Generate unique file ID
-        let file_id = format!(
-            "file_{}_{}_{}",
-            time(),
-            &uploader.to_string()[..8],
-            request.original_name.len()
-        );
+        if request.algorithm.is_empty() || request.iv.is_empty() {
+            return Err("Encryption algorithm and IV are required".to_string());
+        }
 
-        // Calculate file hash
-        let file_hash = self.calculate_file_hash(&request.data);
+        match &request.key_scope {
+            KeyScope::File { key_nonce } => {
+                if key_nonce.len() < 16 {
+                    return Err("Key nonce must be at least 16 bytes".to_string());
+                }
+            }
+            KeyScope::Identity { identity_id } => {
+                if request.identity_id.as_ref() != Some(identity_id) {
+                    return Err("Identity-scoped key must match the file's identity".to_string());
+                }
+            }
+        }
 
-        // Split file into chunks for storage efficiency
-        const CHUNK_SIZE: usize = 64 * 1024; // 64KB chunks
-        let chunks: Vec<Vec<u8>> = request
-            .data
-            .chunks(CHUNK_SIZE)
-            .map(|chunk| chunk.to_vec())
-            .collect();
+        self.ensure_unique_file_id(&file_id)?;
 
-        // Create file metadata
         let metadata = FileMetadata {
             file_id: file_id.clone(),
             original_name: request.original_name,
             mime_type: request.mime_type,
-            size: request.data.len() as u64,
+            size: request.ciphertext.len() as u64,
             uploaded_by: uploader,
             uploaded_at: time(),
-            asset_id: request.asset_id.clone(),
-            identity_id: request.identity_id.clone(),
-            file_hash,
-            is_public: false, // Default to private
+            asset_id: request.asset_id,
+            identity_id: request.identity_id,
+            file_hash: self.calculate_file_hash(&request.ciphertext),
+            is_public: false,
             tags: request.tags,
+            encryption: Some(EncryptionMetadata {
+                algorithm: request.algorithm,
+                key_scope: request.key_scope,
+                key_name,
+                iv: request.iv,
+                plaintext_size: request.plaintext_size,
+                authorized_principals: Vec::new(),
+                encrypted_at: time(),
+            }),
+            detected_type: None,
+            metadata_stripped: false,
         };
 
-        // Create stored file
-        let stored_file = StoredFile {
-            metadata: metadata.clone(),
-            chunks,
-        };
+        self.store_file(metadata, &request.ciphertext);
 
-        // Store the file
-        self.files.insert(file_id.clone(), stored_file);
+        Ok(FileUploadResponse { file_id, url: None })
+    }
 
-        // Update indices
-        self.file_index
-            .entry(uploader)
-            .or_default()
-            .push(file_id.clone());
+    /// Returns the vetKD derivation input of an encrypted file and the master
+    /// key it was encrypted under, if `requester` is allowed to obtain its key.
+    pub fn key_derivation_input(
+        &self,
+        file_id: &str,
+        requester: Principal,
+    ) -> Result<(Vec<u8>, String), String> {
+        let stored_file = self.files.get(file_id).ok_or("File not found")?;
+        let metadata = &stored_file.metadata;
+        let encryption = metadata
+            .encryption
+            .as_ref()
+            .ok_or("File is not encrypted")?;
+
+        if metadata.uploaded_by != requester
+            && !encryption.authorized_principals.contains(&requester)
+        {
+            return Err("Access denied".to_string());
+        }
 
-        if let Some(asset_id) = &request.asset_id {
-            self.asset_files
-                .entry(asset_id.clone())
-                .or_default()
-                .push(file_id.clone());
+        Ok((
+            vault_derivation_input(metadata.uploaded_by, &encryption.key_scope),
+            encryption.key_name.clone(),
+        ))
+    }
+
+    pub fn grant_key_access(
+        &mut self,
+        file_id: &str,
+        owner: Principal,
+        grantee: Principal,
+    ) -> Result<(), String> {
+        let encryption = self.owned_encryption_mut(file_id, owner)?;
+
+        if matches!(encryption.key_scope, KeyScope::Identity { .. }) {
+            return Err(
+                "Identity-scoped documents cannot be shared; use a per-file key".to_string(),
+            );
         }
 
-        if let Some(identity_id) = &request.identity_id {
-            self.identity_files
-                .entry(identity_id.clone())
-                .or_default()
-                .push(file_id.clone());
+        if !encryption.authorized_principals.contains(&grantee) {
+            encryption.authorized_principals.push(grantee);
         }
+        Ok(())
+    }
 
-        Ok(FileUploadResponse {
-            file_id,
-            url: None, // We can add URL generation later
-        })
+    pub fn revoke_key_access(
+        &mut self,
+        file_id: &str,
+        owner: Principal,
+        grantee: Principal,
+    ) -> Result<(), String> {
+        let encryption = self.owned_encryption_mut(file_id, owner)?;
+        encryption.authorized_principals.retain(|p| *p != grantee);
+        Ok(())
     }
 
     pub fn get_file(&self, file_id: &str, requester: Principal) -> Result<Vec<u8>, String> {
@@ -280,6 +397,13 @@ impl FileStorageService {
             return true;
         }
 
+        // Principals the owner shared an encrypted document's key with
+        if let Some(encryption) = &metadata.encryption {
+            if encryption.authorized_principals.contains(&requester) {
+                return true;
+            }
+        }
+
         // TODO: Add more sophisticated access control (e.g., sharing permissions)
         false
     }
@@ -300,6 +424,66 @@ impl FileStorageService {
         SUPPORTED_TYPES.contains(&mime_type)
     }
 
+    fn ensure_unique_file_id(&self, file_id: &str) -> Result<(), String> {
+        if self.files.contains_key(file_id) {
+            return Err("File ID already exists".to_string());
+        }
+        Ok(())
+    }
+
+    fn store_file(&mut self, metadata: FileMetadata, data: &[u8]) {
+        // Split file into chunks for storage efficiency
+        const CHUNK_SIZE: usize = 64 * 1024; // 64KB chunks
+        let chunks: Vec<Vec<u8>> = data
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+
+        let file_id = metadata.file_id.clone();
+        let uploader = metadata.uploaded_by;
+        let asset_id = metadata.asset_id.clone();
+        let identity_id = metadata.identity_id.clone();
+
+        self.files
+            .insert(file_id.clone(), StoredFile { metadata, chunks });
+
+        // Update indices
+        self.file_index
+            .entry(uploader)
+            .or_default()
+            .push(file_id.clone());
+
+        if let Some(asset_id) = asset_id {
+            self.asset_files
+                .entry(asset_id)
+                .or_default()
+                .push(file_id.clone());
+        }
+
+        if let Some(identity_id) = identity_id {
+            self.identity_files
+                .entry(identity_id)
+                .or_default()
+                .push(file_id);
+        }
+    }
+
+    fn owned_encryption_mut(
+        &mut self,
+        file_id: &str,
+        owner: Principal,
+    ) -> Result<&mut EncryptionMetadata, String> {
+        let stored_file = self.files.get_mut(file_id).ok_or("File not found")?;
+        if stored_file.metadata.uploaded_by != owner {
+            return Err("Only file owner can manage key access".to_string());
+        }
+        stored_file
+            .metadata
+            .encryption
+            .as_mut()
+            .ok_or_else(|| "File is not encrypted".to_string())
+    }
+
     fn calculate_file_hash(&self, data: &[u8]) -> String {
         use sha2::{Digest, Sha256};
         let mut hasher = Sha256::new();
@@ -307,3 +491,22 @@ impl FileStorageService {
         format!("{:x}", hasher.finalize())
     }
 }
+
+/// Builds the vetKD input for a document key. Per-file inputs are bound to
+/// the uploader so that nobody else can derive them before a grant.
+pub fn vault_derivation_input(owner: Principal, scope: &KeyScope) -> Vec<u8> {
+    let mut input = Vec::new();
+    match scope {
+        KeyScope::File { key_nonce } => {
+            input.extend_from_slice(b"file:");
+            input.push(owner.as_slice().len() as u8);
+            input.extend_from_slice(owner.as_slice());
+            input.extend_from_slice(key_nonce);
+        }
+        KeyScope::Identity { identity_id } => {
+            input.extend_from_slice(b"identity:");
+            input.extend_from_slice(identity_id.as_bytes());
+        }
+    }
+    input
+}
@@ -0,0 +1,164 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separator for every key derived by the document vault.
+pub const VAULT_KEY_CONTEXT: &[u8] = b"globaltrust_document_vault_v1";
+
+/// Cycles attached to `vetkd_derive_key`; sized for the production `key_1`.
+const VETKD_DERIVE_KEY_CYCLES: u128 = 26_153_846_153;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum VetKdCurve {
+    #[serde(rename = "bls12_381_g2")]
+    Bls12_381G2,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct VetKdKeyId {
+    pub curve: VetKdCurve,
+    pub name: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct VetKdPublicKeyArgs {
+    canister_id: Option<Principal>,
+    context: Vec<u8>,
+    key_id: VetKdKeyId,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct VetKdPublicKeyResult {
+    public_key: Vec<u8>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct VetKdDeriveKeyArgs {
+    input: Vec<u8>,
+    context: Vec<u8>,
+    transport_public_key: Vec<u8>,
+    key_id: VetKdKeyId,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct VetKdDeriveKeyResult {
+    encrypted_key: Vec<u8>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum VetKdMode {
+    /// Call the management canister's vetKD API.
+    Management,
+    /// Deterministic local stand-in for tests and local replicas without vetKD.
+    /// Keys produced in this mode offer no confidentiality.
+    Mock,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct VetKdConfig {
+    pub key_name: String,
+    pub mode: VetKdMode,
+}
+
+impl Default for VetKdConfig {
+    fn default() -> Self {
+        Self {
+            key_name: "dfx_test_key".to_string(),
+            mode: VetKdMode::Management,
+        }
+    }
+}
+
+impl VetKdConfig {
+    fn key_id(&self) -> VetKdKeyId {
+        VetKdKeyId {
+            curve: VetKdCurve::Bls12_381G2,
+            name: self.key_name.clone(),
+        }
+    }
+}
+
+/// Fetches the vault's derived public key, which clients use to verify
+/// derived keys and to encrypt documents before upload.
+pub async fn vault_public_key(config: VetKdConfig) -> Result<Vec<u8>, String> {
+    match config.mode {
+        VetKdMode::Mock => Ok(mock_digest(&[
+            b"public_key",
+            config.key_name.as_bytes(),
+            VAULT_KEY_CONTEXT,
+        ])),
+        VetKdMode::Management => {
+            let args = VetKdPublicKeyArgs {
+                canister_id: None,
+                context: VAULT_KEY_CONTEXT.to_vec(),
+                key_id: config.key_id(),
+            };
+
+            let result: Result<(VetKdPublicKeyResult,), _> = ic_cdk::call(
+                Principal::management_canister(),
+                "vetkd_public_key",
+                (args,),
+            )
+            .await;
+
+            match result {
+                Ok((response,)) => Ok(response.public_key),
+                Err((code, msg)) => Err(format!("vetkd_public_key failed: {:?} - {}", code, msg)),
+            }
+        }
+    }
+}
+
+/// Derives the vault key for `input` and returns it encrypted under the
+/// caller-supplied transport public key. The canister never sees the
+/// plaintext key.
+pub async fn derive_encrypted_key(
+    config: VetKdConfig,
+    input: Vec<u8>,
+    transport_public_key: Vec<u8>,
+) -> Result<Vec<u8>, String> {
+    if transport_public_key.is_empty() {
+        return Err("Transport public key is required".to_string());
+    }
+
+    match config.mode {
+        VetKdMode::Mock => Ok(mock_digest(&[
+            b"derived_key",
+            config.key_name.as_bytes(),
+            VAULT_KEY_CONTEXT,
+            &input,
+            &transport_public_key,
+        ])),
+        VetKdMode::Management => {
+            let args = VetKdDeriveKeyArgs {
+                input,
+                context: VAULT_KEY_CONTEXT.to_vec(),
+                transport_public_key,
+                key_id: config.key_id(),
+            };
+
+            let result: Result<(VetKdDeriveKeyResult,), _> =
+                ic_cdk::api::call::call_with_payment128(
+                    Principal::management_canister(),
+                    "vetkd_derive_key",
+                    (args,),
+                    VETKD_DERIVE_KEY_CYCLES,
+                )
+                .await;
+
+            match result {
+                Ok((response,)) => Ok(response.encrypted_key),
+                Err((code, msg)) => Err(format!("vetkd_derive_key failed: {:?} - {}", code, msg)),
+            }
+        }
+    }
+}
+
+fn mock_digest(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
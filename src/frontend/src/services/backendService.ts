@@ -89,25 +89,6 @@ export const backendService = {
     }
   },
 
-  /**
-   * Updates reputation score for an identity
-   */
-  async updateReputation(
-    identity_id: string,
-    score_change: number,
-    reason: string,
-  ): Promise<void> {
-    const result = await backend.update_reputation(
-      identity_id,
-      score_change,
-      reason,
-    );
-
-    if ("Err" in result) {
-      throw new Error(result.Err);
-    }
-  },
-
   /**
    * Gets statistics about the identity system
    */
@@ -146,8 +127,12 @@ export const backendService = {
   /**
    * Links an asset to an identity
    */
-  async linkAsset(identity_id: string, asset_id: string): Promise<void> {
-    const result = await backend.link_asset(identity_id, asset_id);
+  async linkAsset(
+    identity_id: string,
+    asset_id: string,
+    asset_type: string,
+  ): Promise<void> {
+    const result = await backend.link_asset(identity_id, asset_id, asset_type);
 
     if ("Err" in result) {
       throw new Error(result.Err);
@@ -1,9 +1,12 @@
 import { backend } from "../../../declarations/backend";
 import type {
+  AssetConfig,
+  BridgeHistoryFilter,
   BridgeRequest,
   BridgeStatus,
   ChainConfig,
   BridgeFee,
+  BridgeQuote,
   ChainType,
 } from "../../../declarations/backend/backend.did";
 
@@ -21,16 +24,29 @@ export interface CrossChainTransfer {
   transactionHashes: string[];
 }
 
+export interface BridgeHistoryPage {
+  transfers: CrossChainTransfer[];
+  nextCursor?: string;
+}
+
+export interface SupportedAsset {
+  symbol: string;
+  decimals: number;
+  minAmount: bigint;
+  maxAmount: bigint;
+  fixedFee: bigint;
+}
+
 export interface SupportedChain {
   type: string;
   name: string;
   rpcUrl: string;
   bridgeContract: string;
   supportedAssets: string[];
-  minAmount: bigint;
-  maxAmount: bigint;
+  assets: SupportedAsset[];
   feePercentage: number;
   confirmationBlocks: number;
+  enabled: boolean;
 }
 
 export interface BridgeFeeEstimate {
@@ -77,6 +93,9 @@ function bridgeStatusToString(status: BridgeStatus): string {
   if ("Completed" in status) return "Completed";
   if ("Failed" in status) return `Failed: ${status.Failed.reason}`;
   if ("Cancelled" in status) return "Cancelled";
+  if ("RefundPending" in status)
+    return `Refund Pending: ${status.RefundPending.reason}`;
+  if ("Refunded" in status) return "Refunded";
   return "Unknown";
 }
 
@@ -101,46 +120,80 @@ function toCrossChainTransfer(
   };
 }
 
+function toSupportedAsset(asset: AssetConfig): SupportedAsset {
+  return {
+    symbol: asset.symbol,
+    decimals: asset.decimals,
+    minAmount: asset.min_amount,
+    maxAmount: asset.max_amount,
+    fixedFee: asset.fixed_fee,
+  };
+}
+
 function toSupportedChain(chainConfig: ChainConfig): SupportedChain {
   return {
     type: chainTypeToString(chainConfig.chain_type),
     name: chainTypeToString(chainConfig.chain_type),
     rpcUrl: chainConfig.rpc_url,
     bridgeContract: chainConfig.bridge_contract,
-    supportedAssets: chainConfig.supported_assets,
-    minAmount: chainConfig.min_amount,
-    maxAmount: chainConfig.max_amount,
-    feePercentage: chainConfig.fee_percentage,
+    supportedAssets: chainConfig.supported_assets.map((asset) => asset.symbol),
+    assets: chainConfig.supported_assets.map(toSupportedAsset),
+    feePercentage: chainConfig.fee_bps / 100, // Basis points to percent
     confirmationBlocks: chainConfig.confirmation_blocks,
+    enabled: chainConfig.enabled,
   };
 }
 
 function toBridgeFeeEstimate(bridgeFee: BridgeFee): BridgeFeeEstimate {
   return {
     amount: bridgeFee.amount,
-    percentage: bridgeFee.percentage,
+    percentage: bridgeFee.fee_bps / 100, // Basis points to percent
     fixedFee: bridgeFee.fixed_fee,
   };
 }
 
 export const bridgeService = {
   /**
-   * Initiate a cross-chain bridge transfer
+   * Get a signed quote for a transfer; it must be used before it expires
    */
-  async initiateBridge(
+  async quoteBridge(
     fromChain: string,
     toChain: string,
     assetType: string,
     amount: bigint,
-    fromAddress: string,
-    toAddress: string,
-  ): Promise<string> {
+  ): Promise<BridgeQuote> {
     try {
-      const result = await backend.initiate_cross_chain_bridge(
+      const result = await backend.quote_bridge(
         stringToChainType(fromChain),
         stringToChainType(toChain),
         assetType,
         amount,
+      );
+
+      if ("Err" in result) {
+        throw new Error(result.Err);
+      }
+
+      return result.Ok;
+    } catch (error) {
+      console.error("Bridge quote failed:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Initiate a cross-chain bridge transfer on the terms of a quote
+   */
+  async initiateBridge(
+    identityId: string,
+    quote: BridgeQuote,
+    fromAddress: string,
+    toAddress: string,
+  ): Promise<string> {
+    try {
+      const result = await backend.initiate_cross_chain_bridge(
+        identityId,
+        quote,
         fromAddress,
         toAddress,
       );
@@ -175,12 +228,35 @@ export const bridgeService = {
   },
 
   /**
-   * Get user's bridge transaction history
+   * Get a page of the user's bridge transaction history, newest first.
+   * Pass the previous page's `nextCursor` to continue.
    */
-  async getUserBridgeHistory(): Promise<CrossChainTransfer[]> {
+  async getUserBridgeHistory(
+    cursor?: string,
+    limit?: number,
+  ): Promise<BridgeHistoryPage> {
+    const filter: BridgeHistoryFilter = {
+      status: [],
+      from_chain: [],
+      to_chain: [],
+      asset_type: [],
+      created_after: [],
+      created_before: [],
+      user_principal: [],
+    };
     try {
-      const requests = await backend.get_user_bridge_history();
-      return requests.map(toCrossChainTransfer);
+      const result = await backend.get_user_bridge_history(
+        filter,
+        cursor ? [cursor] : [],
+        limit ? [limit] : [],
+      );
+      if ("Err" in result) {
+        throw new Error(result.Err);
+      }
+      return {
+        transfers: result.Ok.requests.map(toCrossChainTransfer),
+        nextCursor: result.Ok.next_cursor[0],
+      };
     } catch (error) {
       console.error("Failed to get bridge history:", error);
       throw error;
@@ -192,14 +268,21 @@ export const bridgeService = {
    */
   async calculateBridgeFee(
     fromChain: string,
+    assetType: string,
     amount: bigint,
   ): Promise<BridgeFeeEstimate> {
     try {
-      const fee = await backend.calculate_bridge_fee(
+      const result = await backend.calculate_bridge_fee(
         stringToChainType(fromChain),
+        assetType,
         amount,
       );
-      return toBridgeFeeEstimate(fee);
+
+      if ("Err" in result) {
+        throw new Error(result.Err);
+      }
+
+      return toBridgeFeeEstimate(result.Ok);
     } catch (error) {
       console.error("Failed to calculate bridge fee:", error);
       throw error;
@@ -219,11 +302,41 @@ export const bridgeService = {
     }
   },
 
+  /**
+   * Decimals of `asset` on `chain`, or undefined if the chain does not list it
+   */
+  getAssetDecimals(
+    chains: SupportedChain[],
+    chain: string,
+    asset: string,
+  ): number | undefined {
+    return chains
+      .find((c) => c.type === chain)
+      ?.assets.find((a) => a.symbol === asset)?.decimals;
+  },
+
+  /**
+   * Format a transfer's amount, which is in its source chain's units
+   */
+  formatTransferAmount(
+    transfer: CrossChainTransfer,
+    chains: SupportedChain[],
+  ): string {
+    const decimals = bridgeService.getAssetDecimals(
+      chains,
+      transfer.fromChain,
+      transfer.asset,
+    );
+    return decimals === undefined
+      ? `${transfer.amount} base units of`
+      : bridgeService.formatAmount(transfer.amount, decimals);
+  },
+
   /**
    * Format amount for display
    */
-  formatAmount(amount: bigint, decimals: number = 8): string {
-    const divisor = BigInt(10 ** decimals);
+  formatAmount(amount: bigint, decimals: number): string {
+    const divisor = BigInt(10) ** BigInt(decimals);
     const wholePart = amount / divisor;
     const fractionalPart = amount % divisor;
 
@@ -241,7 +354,7 @@ export const bridgeService = {
   /**
    * Parse amount from string input
    */
-  parseAmount(amountStr: string, decimals: number = 8): bigint {
+  parseAmount(amountStr: string, decimals: number): bigint {
     const [wholePart = "0", fractionalPart = "0"] = amountStr.split(".");
     const paddedFractional = fractionalPart
       .padEnd(decimals, "0")
@@ -275,6 +388,7 @@ export const bridgeService = {
     if (status === "Completed") return "text-green-400";
     if (status.includes("Failed")) return "text-red-400";
     if (status === "Cancelled") return "text-gray-400";
+    if (status === "Refunded") return "text-blue-400";
     return "text-yellow-400";
   },
 
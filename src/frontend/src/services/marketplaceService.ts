@@ -33,6 +33,7 @@ export const marketplaceService = (
     price: number;
     paymentMethod: string;
     listingType: string;
+    sellerIdentityId?: string; // Required for real estate
   }) {
     return apiCall(async () => {
       const validation = validateMarketplaceListing({
@@ -52,6 +53,7 @@ export const marketplaceService = (
         [],
         0.0,
         [],
+        listingData.sellerIdentityId ? [listingData.sellerIdentityId] : [],
       );
       if ("Ok" in result) {
         return result.Ok;
@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import Layout from "../components/Layout";
+import { backendService } from "../services/backendService";
 import {
   bridgeService,
   CrossChainTransfer,
@@ -16,6 +17,7 @@ const CrossChainBridge = () => {
   const [toAddress, setToAddress] = useState("");
   const [supportedChains, setSupportedChains] = useState<SupportedChain[]>([]);
   const [bridgeHistory, setBridgeHistory] = useState<CrossChainTransfer[]>([]);
+  const [historyCursor, setHistoryCursor] = useState<string | undefined>();
   const [feeEstimate, setFeeEstimate] = useState<BridgeFeeEstimate | null>(
     null,
   );
@@ -24,20 +26,27 @@ const CrossChainBridge = () => {
   const [error, setError] = useState<string | null>(null);
   const [success, setSuccess] = useState<string | null>(null);
 
+  // Amounts are entered and shown in the source chain's units of the asset
+  const assetDecimals = bridgeService.getAssetDecimals(
+    supportedChains,
+    fromChain,
+    assetType,
+  );
+
   // Load supported chains and history on component mount
   useEffect(() => {
     loadSupportedChains();
     loadBridgeHistory();
   }, []);
 
-  // Calculate fee when amount and fromChain change
+  // Calculate fee when amount, asset and fromChain change
   useEffect(() => {
-    if (fromChain && amount && parseFloat(amount) > 0) {
+    if (fromChain && assetType && amount && parseFloat(amount) > 0) {
       calculateFee();
     } else {
       setFeeEstimate(null);
     }
-  }, [fromChain, amount]);
+  }, [fromChain, assetType, amount, assetDecimals]);
 
   const loadSupportedChains = async () => {
     try {
@@ -48,11 +57,16 @@ const CrossChainBridge = () => {
     }
   };
 
-  const loadBridgeHistory = async () => {
+  // Without a cursor this reloads the first page; with one it appends the
+  // next page to what is already shown.
+  const loadBridgeHistory = async (cursor?: string) => {
     setIsLoading(true);
     try {
-      const history = await bridgeService.getUserBridgeHistory();
-      setBridgeHistory(history);
+      const history = await bridgeService.getUserBridgeHistory(cursor);
+      setBridgeHistory((previous) =>
+        cursor ? [...previous, ...history.transfers] : history.transfers,
+      );
+      setHistoryCursor(history.nextCursor);
     } catch (err) {
       console.error("Failed to load bridge history:", err);
     } finally {
@@ -61,10 +75,14 @@ const CrossChainBridge = () => {
   };
 
   const calculateFee = async () => {
+    if (assetDecimals === undefined) {
+      return;
+    }
     try {
-      const amountBigInt = bridgeService.parseAmount(amount, 8);
+      const amountBigInt = bridgeService.parseAmount(amount, assetDecimals);
       const fee = await bridgeService.calculateBridgeFee(
         fromChain,
+        assetType,
         amountBigInt,
       );
       setFeeEstimate(fee);
@@ -92,15 +110,29 @@ const CrossChainBridge = () => {
       setError(validation.errors.join(", "));
       return;
     }
+    if (assetDecimals === undefined) {
+      setError(`${assetType} is not supported on ${fromChain}`);
+      return;
+    }
 
     setIsSubmitting(true);
     try {
-      const amountBigInt = bridgeService.parseAmount(amount, 8);
-      const requestId = await bridgeService.initiateBridge(
+      // Get user's first identity (in a real app, let user select)
+      const identities = await backendService.getMyIdentities();
+      if (identities.length === 0) {
+        throw new Error("No identity found. Please create an identity first.");
+      }
+
+      const amountBigInt = bridgeService.parseAmount(amount, assetDecimals);
+      const quote = await bridgeService.quoteBridge(
         fromChain,
         toChain,
         assetType,
         amountBigInt,
+      );
+      const requestId = await bridgeService.initiateBridge(
+        identities[0].id,
+        quote,
         fromAddress,
         toAddress,
       );
@@ -276,15 +308,23 @@ const CrossChainBridge = () => {
               </p>
               <input
                 type="number"
-                step="0.00000001"
-                placeholder="0.00000000"
+                step={
+                  assetDecimals === undefined
+                    ? "any"
+                    : (10 ** -assetDecimals).toFixed(assetDecimals)
+                }
+                placeholder={(0).toFixed(assetDecimals ?? 2)}
                 className="form-input flex h-14 w-full min-w-0 flex-1 resize-none overflow-hidden rounded-lg border border-[#3b4754] bg-[#1b2127] p-[15px] text-base leading-normal font-normal text-white placeholder:text-[#9cabba] focus:border-[#3b4754] focus:ring-0 focus:outline-0"
                 value={amount}
                 onChange={(e) => setAmount(e.target.value)}
               />
               {feeEstimate && (
                 <p className="mt-1 text-xs text-[#9cabba]">
-                  Fee: ~{bridgeService.formatAmount(feeEstimate.amount, 8)}{" "}
+                  Fee: ~
+                  {bridgeService.formatAmount(
+                    feeEstimate.amount,
+                    assetDecimals ?? 0,
+                  )}{" "}
                   {assetType} ({feeEstimate.percentage}%)
                 </p>
               )}
@@ -377,7 +417,7 @@ const CrossChainBridge = () => {
                     </tr>
                   </thead>
                   <tbody>
-                    {bridgeHistory.slice(0, 10).map((transfer) => (
+                    {bridgeHistory.map((transfer) => (
                       <tr
                         key={transfer.id}
                         className="border-t border-t-[#3b4754]"
@@ -397,7 +437,10 @@ const CrossChainBridge = () => {
                           {transfer.toChain}
                         </td>
                         <td className="h-[72px] px-4 py-2 text-sm leading-normal font-normal text-[#9cabba]">
-                          {bridgeService.formatAmount(transfer.amount, 8)}{" "}
+                          {bridgeService.formatTransferAmount(
+                            transfer,
+                            supportedChains,
+                          )}{" "}
                           {transfer.asset}
                         </td>
                         <td className="h-[72px] px-4 py-2 text-sm leading-normal font-normal">
@@ -421,6 +464,17 @@ const CrossChainBridge = () => {
                 </p>
               </div>
             )}
+            {historyCursor && (
+              <div className="mt-3 flex justify-center">
+                <button
+                  onClick={() => loadBridgeHistory(historyCursor)}
+                  disabled={isLoading}
+                  className="flex h-10 cursor-pointer items-center justify-center rounded-lg bg-[#283039] px-4 text-sm font-bold text-white disabled:cursor-not-allowed disabled:opacity-50"
+                >
+                  {isLoading ? "Loading..." : "Load more"}
+                </button>
+              </div>
+            )}
           </div>
           <style>
             {`
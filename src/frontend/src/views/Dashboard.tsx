@@ -7,6 +7,7 @@ import { fileService, type FileMetadata } from "../services/fileService";
 import {
   bridgeService,
   type CrossChainTransfer,
+  type SupportedChain,
 } from "../services/bridgeService";
 import Layout from "../components/Layout";
 import {
@@ -28,6 +29,9 @@ const Dashboard = () => {
   const [marketplaceStats, setMarketplaceStats] = useState<any>(null);
   const [userFiles, setUserFiles] = useState<FileMetadata[]>([]);
   const [bridgeHistory, setBridgeHistory] = useState<CrossChainTransfer[]>([]);
+  const [bridgeCursor, setBridgeCursor] = useState<string | undefined>();
+  const [loadingMoreBridge, setLoadingMoreBridge] = useState(false);
+  const [supportedChains, setSupportedChains] = useState<SupportedChain[]>([]);
   const [loading, setLoading] = useState(true);
 
   useEffect(() => {
@@ -35,15 +39,19 @@ const Dashboard = () => {
       if (isAuthenticated && backendActor) {
         try {
           setLoading(true);
-          const [identities, files, bridgeTransfers] = await Promise.all([
-            backendService.getMyIdentities(),
-            fileService.getUserFiles(),
-            bridgeService.getUserBridgeHistory(),
-          ]);
+          const [identities, files, bridgeTransfers, chains] =
+            await Promise.all([
+              backendService.getMyIdentities(),
+              fileService.getUserFiles(),
+              bridgeService.getUserBridgeHistory(),
+              bridgeService.getSupportedChains(),
+            ]);
 
           setUserIdentities(identities);
           setUserFiles(files);
-          setBridgeHistory(bridgeTransfers);
+          setBridgeHistory(bridgeTransfers.transfers);
+          setBridgeCursor(bridgeTransfers.nextCursor);
+          setSupportedChains(chains);
 
           // backendService.getAuditTrail(principal.toText()).then(setAuditTrail);
         } catch (error) {
@@ -66,6 +74,22 @@ const Dashboard = () => {
     loadDashboardData();
   }, [isAuthenticated, backendActor, lendingActor, marketplaceActor]);
 
+  const loadMoreBridgeHistory = async () => {
+    if (!bridgeCursor) {
+      return;
+    }
+    setLoadingMoreBridge(true);
+    try {
+      const page = await bridgeService.getUserBridgeHistory(bridgeCursor);
+      setBridgeHistory((previous) => [...previous, ...page.transfers]);
+      setBridgeCursor(page.nextCursor);
+    } catch (error) {
+      console.error("Failed to load more bridge history:", error);
+    } finally {
+      setLoadingMoreBridge(false);
+    }
+  };
+
   return (
     <Layout>
       <>
@@ -298,7 +322,7 @@ const Dashboard = () => {
                       </tr>
                     </thead>
                     <tbody>
-                      {bridgeHistory.slice(0, 5).map((transfer) => (
+                      {bridgeHistory.map((transfer) => (
                         <tr
                           className="border-t border-t-[#3b4754]"
                           key={transfer.id}
@@ -312,7 +336,10 @@ const Dashboard = () => {
                             {transfer.toChain}
                           </td>
                           <td className="h-[60px] px-4 py-2 text-sm leading-normal font-normal text-[#9cabba]">
-                            {bridgeService.formatAmount(transfer.amount)}{" "}
+                            {bridgeService.formatTransferAmount(
+                              transfer,
+                              supportedChains,
+                            )}{" "}
                             {transfer.asset}
                           </td>
                           <td className="h-[60px] px-4 py-2 text-sm leading-normal font-normal">
@@ -333,11 +360,15 @@ const Dashboard = () => {
                   </table>
                 )}
               </div>
-              {bridgeHistory.length > 5 && (
-                <div className="mt-3 text-center">
-                  <p className="text-sm text-[#9cabba]">
-                    Showing 5 of {bridgeHistory.length} transfers
-                  </p>
+              {bridgeCursor && (
+                <div className="mt-3 flex justify-center">
+                  <button
+                    onClick={loadMoreBridgeHistory}
+                    disabled={loadingMoreBridge}
+                    className="flex h-10 cursor-pointer items-center justify-center rounded-lg bg-[#283039] px-4 text-sm font-bold text-white disabled:cursor-not-allowed disabled:opacity-50"
+                  >
+                    {loadingMoreBridge ? "Loading..." : "Load more"}
+                  </button>
                 </div>
               )}
             </div>
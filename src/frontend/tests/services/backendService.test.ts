@@ -9,7 +9,6 @@ vi.mock("../../../declarations/backend", () => ({
     get_my_identities: vi.fn(),
     add_credential: vi.fn(),
     link_wallet: vi.fn(),
-    update_reputation: vi.fn(),
     get_identity_stats: vi.fn(),
   },
 }));
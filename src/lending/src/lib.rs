@@ -3,13 +3,18 @@ use ic_cdk_macros::*;
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
     storable::Bound,
-    DefaultMemoryImpl, StableBTreeMap, Storable,
+    DefaultMemoryImpl, StableBTreeMap, StableCell, Storable,
 };
 use serde::Serialize;
 
+// Delivery of loan outcomes to the identity canister
+mod outbox;
+pub use outbox::*;
+
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 type StoredLoans = StableBTreeMap<u64, Loan, Memory>;
 type StoredOffers = StableBTreeMap<u64, LoanOffer, Memory>;
+type StoredOutbox = StableBTreeMap<u64, OutboxEntry, Memory>;
 
 #[derive(CandidType, Deserialize, Clone, Debug, Serialize, PartialEq)]
 pub enum AssetType {
@@ -155,6 +160,19 @@ thread_local! {
         )
     );
 
+    static OUTBOX: std::cell::RefCell<StoredOutbox> = std::cell::RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2)))
+        )
+    );
+
+    static OUTBOX_CONFIG: std::cell::RefCell<StableCell<OutboxConfig, Memory>> = std::cell::RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))),
+            OutboxConfig::default(),
+        ).expect("Failed to init outbox config")
+    );
+
     static NEXT_LOAN_ID: std::cell::RefCell<u64> = const { std::cell::RefCell::new(1) };
     static NEXT_OFFER_ID: std::cell::RefCell<u64> = const { std::cell::RefCell::new(1) };
     static EMERGENCY_PAUSE: std::cell::RefCell<bool> = const { std::cell::RefCell::new(false) };
@@ -277,6 +295,7 @@ fn liquidate_loan(loan_id: u64) -> Result<(), String> {
             }
 
             loan.status = LoanStatus::Liquidated;
+            enqueue_loan_outcome(&loan);
             loans_map.insert(loan_id, loan);
 
             // In production, this would:
@@ -507,6 +526,7 @@ pub fn repay_loan(loan_id: u64) -> Result<(), String> {
 
     loan.status = LoanStatus::Repaid;
     loan.repaid_at = Some(ic_cdk::api::time());
+    enqueue_loan_outcome(&loan);
 
     LOANS.with(|l| l.borrow_mut().insert(loan_id, loan));
     Ok(())
@@ -545,11 +565,119 @@ pub async fn liquidateloan(loan_id: u64) -> Result<(), String> {
 
     // Transfer asset ownership to lender
     transfer_asset_ownership(&loan.collateral_asset.asset_id, loan.lender).await?;
+    enqueue_loan_outcome(&loan);
 
     LOANS.with(|l| l.borrow_mut().insert(loan_id, loan));
     Ok(())
 }
 
+// Outcome reporting to the identity canister
+#[init]
+fn init() {
+    start_outbox();
+}
+
+#[post_upgrade]
+fn post_upgrade() {
+    start_outbox();
+}
+
+fn start_outbox() {
+    ic_cdk_timers::set_timer_interval(OUTBOX_INTERVAL, || ic_cdk::spawn(flush_outbox()));
+}
+
+// Queues the outcome of a loan that reached a final status.
+fn enqueue_loan_outcome(loan: &Loan) {
+    let (loan_id, borrower) = (loan.id, loan.borrower);
+    let outcome = match loan.status {
+        LoanStatus::Repaid => TransactionOutcome::LoanRepaid { loan_id, borrower },
+        LoanStatus::Defaulted => TransactionOutcome::LoanDefaulted { loan_id, borrower },
+        LoanStatus::Liquidated => TransactionOutcome::LoanLiquidated { loan_id, borrower },
+        LoanStatus::Pending | LoanStatus::Active => return,
+    };
+
+    let now = ic_cdk::api::time();
+    OUTBOX.with(|o| {
+        let mut outbox = o.borrow_mut();
+        let id = outbox.last_key_value().map_or(1, |(id, _)| id + 1);
+        outbox.insert(
+            id,
+            OutboxEntry {
+                id,
+                outcome,
+                created_at: now,
+                attempts: 0,
+                next_attempt_at: now,
+                last_error: None,
+            },
+        );
+    });
+}
+
+// Sends due entries to the identity canister, removing those it accepts and
+// rescheduling the rest with backoff.
+async fn flush_outbox() {
+    let Some(identity_canister) = OUTBOX_CONFIG.with(|c| c.borrow().get().identity_canister) else {
+        return;
+    };
+    let now = ic_cdk::api::time();
+    let due: Vec<OutboxEntry> = OUTBOX.with(|o| {
+        o.borrow()
+            .iter()
+            .filter(|(_, entry)| entry.next_attempt_at <= now)
+            .take(OUTBOX_BATCH_SIZE)
+            .map(|(_, entry)| entry)
+            .collect()
+    });
+
+    for mut entry in due {
+        // Reschedule before calling so an overlapping run does not resend it
+        entry.attempts += 1;
+        entry.next_attempt_at = now + retry_delay(entry.attempts);
+        OUTBOX.with(|o| o.borrow_mut().insert(entry.id, entry.clone()));
+
+        let result: Result<(Result<u32, String>,), _> = ic_cdk::call(
+            identity_canister,
+            "report_transaction_outcome",
+            (entry.outcome.clone(),),
+        )
+        .await;
+        let error = match result {
+            Ok((Ok(_),)) => {
+                OUTBOX.with(|o| o.borrow_mut().remove(&entry.id));
+                continue;
+            }
+            Ok((Err(e),)) => e,
+            Err((code, msg)) => format!("{:?}: {}", code, msg),
+        };
+        OUTBOX.with(|o| {
+            let mut outbox = o.borrow_mut();
+            if let Some(mut stored) = outbox.get(&entry.id) {
+                stored.last_error = Some(error);
+                outbox.insert(entry.id, stored);
+            }
+        });
+    }
+}
+
+#[update]
+pub fn set_identity_canister(identity_canister: Principal) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::api::caller()) {
+        return Err("Only controllers can set the identity canister".to_string());
+    }
+    OUTBOX_CONFIG.with(|c| {
+        let _ = c.borrow_mut().set(OutboxConfig {
+            identity_canister: Some(identity_canister),
+        });
+    });
+    Ok(())
+}
+
+#[query]
+pub fn get_outbox_entries() -> Vec<OutboxEntry> {
+    OUTBOX.with(|o| o.borrow().iter().map(|(_, entry)| entry).collect())
+}
+
 // Mock function to get asset from identity canister
 async fn get_asset_from_identity_canister(asset_id: &str) -> Result<CollateralAsset, String> {
     // In production, this would call the identity canister
@@ -0,0 +1,68 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::time::Duration;
+
+pub const OUTBOX_INTERVAL: Duration = Duration::from_secs(60);
+pub const OUTBOX_BATCH_SIZE: usize = 20;
+
+const RETRY_BASE_NS: u64 = 60 * 1_000_000_000;
+const RETRY_MAX_NS: u64 = 6 * 3600 * 1_000_000_000;
+
+// Subset of the identity canister's `TransactionOutcome` reported from here.
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize)]
+pub enum TransactionOutcome {
+    LoanRepaid { loan_id: u64, borrower: Principal },
+    LoanDefaulted { loan_id: u64, borrower: Principal },
+    LoanLiquidated { loan_id: u64, borrower: Principal },
+}
+
+// An outcome waiting to be delivered to the identity canister. Entries stay
+// until the identity canister accepts them.
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize)]
+pub struct OutboxEntry {
+    pub id: u64,
+    pub outcome: TransactionOutcome,
+    pub created_at: u64,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+}
+
+impl Storable for OutboxEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize, Default)]
+pub struct OutboxConfig {
+    pub identity_canister: Option<Principal>,
+}
+
+impl Storable for OutboxConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Encode!(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// Delay before the next attempt after `attempts` tries: one minute,
+// doubling up to six hours.
+pub fn retry_delay(attempts: u32) -> u64 {
+    RETRY_BASE_NS
+        .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+        .min(RETRY_MAX_NS)
+}
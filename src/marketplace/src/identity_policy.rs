@@ -0,0 +1,54 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+use std::fmt;
+
+// Subset of the identity canister's `PolicyAction` checked from here.
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize)]
+pub enum PolicyAction {
+    ListRealEstate { seller: Principal },
+}
+
+// The identity canister's `Error`. Every variant is mirrored, since a reply
+// carrying one missing here would fail to decode.
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize)]
+pub enum IdentityError {
+    NotFound(String),
+    Unauthorized,
+    RateLimitExceeded,
+    InvalidInput(String),
+    VerificationFailed(String),
+    CanisterError(String),
+    EmergencyPause,
+    InsufficientSignatures,
+    OperationExpired,
+    PolicyViolation(PolicyViolation),
+    AlreadyRecorded,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize)]
+pub struct PolicyViolation {
+    pub jurisdiction: String,
+    pub rule: PolicyRule,
+    pub message: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Serialize)]
+pub enum PolicyRule {
+    MinKycForAssetLinking,
+    MaxBridgeAmount,
+    BlockedChain,
+    RealEstateCredentials,
+}
+
+impl fmt::Display for IdentityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentityError::PolicyViolation(violation) => write!(
+                f,
+                "Policy violation ({:?}, {}): {}",
+                violation.rule, violation.jurisdiction, violation.message
+            ),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
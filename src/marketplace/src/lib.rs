@@ -9,16 +9,25 @@ use ic_cdk_macros::*;
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
     storable::Bound,
-    DefaultMemoryImpl, StableBTreeMap, Storable,
+    DefaultMemoryImpl, StableBTreeMap, StableCell, Storable,
 };
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
+// Delivery of order outcomes to the identity canister
+mod outbox;
+pub use outbox::*;
+
+// Jurisdiction checks against the identity canister
+mod identity_policy;
+use identity_policy::*;
+
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 type StoredAssets = StableBTreeMap<u64, VerifiedAsset, Memory>;
 type StoredListings = StableBTreeMap<u64, MarketplaceListing, Memory>;
 type StoredOrders = StableBTreeMap<u64, Order, Memory>;
+type StoredOutbox = StableBTreeMap<u64, OutboxEntry, Memory>;
 
 // Asset types supported in the marketplace
 #[derive(CandidType, Deserialize, Clone, Debug, Serialize, PartialEq)]
@@ -206,6 +215,19 @@ thread_local! {
         )
     );
 
+    static OUTBOX: std::cell::RefCell<StoredOutbox> = std::cell::RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3)))
+        )
+    );
+
+    static OUTBOX_CONFIG: std::cell::RefCell<StableCell<OutboxConfig, Memory>> = std::cell::RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4))),
+            OutboxConfig::default(),
+        ).expect("Failed to init outbox config")
+    );
+
     static NEXT_ASSET_ID: std::cell::RefCell<u64> = const { std::cell::RefCell::new(1) };
     static NEXT_LISTING_ID: std::cell::RefCell<u64> = const { std::cell::RefCell::new(1) };
     static NEXT_ORDER_ID: std::cell::RefCell<u64> = const { std::cell::RefCell::new(1) };
@@ -249,6 +271,7 @@ fn validate_expiration(expires_at: Option<u64>) -> Result<(), String> {
 
 // Marketplace core functions
 #[update]
+#[allow(clippy::too_many_arguments)] // Candid endpoint arguments
 pub async fn create_listing(
     asset_id: u64,
     price: f64,
@@ -257,6 +280,7 @@ pub async fn create_listing(
     expires_at: Option<u64>,
     minimum_verification_score: f32,
     cross_chain_settlement: Option<CrossChainNetwork>,
+    seller_identity_id: Option<String>,
 ) -> Result<u64, String> {
     let caller = ic_cdk::api::caller();
 
@@ -286,6 +310,10 @@ pub async fn create_listing(
         return Err("Only verified assets can be listed".to_string());
     }
 
+    if asset.asset_type == AssetType::RealEstate {
+        check_real_estate_policy(caller, seller_identity_id).await?;
+    }
+
     let listing_id = NEXT_LISTING_ID.with(|n| {
         let id = *n.borrow();
         *n.borrow_mut() = id + 1;
@@ -310,6 +338,33 @@ pub async fn create_listing(
     Ok(listing_id)
 }
 
+// Real estate may only be listed by a seller whose identity meets its
+// jurisdiction's credential rules, as evaluated by the identity canister.
+async fn check_real_estate_policy(
+    seller: Principal,
+    identity_id: Option<String>,
+) -> Result<(), String> {
+    let identity_id = identity_id.ok_or("Real estate listings require the seller's identity")?;
+    let identity_canister = OUTBOX_CONFIG
+        .with(|c| c.borrow().get().identity_canister)
+        .ok_or("Identity canister is not configured")?;
+
+    let result: Result<(Result<(), IdentityError>,), _> = ic_cdk::call(
+        identity_canister,
+        "check_jurisdiction_policy",
+        (identity_id, PolicyAction::ListRealEstate { seller }),
+    )
+    .await;
+    match result {
+        Ok((Ok(()),)) => Ok(()),
+        Ok((Err(IdentityError::Unauthorized),)) => {
+            Err("Seller does not own the identity".to_string())
+        }
+        Ok((Err(e),)) => Err(e.to_string()),
+        Err((code, msg)) => Err(format!("Policy check failed: {:?}: {}", code, msg)),
+    }
+}
+
 #[query]
 pub fn get_listings(
     limit: Option<u32>,
@@ -559,6 +614,11 @@ pub async fn complete_cross_chain_settlement(order_id: u64, tx_hash: String) ->
                 }
             });
 
+            enqueue_outcome(TransactionOutcome::OrderCompleted {
+                order_id,
+                buyer: order.buyer,
+                seller: order.seller,
+            });
             ORDERS.with(|o| o.borrow_mut().insert(order_id, order));
             Ok(())
         } else {
@@ -775,6 +835,104 @@ pub fn register_verified_asset(asset: VerifiedAsset) -> Result<u64, String> {
     Ok(asset_id)
 }
 
+// Outcome reporting to the identity canister
+#[init]
+fn init() {
+    start_outbox();
+}
+
+#[post_upgrade]
+fn post_upgrade() {
+    start_outbox();
+}
+
+fn start_outbox() {
+    ic_cdk_timers::set_timer_interval(OUTBOX_INTERVAL, || ic_cdk::spawn(flush_outbox()));
+}
+
+fn enqueue_outcome(outcome: TransactionOutcome) {
+    let now = ic_cdk::api::time();
+    OUTBOX.with(|o| {
+        let mut outbox = o.borrow_mut();
+        let id = outbox.last_key_value().map_or(1, |(id, _)| id + 1);
+        outbox.insert(
+            id,
+            OutboxEntry {
+                id,
+                outcome,
+                created_at: now,
+                attempts: 0,
+                next_attempt_at: now,
+                last_error: None,
+            },
+        );
+    });
+}
+
+// Sends due entries to the identity canister, removing those it accepts and
+// rescheduling the rest with backoff.
+async fn flush_outbox() {
+    let Some(identity_canister) = OUTBOX_CONFIG.with(|c| c.borrow().get().identity_canister) else {
+        return;
+    };
+    let now = ic_cdk::api::time();
+    let due: Vec<OutboxEntry> = OUTBOX.with(|o| {
+        o.borrow()
+            .iter()
+            .filter(|(_, entry)| entry.next_attempt_at <= now)
+            .take(OUTBOX_BATCH_SIZE)
+            .map(|(_, entry)| entry)
+            .collect()
+    });
+
+    for mut entry in due {
+        // Reschedule before calling so an overlapping run does not resend it
+        entry.attempts += 1;
+        entry.next_attempt_at = now + retry_delay(entry.attempts);
+        OUTBOX.with(|o| o.borrow_mut().insert(entry.id, entry.clone()));
+
+        let result: Result<(Result<u32, String>,), _> = ic_cdk::call(
+            identity_canister,
+            "report_transaction_outcome",
+            (entry.outcome.clone(),),
+        )
+        .await;
+        let error = match result {
+            Ok((Ok(_),)) => {
+                OUTBOX.with(|o| o.borrow_mut().remove(&entry.id));
+                continue;
+            }
+            Ok((Err(e),)) => e,
+            Err((code, msg)) => format!("{:?}: {}", code, msg),
+        };
+        OUTBOX.with(|o| {
+            let mut outbox = o.borrow_mut();
+            if let Some(mut stored) = outbox.get(&entry.id) {
+                stored.last_error = Some(error);
+                outbox.insert(entry.id, stored);
+            }
+        });
+    }
+}
+
+#[update]
+pub fn set_identity_canister(identity_canister: Principal) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::api::caller()) {
+        return Err("Only controllers can set the identity canister".to_string());
+    }
+    OUTBOX_CONFIG.with(|c| {
+        let _ = c.borrow_mut().set(OutboxConfig {
+            identity_canister: Some(identity_canister),
+        });
+    });
+    Ok(())
+}
+
+#[query]
+pub fn get_outbox_entries() -> Vec<OutboxEntry> {
+    OUTBOX.with(|o| o.borrow().iter().map(|(_, entry)| entry).collect())
+}
+
 // Marketplace statistics
 #[query]
 pub fn get_marketplace_stats() -> MarketplaceStats {
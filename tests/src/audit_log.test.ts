@@ -0,0 +1,114 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+import { Principal } from "@dfinity/principal";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+describe("Audit log", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const createIdentity = async () => {
+    const result = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in result)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  it("should keep entries from the same caller and round", async () => {
+    const [first, second] = await Promise.all([
+      createIdentity(),
+      createIdentity(),
+    ]);
+
+    const trail = await actor.get_principal_audit_trail(
+      Principal.anonymous(),
+      [],
+      [],
+    );
+    if (!("Ok" in trail)) {
+      throw new Error(`audit trail failed: ${JSON.stringify(trail)}`);
+    }
+    expect(trail.Ok.map((e) => e.resource_id).sort()).toEqual(
+      [first, second].sort(),
+    );
+    expect(trail.Ok.map((e) => e.sequence)).toEqual([1n, 0n]);
+  });
+
+  it("should link each entry to the hash of its predecessor", async () => {
+    const identityId = await createIdentity();
+    await createIdentity();
+
+    const trail = await actor.get_audit_trail(identityId, [], []);
+    if (!("Ok" in trail)) {
+      throw new Error(`audit trail failed: ${JSON.stringify(trail)}`);
+    }
+    expect(trail.Ok[0].sequence).toEqual(0n);
+    expect(trail.Ok[0].previous_hash).toEqual(new Uint8Array(32));
+
+    const verification = await actor.verify_audit_chain(0n, 100n);
+    if (!("Ok" in verification)) {
+      throw new Error(`verify failed: ${JSON.stringify(verification)}`);
+    }
+    expect(verification.Ok.valid).toBe(true);
+    expect(verification.Ok.to).toEqual(1n);
+    expect(verification.Ok.first_invalid).toEqual([]);
+  });
+
+  it("should certify the audit head", async () => {
+    await createIdentity();
+
+    const checkpoint = await actor.get_audit_checkpoint();
+    expect(checkpoint.length).toEqual(1n);
+    expect(checkpoint.certificate).toHaveLength(1);
+
+    const verification = await actor.verify_audit_chain(0n, 0n);
+    if (!("Ok" in verification)) {
+      throw new Error(`verify failed: ${JSON.stringify(verification)}`);
+    }
+    expect(checkpoint.head_hash).toEqual(verification.Ok.last_hash);
+  });
+
+  it("should reject ranges past the end of the log", async () => {
+    await createIdentity();
+
+    expect(await actor.verify_audit_chain(5n, 10n)).toEqual({
+      Err: "Audit log has 1 entries",
+    });
+  });
+});
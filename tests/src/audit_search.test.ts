@@ -0,0 +1,145 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  type AuditSearchFilter,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const ETH_ADDRESS = "0x52908400098527886e0f7030069857d2e4169ee7";
+
+const LINK_WALLET_FILTER: AuditSearchFilter = {
+  operation: [{ LinkWallet: null }],
+  principal: [],
+  resource_type: [],
+  result: [],
+  recorded_after: [],
+  recorded_before: [],
+};
+
+describe("Audit search and export", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  const auditor = createIdentity();
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    await actor.link_wallet(identity.Ok, { Ethereum: null }, ETH_ADDRESS);
+    await actor.set_audit_role(auditor.getPrincipal(), [{ Auditor: null }]);
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  it("should require an audit role", async () => {
+    actor.setIdentity(createIdentity());
+    expect(await actor.search_audit_log(LINK_WALLET_FILTER, [], [])).toEqual({
+      Err: { Unauthorized: null },
+    });
+  });
+
+  it("should show wallet addresses to compliance officers", async () => {
+    const page = await actor.search_audit_log(LINK_WALLET_FILTER, [], []);
+    if (!("Ok" in page)) {
+      throw new Error(`search failed: ${JSON.stringify(page)}`);
+    }
+    expect(page.Ok.entries).toHaveLength(1);
+    expect(page.Ok.entries[0].details.related_entities).toEqual([ETH_ADDRESS]);
+  });
+
+  it("should mask wallet addresses for auditors", async () => {
+    actor.setIdentity(auditor);
+    const page = await actor.search_audit_log(LINK_WALLET_FILTER, [], []);
+    if (!("Ok" in page)) {
+      throw new Error(`search failed: ${JSON.stringify(page)}`);
+    }
+    expect(page.Ok.entries[0].details.related_entities).toEqual([
+      "0x5290…9ee7",
+    ]);
+    expect(page.Ok.entries[0].details.payload).toEqual({
+      WalletLinked: {
+        chain_type: { Ethereum: null },
+        address: "0x5290…9ee7",
+        signature_verified: false,
+      },
+    });
+  });
+
+  it("should export in chunks with a single CSV header", async () => {
+    const filter = { ...LINK_WALLET_FILTER, operation: [] as [] };
+
+    const first = await actor.export_audit_log(filter, { Csv: null }, [], [1]);
+    if (!("Ok" in first)) {
+      throw new Error(`export failed: ${JSON.stringify(first)}`);
+    }
+    expect(first.Ok.data.split("\n")[0]).toMatch(/^sequence,id,timestamp/);
+    expect(first.Ok.entry_count).toEqual(1);
+    expect(first.Ok.next_cursor).toHaveLength(1);
+
+    const second = await actor.export_audit_log(
+      filter,
+      { Csv: null },
+      first.Ok.next_cursor,
+      [1],
+    );
+    if (!("Ok" in second)) {
+      throw new Error(`export failed: ${JSON.stringify(second)}`);
+    }
+    expect(second.Ok.data).not.toMatch(/^sequence,/);
+  });
+
+  it("should export JSON Lines", async () => {
+    const chunk = await actor.export_audit_log(
+      LINK_WALLET_FILTER,
+      { JsonLines: null },
+      [],
+      [],
+    );
+    if (!("Ok" in chunk)) {
+      throw new Error(`export failed: ${JSON.stringify(chunk)}`);
+    }
+    const rows = chunk.Ok.data
+      .trim()
+      .split("\n")
+      .map((line) => JSON.parse(line));
+    expect(rows).toHaveLength(1);
+    expect(rows[0].operation).toEqual("LinkWallet");
+    expect(JSON.parse(rows[0].details)).toEqual({
+      WalletLinked: {
+        chain_type: "Ethereum",
+        address: ETH_ADDRESS,
+        signature_verified: false,
+      },
+    });
+  });
+});
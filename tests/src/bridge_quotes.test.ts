@@ -0,0 +1,270 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  type BridgeHistoryFilter,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+import { addPolygonRoute } from "./bridge_routes";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const ETH_ADDRESS = "0x52908400098527886e0f7030069857d2e4169ee7";
+const POLYGON_ADDRESS = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+
+describe("Bridge quotes", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    // Let the init timer load the quote secret
+    await pic.tick(2);
+
+    // Bridge prerequisites: an identity for the caller and allow-listed
+    // addresses, since the test principal cannot verify wallets or pass KYC
+    await actor.set_bridge_min_kyc_level({ None: null });
+    await actor.add_bridge_allowed_address({ Ethereum: null }, ETH_ADDRESS);
+    await actor.add_bridge_allowed_address({ Polygon: null }, POLYGON_ADDRESS);
+    await addPolygonRoute(actor);
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const quoteEth = async () => {
+    const quote = await actor.quote_bridge(
+      { Ethereum: null },
+      { Polygon: null },
+      "ETH",
+      1_000_000_000_000_000n,
+    );
+    if (!("Ok" in quote)) {
+      throw new Error(`quote failed: ${JSON.stringify(quote)}`);
+    }
+    return quote.Ok;
+  };
+
+  it("should rescale the payout to the destination asset's decimals", async () => {
+    const quote = await quoteEth();
+
+    // 0.001 ETH less 30 bps and the 0.0001 ETH fixed fee, converted from
+    // 18 to 8 decimals, less the destination gas estimate
+    expect(quote.fee.amount).toEqual(103_000_000_000_000n);
+    expect(quote.receive_amount).toEqual(89_700n - 100n);
+    expect(quote.destination_decimals).toEqual(8);
+  });
+
+  it("should reject routes the bridge cannot pay out", async () => {
+    const quote = await actor.quote_bridge(
+      { Ethereum: null },
+      { Solana: null },
+      "ETH",
+      1_000_000_000_000_000n,
+    );
+    expect(quote).toEqual({
+      Err: "Asset ETH not supported on destination chain",
+    });
+  });
+
+  it("should store the quoted terms on the request", async () => {
+    const quote = await quoteEth();
+
+    const created = await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote,
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    if (!("Ok" in created)) {
+      throw new Error(`initiate failed: ${JSON.stringify(created)}`);
+    }
+
+    const request = await actor.get_bridge_request(created.Ok);
+    if (!("Ok" in request)) {
+      throw new Error("bridge request not found");
+    }
+    expect(request.Ok.quote_id).toEqual(quote.quote_id);
+    expect(request.Ok.receive_amount).toEqual(quote.receive_amount);
+  });
+
+  it("should reject a quote with altered terms", async () => {
+    const quote = await quoteEth();
+
+    const result = await actor.initiate_cross_chain_bridge(
+      identityId,
+      { ...quote, receive_amount: quote.receive_amount * 2n },
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    expect(result).toEqual({ Err: "Invalid bridge quote" });
+  });
+
+  it("should reject a quote that was already used", async () => {
+    const quote = await quoteEth();
+
+    await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote,
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    const second = await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote,
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    expect(second).toEqual({ Err: "Quote has already been used" });
+  });
+
+  it("should reject an expired quote", async () => {
+    const quote = await quoteEth();
+
+    await pic.advanceTime(6 * 60 * 1_000);
+    await pic.tick();
+
+    const result = await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote,
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    expect(result).toEqual({ Err: "Quote has expired" });
+  });
+
+  it("should enforce the rolling per-user daily limit", async () => {
+    const tenEth = 10_000_000_000_000_000_000n;
+    const bridgeTenEth = async () => {
+      const quote = await actor.quote_bridge(
+        { Ethereum: null },
+        { Polygon: null },
+        "ETH",
+        tenEth,
+      );
+      if (!("Ok" in quote)) {
+        return quote;
+      }
+      return actor.initiate_cross_chain_bridge(
+        identityId,
+        quote.Ok,
+        ETH_ADDRESS,
+        POLYGON_ADDRESS,
+      );
+    };
+
+    expect("Ok" in (await bridgeTenEth())).toBe(true);
+    expect("Ok" in (await bridgeTenEth())).toBe(true);
+    expect(await bridgeTenEth()).toEqual({
+      Err: "Daily limit for ETH exceeded: 0 remaining",
+    });
+
+    await pic.advanceTime(24 * 60 * 60 * 1_000 + 1_000);
+    await pic.tick();
+
+    expect("Ok" in (await bridgeTenEth())).toBe(true);
+  });
+
+  it("should reject addresses that are neither linked nor allow-listed", async () => {
+    const quote = await quoteEth();
+
+    const result = await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote,
+      "0x0000000000000000000000000000000000000001",
+      POLYGON_ADDRESS,
+    );
+    expect(result).toEqual({
+      Err: "Source address is not a verified wallet of this identity",
+    });
+  });
+
+  it("should require the configured KYC level", async () => {
+    await actor.set_bridge_min_kyc_level({ Basic: null });
+    const quote = await quoteEth();
+
+    const result = await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote,
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    expect(result).toEqual({ Err: "Bridge requires KYC level Basic" });
+  });
+
+  it("should page bridge history newest first", async () => {
+    const ids: string[] = [];
+    for (let i = 0; i < 3; i++) {
+      const created = await actor.initiate_cross_chain_bridge(
+        identityId,
+        await quoteEth(),
+        ETH_ADDRESS,
+        POLYGON_ADDRESS,
+      );
+      if (!("Ok" in created)) {
+        throw new Error(`initiate failed: ${JSON.stringify(created)}`);
+      }
+      ids.push(created.Ok);
+    }
+
+    const filter: BridgeHistoryFilter = {
+      status: [{ Initiated: null }],
+      from_chain: [],
+      to_chain: [],
+      asset_type: [],
+      created_after: [],
+      created_before: [],
+      user_principal: [],
+    };
+
+    const page = async (cursor: [] | [string]) => {
+      const result = await actor.get_user_bridge_history(filter, cursor, [2]);
+      if (!("Ok" in result)) {
+        throw new Error(`history failed: ${JSON.stringify(result)}`);
+      }
+      return result.Ok;
+    };
+
+    const first = await page([]);
+    expect(first.requests.map((r) => r.request_id)).toEqual([ids[2], ids[1]]);
+    expect(first.next_cursor).toEqual([ids[1]]);
+
+    const second = await page(first.next_cursor);
+    expect(second.requests.map((r) => r.request_id)).toEqual([ids[0]]);
+    expect(second.next_cursor).toEqual([]);
+
+    expect(
+      await actor.get_user_bridge_history(filter, ["bridge_unknown"], [2]),
+    ).toEqual({ Err: "Unknown cursor" });
+  });
+});
@@ -0,0 +1,47 @@
+import { type Actor } from "@dfinity/pic";
+
+import { type _SERVICE } from "../../src/declarations/backend/backend.did.js";
+
+// The default chains have no second EVM chain, and only EVM chains can pay
+// out wrapped assets. This adds Polygon with wrapped ETH at 8 decimals so the
+// tests have an ETH route with a decimal conversion.
+export const addPolygonRoute = async (actor: Actor<_SERVICE>) => {
+  const result = await actor.add_chain_config({
+    chain_type: { Polygon: null },
+    rpc_url: "https://polygon-rpc.com",
+    bridge_contract: "0x8484Ef722627bf18ca5Ae6BcF031c23E6e922B30",
+    supported_assets: [
+      {
+        symbol: "POL",
+        decimals: 18,
+        min_amount: 10_000_000_000_000_000n,
+        max_amount: 10_000_000_000_000_000_000_000n,
+        fixed_fee: 1_000_000_000_000_000n,
+        destination_gas_fee: 1_000_000_000_000_000n,
+        user_daily_limit: 20_000_000_000_000_000_000_000n,
+        daily_limit: 100_000_000_000_000_000_000_000n,
+        outflow_breaker_threshold: 100_000_000_000_000_000_000_000n,
+        token_contract: [],
+      },
+      {
+        symbol: "ETH",
+        decimals: 8,
+        min_amount: 100_000n,
+        max_amount: 1_000_000_000n,
+        fixed_fee: 10_000n,
+        destination_gas_fee: 100n,
+        user_daily_limit: 2_000_000_000n,
+        daily_limit: 10_000_000_000n,
+        outflow_breaker_threshold: 10_000_000_000n,
+        token_contract: ["0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"],
+      },
+    ],
+    fee_bps: 20,
+    confirmation_blocks: 64,
+    evm_chain_id: [137n],
+    enabled: true,
+  });
+  if (!("Ok" in result)) {
+    throw new Error(`add_chain_config failed: ${JSON.stringify(result)}`);
+  }
+};
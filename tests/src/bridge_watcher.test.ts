@@ -0,0 +1,160 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { readFileSync } from "fs";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+import { addPolygonRoute } from "./bridge_routes";
+
+const here = dirname(fileURLToPath(import.meta.url));
+
+const WASM_PATH = resolve(
+  here,
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const fixture = (name: string): Uint8Array =>
+  readFileSync(resolve(here, "..", "fixtures", "rpc", name));
+
+const ETH_ADDRESS = "0x52908400098527886e0f7030069857d2e4169ee7";
+const POLYGON_ADDRESS = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+
+const DEPOSIT_TX =
+  "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+
+describe("Bridge deposit watcher", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const setup = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = setup.actor;
+
+    // Let the init timer load the quote secret
+    await pic.tick(2);
+
+    // Bridge prerequisites: an identity for the caller and allow-listed
+    // addresses, since the test principal cannot verify wallets or pass KYC
+    await actor.set_bridge_min_kyc_level({ None: null });
+    await actor.add_bridge_allowed_address({ Ethereum: null }, ETH_ADDRESS);
+    await actor.add_bridge_allowed_address({ Polygon: null }, POLYGON_ADDRESS);
+    await addPolygonRoute(actor);
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  // Creates an ETH -> Polygon request, attaches the deposit and lets the watcher
+  // timer issue its RPC outcall, which is answered with `fixtureName`.
+  const runWatcherWith = async (fixtureName: string) => {
+    const quote = await actor.quote_bridge(
+      { Ethereum: null },
+      { Polygon: null },
+      "ETH",
+      1_000_000_000_000_000n,
+    );
+    if (!("Ok" in quote)) {
+      throw new Error(`quote failed: ${JSON.stringify(quote)}`);
+    }
+
+    const created = await actor.initiate_cross_chain_bridge(
+      identityId,
+      quote.Ok,
+      ETH_ADDRESS,
+      POLYGON_ADDRESS,
+    );
+    if (!("Ok" in created)) {
+      throw new Error(`initiate failed: ${JSON.stringify(created)}`);
+    }
+    const requestId = created.Ok;
+
+    expect(await actor.submit_bridge_deposit(requestId, DEPOSIT_TX)).toEqual({
+      Ok: null,
+    });
+
+    await pic.advanceTime(60_000);
+    await pic.tick(2);
+
+    const outcalls = await pic.getPendingHttpsOutcalls();
+    expect(outcalls).toHaveLength(1);
+
+    await pic.mockPendingHttpsOutcall({
+      requestId: outcalls[0].requestId,
+      subnetId: outcalls[0].subnetId,
+      response: {
+        type: "success",
+        statusCode: 200,
+        headers: [],
+        body: fixture(fixtureName),
+      },
+    });
+    await pic.tick(2);
+
+    const request = await actor.get_bridge_request(requestId);
+    if (!("Ok" in request)) {
+      throw new Error("bridge request not found");
+    }
+    return request.Ok;
+  };
+
+  it("should lock a confirmed deposit matching the request", async () => {
+    const request = await runWatcherWith("evm_deposit_confirmed.json");
+
+    expect(request.status).toEqual({ SourceLocked: null });
+    expect(request.transaction_hashes).toEqual([DEPOSIT_TX]);
+  });
+
+  it("should keep waiting while the deposit lacks confirmations", async () => {
+    const request = await runWatcherWith("evm_deposit_unconfirmed.json");
+
+    expect(request.status).toEqual({ Initiated: null });
+  });
+
+  it("should fail a request whose deposit amount does not match", async () => {
+    const request = await runWatcherWith("evm_deposit_wrong_amount.json");
+
+    expect("Failed" in request.status).toBe(true);
+  });
+
+  it("should move a locked request that is never released to RefundPending", async () => {
+    const locked = await runWatcherWith("evm_deposit_confirmed.json");
+    expect(locked.status).toEqual({ SourceLocked: null });
+
+    await pic.advanceTime(6 * 60 * 60 * 1_000 + 60_000);
+    await pic.tick(2);
+
+    const request = await actor.get_bridge_request(locked.request_id);
+    if (!("Ok" in request)) {
+      throw new Error("bridge request not found");
+    }
+    expect(request.Ok.status).toEqual({
+      RefundPending: { reason: "Release was not signed in time" },
+    });
+  });
+});
@@ -0,0 +1,148 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const MINUTE_NS = 60_000_000_000n;
+
+describe("Compliance workflow", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const attestation = async () => {
+    const result = await actor.get_compliance_attestation(identityId);
+    if (!("Ok" in result)) {
+      throw new Error(`attestation failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  it("should require evidence before raising the KYC level", async () => {
+    const result = await actor.update_kyc_level(
+      identityId,
+      { Basic: null },
+      [],
+    );
+    expect(result).toEqual({
+      Err: {
+        InvalidInput:
+          "KYC evidence must be submitted before raising the level",
+      },
+    });
+  });
+
+  it("should attest screening results until they expire", async () => {
+    await actor.submit_kyc_evidence(identityId, "passport", "case-4411");
+    await actor.update_kyc_level(identityId, { Enhanced: null }, [
+      10n * MINUTE_NS,
+    ]);
+    await actor.record_sanctions_check(identityId, { Cleared: null }, []);
+
+    const current = await attestation();
+    expect(current.kyc_level).toEqual({ Enhanced: null });
+    expect(current.sanctions_status).toEqual({ Cleared: null });
+    expect(current.valid_until).toHaveLength(1);
+
+    await pic.advanceTime(11 * 60 * 1_000);
+    await pic.tick();
+
+    const expired = await attestation();
+    expect(expired.kyc_level).toEqual({ None: null });
+    expect(expired.sanctions_status).toEqual({ Cleared: null });
+
+    const queue = await actor.get_rescreening_queue([]);
+    if (!("Ok" in queue)) {
+      throw new Error(`queue failed: ${JSON.stringify(queue)}`);
+    }
+    expect(queue.Ok.map((item) => item.identity_id)).toEqual([identityId]);
+  });
+
+  it("should let registered KYC providers record results", async () => {
+    const provider = createIdentity();
+    await actor.set_kyc_provider(provider.getPrincipal(), true);
+
+    actor.setIdentity(provider);
+    expect(
+      await actor.record_aml_screening(identityId, { Cleared: null }, []),
+    ).toEqual({ Ok: null });
+
+    actor.setIdentity(createIdentity());
+    expect(
+      await actor.record_aml_screening(identityId, { Flagged: null }, []),
+    ).toEqual({ Err: { Unauthorized: null } });
+  });
+
+  it("should keep officer duties from KYC providers", async () => {
+    const provider = createIdentity();
+    await actor.set_kyc_provider(provider.getPrincipal(), true);
+
+    actor.setIdentity(provider);
+    expect(await actor.get_rescreening_queue([])).toEqual({
+      Err: { Unauthorized: null },
+    });
+    expect(await actor.get_report_queue()).toEqual({
+      Err: { Unauthorized: null },
+    });
+    expect(await actor.set_identity_jurisdiction(identityId, "DE")).toEqual({
+      Err: { Unauthorized: null },
+    });
+  });
+
+  it("should audit every compliance change", async () => {
+    await actor.submit_kyc_evidence(identityId, "passport", "case-4411");
+    await actor.update_kyc_level(identityId, { Basic: null }, []);
+
+    const trail = await actor.get_audit_trail(identityId, [], []);
+    if (!("Ok" in trail)) {
+      throw new Error(`audit trail failed: ${JSON.stringify(trail)}`);
+    }
+    const updates = trail.Ok.filter(
+      (entry) => "ComplianceUpdate" in entry.operation,
+    );
+    expect(updates).toHaveLength(2);
+    expect(updates[0].details.payload).toMatchObject({
+      KycLevelChanged: { from: { None: null }, to: { Basic: null } },
+    });
+  });
+});
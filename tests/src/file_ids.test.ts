@@ -0,0 +1,87 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+describe("File IDs", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const textUpload = (name: string, body: string) => ({
+    original_name: name,
+    mime_type: "text/plain",
+    data: new TextEncoder().encode(body),
+    asset_id: [] as [] | [string],
+    identity_id: [] as [] | [string],
+    tags: [],
+  });
+
+  // Regression: IDs used to be `file_{time}_{principal[..8]}_{name_len}`, so
+  // two uploads at the same timestamp with equal-length names collided and the
+  // second overwrote the first. Resetting the clock before each upload keeps
+  // them as close together as PocketIC allows, though it still advances time
+  // between rounds.
+  it("should not overwrite files uploaded close together", async () => {
+    const start = await pic.getTime();
+
+    await pic.setTime(start);
+    const first = await actor.upload_file(textUpload("a.txt", "first"));
+    await pic.setTime(start);
+    const second = await actor.upload_file(textUpload("b.txt", "second"));
+
+    if (!("Ok" in first) || !("Ok" in second)) {
+      throw new Error("upload failed");
+    }
+    expect(first.Ok.file_id).not.toEqual(second.Ok.file_id);
+
+    const files = await actor.get_user_files();
+    expect(files.map((f) => f.file_id).sort()).toEqual(
+      [first.Ok.file_id, second.Ok.file_id].sort(),
+    );
+
+    const downloaded = await actor.download_file(first.Ok.file_id);
+    expect(downloaded).toEqual({ Ok: new TextEncoder().encode("first") });
+  });
+
+  it("should issue unique IDs for repeated uploads of the same name", async () => {
+    const ids = new Set<string>();
+    for (let i = 0; i < 5; i++) {
+      const result = await actor.upload_file(textUpload("same.txt", `n${i}`));
+      if (!("Ok" in result)) {
+        throw new Error("upload failed");
+      }
+      ids.add(result.Ok.file_id);
+    }
+    expect(ids.size).toEqual(5);
+  });
+});
@@ -0,0 +1,245 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const DAY_MS = 24 * 60 * 60 * 1_000;
+
+const PRIVACY_SETTINGS = {
+  default_privacy_level: { Public: null },
+  public_credentials: [],
+  cross_chain_visibility: [],
+};
+
+describe("Identity reports", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let targetId: string;
+  const admin = createIdentity();
+  const target = createIdentity();
+  const reporter = createIdentity();
+  const appealReviewer = createIdentity();
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+      sender: admin.getPrincipal(),
+    });
+
+    actor = fixture.actor;
+    actor.setIdentity(admin);
+    await actor.set_audit_role(appealReviewer.getPrincipal(), [
+      { ComplianceOfficer: null },
+    ]);
+
+    for (const owner of [target, reporter]) {
+      actor.setIdentity(owner);
+      const identity = await actor.create_identity([], [], PRIVACY_SETTINGS);
+      if (!("Ok" in identity)) {
+        throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+      }
+      if (owner === target) {
+        targetId = identity.Ok;
+      }
+    }
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const fileReport = async () => {
+    actor.setIdentity(reporter);
+    const result = await actor.report_identity(
+      targetId,
+      { NonDelivery: null },
+      [],
+      "Paid for an order that never arrived",
+    );
+    if (!("Ok" in result)) {
+      throw new Error(`report_identity failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  const identity = async () => {
+    const result = await actor.get_identity(targetId);
+    if (!("Ok" in result)) {
+      throw new Error(`get_identity failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  it("should reject self-reports and duplicate open reports", async () => {
+    actor.setIdentity(target);
+    expect(
+      await actor.report_identity(targetId, { Fraud: null }, [], "Me"),
+    ).toEqual({
+      Err: { InvalidInput: "Cannot report your own identity" },
+    });
+
+    await fileReport();
+    actor.setIdentity(reporter);
+    expect(
+      await actor.report_identity(targetId, { Fraud: null }, [], "Again"),
+    ).toEqual({
+      Err: {
+        InvalidInput: "You already have an open report against this identity",
+      },
+    });
+  });
+
+  it("should only accept evidence uploaded by the reporter", async () => {
+    actor.setIdentity(target);
+    const upload = await actor.upload_file({
+      original_name: "receipt.txt",
+      mime_type: "text/plain",
+      data: new TextEncoder().encode("receipt"),
+      asset_id: [],
+      identity_id: [],
+      tags: [],
+    });
+    if (!("Ok" in upload)) {
+      throw new Error(`upload failed: ${JSON.stringify(upload)}`);
+    }
+
+    actor.setIdentity(reporter);
+    expect(
+      await actor.report_identity(
+        targetId,
+        { Fraud: null },
+        [upload.Ok.file_id],
+        "See attached",
+      ),
+    ).toEqual({
+      Err: { InvalidInput: `Evidence file ${upload.Ok.file_id} not found` },
+    });
+  });
+
+  it("should queue reports for reviewers only", async () => {
+    const reportId = await fileReport();
+
+    actor.setIdentity(reporter);
+    expect(await actor.get_report_queue()).toEqual({
+      Err: { Unauthorized: null },
+    });
+
+    actor.setIdentity(admin);
+    const queue = await actor.get_report_queue();
+    if (!("Ok" in queue)) {
+      throw new Error(`get_report_queue failed: ${JSON.stringify(queue)}`);
+    }
+    expect(queue.Ok.map((report) => report.id)).toEqual([reportId]);
+    expect(queue.Ok[0].status).toEqual({ Open: null });
+  });
+
+  it("should penalise reputation when a report is upheld", async () => {
+    const reportId = await fileReport();
+
+    actor.setIdentity(admin);
+    const resolved = await actor.resolve_report(
+      reportId,
+      { Upheld: null },
+      "Seller confirmed non-delivery",
+    );
+    expect(resolved).toHaveProperty("Ok");
+    expect((await identity()).reputation_score).toBeCloseTo(30);
+    expect(await actor.get_report_queue()).toEqual({ Ok: [] });
+  });
+
+  it("should not penalise dismissed reports", async () => {
+    const reportId = await fileReport();
+
+    actor.setIdentity(admin);
+    await actor.resolve_report(reportId, { Dismissed: null }, "No evidence");
+    expect((await identity()).reputation_score).toBe(50);
+
+    actor.setIdentity(target);
+    expect(await actor.appeal_report(reportId, "Unfair")).toEqual({
+      Err: { InvalidInput: "Report cannot be appealed" },
+    });
+  });
+
+  it("should lift a suspension when an appeal is granted", async () => {
+    const reportId = await fileReport();
+
+    actor.setIdentity(admin);
+    await actor.resolve_report(
+      reportId,
+      { Suspended: null },
+      "Repeated non-delivery",
+    );
+    expect((await identity()).verification_status).toEqual({
+      Suspended: null,
+    });
+
+    actor.setIdentity(target);
+    const notices = await actor.get_reports_against(targetId);
+    if (!("Ok" in notices)) {
+      throw new Error(`get_reports_against failed: ${JSON.stringify(notices)}`);
+    }
+    expect(notices.Ok.map((notice) => notice.status)).toEqual([
+      { Suspended: null },
+    ]);
+    expect(
+      await actor.appeal_report(reportId, "Tracking shows delivery"),
+    ).toEqual({ Ok: null });
+    expect(await actor.appeal_report(reportId, "Again")).toEqual({
+      Err: { InvalidInput: "Report cannot be appealed" },
+    });
+
+    // The reviewer who suspended the identity cannot decide the appeal
+    actor.setIdentity(admin);
+    expect(
+      await actor.resolve_appeal(reportId, true, "Delivery confirmed"),
+    ).toEqual({ Err: { Unauthorized: null } });
+
+    actor.setIdentity(appealReviewer);
+    const decided = await actor.resolve_appeal(
+      reportId,
+      true,
+      "Delivery confirmed",
+    );
+    if (!("Ok" in decided)) {
+      throw new Error(`resolve_appeal failed: ${JSON.stringify(decided)}`);
+    }
+    expect(decided.Ok.status).toEqual({ Overturned: null });
+
+    const restored = await identity();
+    expect(restored.verification_status).toEqual({ Pending: null });
+    expect(restored.reputation_score).toBe(50);
+  });
+
+  it("should close the appeal window after 30 days", async () => {
+    const reportId = await fileReport();
+
+    actor.setIdentity(admin);
+    await actor.resolve_report(reportId, { Upheld: null }, "Confirmed");
+
+    await pic.advanceTime(31 * DAY_MS);
+    await pic.tick();
+
+    actor.setIdentity(target);
+    expect(await actor.appeal_report(reportId, "Too late")).toEqual({
+      Err: { OperationExpired: null },
+    });
+  });
+});
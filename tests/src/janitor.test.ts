@@ -0,0 +1,118 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+import { Principal } from "@dfinity/principal";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const HOUR_MS = 60 * 60 * 1_000;
+
+describe("Storage janitor", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const metrics = async () => {
+    const result = await actor.get_janitor_metrics();
+    if (!("Ok" in result)) {
+      throw new Error(`metrics failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  it("should keep rate limit trackers that are still in use", async () => {
+    await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+
+    await pic.advanceTime(10 * 60 * 1_000);
+    await pic.tick(2);
+
+    const current = await metrics();
+    expect(current.runs).toBeGreaterThan(0n);
+    expect(current.rate_limit_trackers_removed).toEqual(0n);
+  });
+
+  it("should prune idle rate limit trackers", async () => {
+    await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+
+    await pic.advanceTime(3 * HOUR_MS);
+    await pic.tick(2);
+
+    // The per-operation tracker and the combined tracker
+    expect((await metrics()).rate_limit_trackers_removed).toEqual(2n);
+  });
+
+  it("should prune violation records once they decay", async () => {
+    await actor.set_rate_limit_override(
+      Principal.anonymous(),
+      [{ CreateIdentity: null }],
+      [0],
+    );
+    await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    expect((await actor.get_rate_limit_status()).recent_violations).toEqual(1);
+
+    await pic.advanceTime(3 * HOUR_MS);
+    await pic.tick(2);
+    expect((await metrics()).violation_records_removed).toEqual(0n);
+
+    await pic.advanceTime(25 * HOUR_MS);
+    await pic.tick(2);
+    expect((await metrics()).violation_records_removed).toEqual(1n);
+  });
+
+  it("should prune expired multi-sig operations", async () => {
+    const created = await actor.emergency_pause();
+    expect("Ok" in created).toBe(true);
+
+    await pic.advanceTime(25 * HOUR_MS);
+    await pic.tick(2);
+
+    expect((await metrics()).multisig_operations_removed).toEqual(1n);
+
+    const signed = await actor.sign_multi_sig_operation(
+      "Ok" in created ? created.Ok : "",
+    );
+    expect(signed).toEqual({
+      Err: { NotFound: "Multi-sig operation not found" },
+    });
+  });
+});
@@ -0,0 +1,160 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+import { Principal } from "@dfinity/principal";
+
+import {
+  type _SERVICE,
+  type JurisdictionPolicy,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const ETH_ADDRESS = "0x52908400098527886E0F7030069857D2E4169EE7";
+
+const policy = (
+  jurisdiction: string,
+  rules: Partial<JurisdictionPolicy> = {},
+): JurisdictionPolicy => ({
+  jurisdiction,
+  min_kyc_for_asset_linking: [],
+  max_bridge_amounts: [],
+  blocked_chains: [],
+  real_estate_required_credentials: [],
+  ...rules,
+});
+
+describe("Jurisdiction policy", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  it("should allow everything when no policy is defined", async () => {
+    expect(await actor.link_asset(identityId, "asset-1", "vehicle")).toEqual({
+      Ok: null,
+    });
+  });
+
+  it("should name the failed rule when KYC is too low", async () => {
+    await actor.set_jurisdiction_policy(
+      policy("global", { min_kyc_for_asset_linking: [{ Basic: null }] }),
+    );
+
+    expect(await actor.link_asset(identityId, "asset-1", "vehicle")).toEqual({
+      Err: {
+        PolicyViolation: {
+          jurisdiction: "global",
+          rule: { MinKycForAssetLinking: null },
+          message: "Linking assets requires KYC level Basic",
+        },
+      },
+    });
+  });
+
+  it("should apply the identity's own jurisdiction over the global one", async () => {
+    await actor.set_jurisdiction_policy(
+      policy("global", { blocked_chains: [{ Ethereum: null }] }),
+    );
+    await actor.set_jurisdiction_policy(policy("EU"));
+
+    const blocked = await actor.link_wallet(
+      identityId,
+      { Ethereum: null },
+      ETH_ADDRESS,
+    );
+    expect(blocked).toMatchObject({
+      Err: { PolicyViolation: { rule: { BlockedChain: null } } },
+    });
+
+    expect(await actor.set_identity_jurisdiction(identityId, "EU")).toEqual({
+      Ok: null,
+    });
+    expect(
+      await actor.link_wallet(identityId, { Ethereum: null }, ETH_ADDRESS),
+    ).toEqual({ Ok: null });
+  });
+
+  it("should require credentials for real estate listings", async () => {
+    await actor.set_jurisdiction_policy(
+      policy("global", {
+        real_estate_required_credentials: [{ Government: null }],
+      }),
+    );
+
+    expect(
+      await actor.check_jurisdiction_policy(identityId, {
+        ListRealEstate: { seller: Principal.anonymous() },
+      }),
+    ).toMatchObject({
+      Err: { PolicyViolation: { rule: { RealEstateCredentials: null } } },
+    });
+    expect(
+      await actor.link_asset(identityId, "asset-2", "real_estate"),
+    ).toMatchObject({
+      Err: { PolicyViolation: { rule: { RealEstateCredentials: null } } },
+    });
+    expect(
+      await actor.check_jurisdiction_policy(identityId, {
+        LinkAsset: { asset_type: [] },
+      }),
+    ).toEqual({ Ok: null });
+  });
+
+  it("should only check real estate listings for the identity's owner", async () => {
+    expect(
+      await actor.check_jurisdiction_policy(identityId, {
+        ListRealEstate: { seller: Principal.fromText("aaaaa-aa") },
+      }),
+    ).toEqual({ Err: { Unauthorized: null } });
+  });
+
+  it("should audit blocked operations", async () => {
+    await actor.set_jurisdiction_policy(
+      policy("global", { min_kyc_for_asset_linking: [{ Basic: null }] }),
+    );
+    await actor.link_asset(identityId, "asset-1", "vehicle");
+
+    const trail = await actor.get_audit_trail(identityId, [], []);
+    if (!("Ok" in trail)) {
+      throw new Error(`audit trail failed: ${JSON.stringify(trail)}`);
+    }
+    expect(
+      trail.Ok.some((entry) => "PolicyEnforcement" in entry.operation),
+    ).toBe(true);
+  });
+});
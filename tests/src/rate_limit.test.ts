@@ -0,0 +1,132 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+import { Principal } from "@dfinity/principal";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const PRIVACY_SETTINGS = {
+  default_privacy_level: { Public: null },
+  public_credentials: [],
+  cross_chain_visibility: [],
+};
+
+describe("Rate limiting", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let caller: Principal;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+    caller = Principal.anonymous();
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const createIdentityQuota = async () => {
+    const status = await actor.get_rate_limit_status();
+    return status.quotas.find(
+      (q) => q.operation.length === 1 && "CreateIdentity" in q.operation[0],
+    )!;
+  };
+
+  it("should report the remaining quota", async () => {
+    expect(await createIdentityQuota()).toEqual({
+      operation: [{ CreateIdentity: null }],
+      limit: 5,
+      used: 0,
+      remaining: 5,
+    });
+
+    await actor.create_identity([], [], PRIVACY_SETTINGS);
+
+    const quota = await createIdentityQuota();
+    expect(quota.used).toEqual(1);
+    expect(quota.remaining).toEqual(4);
+  });
+
+  it("should apply per-principal overrides", async () => {
+    await actor.set_rate_limit_override(
+      caller,
+      [{ CreateIdentity: null }],
+      [0],
+    );
+
+    const result = await actor.create_identity([], [], PRIVACY_SETTINGS);
+    expect(result).toEqual({ Err: { RateLimitExceeded: null } });
+
+    await actor.set_rate_limit_override(caller, [{ CreateIdentity: null }], []);
+    expect((await createIdentityQuota()).limit).toEqual(5);
+  });
+
+  it("should ban repeat violators and let an admin lift the ban", async () => {
+    await actor.set_rate_limit_thresholds(
+      60,
+      2,
+      60_000_000_000n,
+      3_600_000_000_000n,
+    );
+    await actor.set_rate_limit_override(
+      caller,
+      [{ CreateIdentity: null }],
+      [0],
+    );
+
+    await actor.create_identity([], [], PRIVACY_SETTINGS);
+    expect((await actor.get_rate_limit_status()).banned_until).toEqual([]);
+
+    await actor.create_identity([], [], PRIVACY_SETTINGS);
+    const status = await actor.get_rate_limit_status();
+    expect(status.recent_violations).toEqual(2);
+    expect(status.banned_until).toHaveLength(1);
+
+    // Banned callers are rejected even for operations under their limit
+    const banned = await actor.link_wallet(
+      "missing",
+      { Ethereum: null },
+      "0x52908400098527886e0f7030069857d2e4169ee7",
+    );
+    expect(banned).toEqual({ Err: { RateLimitExceeded: null } });
+
+    await actor.clear_rate_limit_ban(caller);
+    const cleared = await actor.get_rate_limit_status();
+    expect(cleared.banned_until).toEqual([]);
+    expect(cleared.recent_violations).toEqual(0);
+  });
+
+  it("should exempt allow-listed principals", async () => {
+    await actor.set_rate_limit_override(
+      caller,
+      [{ CreateIdentity: null }],
+      [0],
+    );
+    await actor.set_rate_limit_allow_listed(caller, true);
+
+    const result = await actor.create_identity([], [], PRIVACY_SETTINGS);
+    expect(result).not.toEqual({ Err: { RateLimitExceeded: null } });
+    expect((await actor.get_rate_limit_status()).allow_listed).toBe(true);
+  });
+});
@@ -0,0 +1,143 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const DAY_MS = 24 * 60 * 60 * 1_000;
+
+describe("Reputation", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+  const lending = createIdentity();
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+
+    await actor.set_reputation_submitter(lending.getPrincipal(), true);
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const contribution = async (source: string) => {
+    const result = await actor.get_reputation_explanation(identityId);
+    if (!("Ok" in result)) {
+      throw new Error(`explanation failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok.sources.find((entry) => source in entry.source)
+      ?.contribution;
+  };
+
+  it("should only accept events from trusted submitters", async () => {
+    actor.setIdentity(createIdentity());
+    expect(
+      await actor.submit_reputation_event(
+        identityId,
+        { LoanRepaid: null },
+        "Loan repaid",
+        ["loan-1"],
+      ),
+    ).toEqual({ Err: { Unauthorized: null } });
+
+    actor.setIdentity(lending);
+    expect(
+      await actor.submit_reputation_event(
+        identityId,
+        { LoanRepaid: null },
+        "Loan repaid",
+        ["loan-1"],
+      ),
+    ).toHaveProperty("Ok");
+    expect(await contribution("LoanRepaid")).toBeCloseTo(4);
+  });
+
+  it("should count each reference once", async () => {
+    actor.setIdentity(lending);
+    await actor.submit_reputation_event(
+      identityId,
+      { LoanRepaid: null },
+      "Loan repaid",
+      ["loan-1"],
+    );
+    expect(
+      await actor.submit_reputation_event(
+        identityId,
+        { LoanRepaid: null },
+        "Loan repaid",
+        ["loan-1"],
+      ),
+    ).toEqual({ Err: { AlreadyRecorded: null } });
+  });
+
+  it("should cap each source and decay it over time", async () => {
+    actor.setIdentity(lending);
+    for (const loan of ["loan-1", "loan-2", "loan-3", "loan-4"]) {
+      await actor.submit_reputation_event(
+        identityId,
+        { LoanDefaulted: null },
+        "Loan defaulted",
+        [loan],
+      );
+    }
+    expect(await contribution("LoanDefaulted")).toBe(-45);
+
+    await pic.advanceTime(730 * DAY_MS);
+    await pic.tick();
+    expect(await contribution("LoanDefaulted")).toBeCloseTo(-30, 0);
+  });
+
+  it("should record typed events in the history", async () => {
+    actor.setIdentity(lending);
+    await actor.submit_reputation_event(
+      identityId,
+      { MarketplaceTrade: null },
+      "Trade settled",
+      ["trade-9"],
+    );
+
+    actor.setIdentity(createIdentity());
+    const identity = await actor.get_identity(identityId);
+    if (!("Ok" in identity)) {
+      throw new Error(`get_identity failed: ${JSON.stringify(identity)}`);
+    }
+    const event = identity.Ok.reputation_history.find(
+      (entry) => entry.reason === "Trade settled",
+    );
+    expect(event?.event_type).toEqual({ SuccessfulTransaction: null });
+    expect(event?.source).toEqual([{ MarketplaceTrade: null }]);
+  });
+});
@@ -0,0 +1,118 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const ETH_ADDRESS = "0x52908400098527886E0F7030069857D2E4169EE7";
+
+describe("Risk assessment", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const assessment = async () => {
+    const result = await actor.get_risk_assessment(identityId);
+    if (!("Ok" in result)) {
+      throw new Error(`get_risk_assessment failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  it("should score a new identity from its data", async () => {
+    const initial = await assessment();
+    expect(initial.assessment_model_version).toBe("v2.0.0");
+    expect(initial.fraud_risk).toBe(0);
+    expect(initial.compliance_risk).toBeCloseTo(0.73);
+    expect(initial.overall_risk_score).toBeCloseTo(0.7725);
+    expect(initial.risk_factors.map((factor) => factor.factor_type)).toEqual([
+      "fraud.asset_verification",
+      "fraud.wallet_verification",
+      "fraud.rate_limit_violations",
+      "compliance.kyc_level",
+      "compliance.aml_status",
+      "compliance.sanctions_status",
+      "operational.credentials",
+      "operational.account_age",
+    ]);
+  });
+
+  it("should reassess when an unverified wallet is linked", async () => {
+    await actor.link_wallet(identityId, { Ethereum: null }, ETH_ADDRESS);
+
+    const updated = await assessment();
+    const wallets = updated.risk_factors.find(
+      (factor) => factor.factor_type === "fraud.wallet_verification",
+    );
+    expect(wallets?.score).toBe(1);
+    expect(wallets?.mitigation_suggestions.length).toBeGreaterThan(0);
+    expect(updated.fraud_risk).toBeCloseTo(0.25);
+  });
+
+  it("should reassess when compliance checks complete", async () => {
+    const before = await assessment();
+    await actor.record_sanctions_check(identityId, { Cleared: null }, []);
+
+    const after = await assessment();
+    expect(after.compliance_risk).toBeLessThan(before.compliance_risk);
+  });
+
+  it("should lower account age risk on refresh", async () => {
+    const before = await assessment();
+    await pic.advanceTime(30 * 24 * 60 * 60 * 1_000);
+    await pic.tick();
+
+    const refreshed = await actor.refresh_risk_assessment(identityId);
+    if (!("Ok" in refreshed)) {
+      throw new Error(`refresh failed: ${JSON.stringify(refreshed)}`);
+    }
+    expect(refreshed.Ok.operational_risk).toBeLessThan(
+      before.operational_risk,
+    );
+
+    actor.setIdentity(createIdentity());
+    expect(await actor.refresh_risk_assessment(identityId)).toEqual({
+      Err: { Unauthorized: null },
+    });
+    expect((await assessment()).risk_factors).toEqual([]);
+  });
+});
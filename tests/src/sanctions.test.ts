@@ -0,0 +1,105 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const SANCTIONED_ETH = "0x8589427373D6D84E98730D7795D8f6f8731FDA16";
+
+describe("Sanctions screening", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+
+    await actor.add_sanctioned_addresses("OFAC SDN", [
+      { chain_type: { Ethereum: null }, address: SANCTIONED_ETH },
+    ]);
+    await actor.add_sanctioned_names("OFAC SDN", ["Hans-Peter Müller"]);
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  it("should block linking a sanctioned wallet and flag the identity", async () => {
+    const result = await actor.link_wallet(
+      identityId,
+      { Ethereum: null },
+      SANCTIONED_ETH.toLowerCase(),
+    );
+    expect(result).toEqual({
+      Err: { VerificationFailed: "Address appears on sanctions list OFAC SDN" },
+    });
+
+    const status = await actor.get_compliance_status(identityId);
+    if (!("Ok" in status)) {
+      throw new Error(`status failed: ${JSON.stringify(status)}`);
+    }
+    expect(status.Ok.sanctions_check).toEqual({ Sanctioned: null });
+    expect(status.Ok.aml_status).toEqual({ Blocked: null });
+
+    const trail = await actor.get_audit_trail(identityId, [], []);
+    if (!("Ok" in trail)) {
+      throw new Error(`audit trail failed: ${JSON.stringify(trail)}`);
+    }
+    expect(trail.Ok[0].operation).toEqual({ SanctionsScreening: null });
+  });
+
+  it("should match names regardless of order, case and diacritics", async () => {
+    const exact = await actor.screen_name("MULLER, Hans Peter");
+    if (!("Ok" in exact)) {
+      throw new Error(`screen failed: ${JSON.stringify(exact)}`);
+    }
+    expect(exact.Ok[0].score).toEqual(100);
+
+    const close = await actor.screen_name("Hans Petra Muller");
+    expect("Ok" in close && close.Ok.length).toEqual(1);
+
+    expect(await actor.screen_name("John Smith")).toEqual({ Ok: [] });
+  });
+
+  it("should honour the configured name threshold", async () => {
+    await actor.set_sanctions_name_threshold(95);
+    expect(await actor.screen_name("Hans Petra Muller")).toEqual({ Ok: [] });
+  });
+
+  it("should report list sizes", async () => {
+    expect(await actor.get_sanctions_list_stats()).toEqual({
+      Ok: { addresses: 1n, names: 1n, name_match_threshold: 85 },
+    });
+  });
+});
@@ -0,0 +1,122 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+// Reputation reported by the marketplace and lending canisters. Test
+// identities stand in for the canister principals.
+describe("Transaction outcomes", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let identityId: string;
+  const owner = createIdentity();
+  const marketplace = createIdentity();
+  const lending = createIdentity();
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+    });
+
+    actor = fixture.actor;
+    await actor.set_outcome_reporters(
+      [marketplace.getPrincipal()],
+      [lending.getPrincipal()],
+    );
+
+    actor.setIdentity(owner);
+    const identity = await actor.create_identity([], [], {
+      default_privacy_level: { Public: null },
+      public_credentials: [],
+      cross_chain_visibility: [],
+    });
+    if (!("Ok" in identity)) {
+      throw new Error(`create_identity failed: ${JSON.stringify(identity)}`);
+    }
+    identityId = identity.Ok;
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const sources = async () => {
+    const result = await actor.get_reputation_explanation(identityId);
+    if (!("Ok" in result)) {
+      throw new Error(`explanation failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok.sources;
+  };
+
+  it("should credit both parties of a completed order", async () => {
+    actor.setIdentity(marketplace);
+    const result = await actor.report_transaction_outcome({
+      OrderCompleted: {
+        order_id: 7n,
+        buyer: owner.getPrincipal(),
+        seller: createIdentity().getPrincipal(),
+      },
+    });
+    expect(result).toEqual({ Ok: 1 });
+
+    const trade = (await sources()).find(
+      (entry) => "MarketplaceTrade" in entry.source,
+    );
+    expect(trade?.event_count).toBe(1);
+  });
+
+  it("should only accept outcomes from the matching canister", async () => {
+    actor.setIdentity(marketplace);
+    expect(
+      await actor.report_transaction_outcome({
+        LoanRepaid: { loan_id: 1n, borrower: owner.getPrincipal() },
+      }),
+    ).toEqual({
+      Err: "Caller is not the reporting canister for this outcome",
+    });
+
+    actor.setIdentity(lending);
+    expect(
+      await actor.report_transaction_outcome({
+        LoanRepaid: { loan_id: 1n, borrower: owner.getPrincipal() },
+      }),
+    ).toEqual({ Ok: 1 });
+  });
+
+  it("should accept retried outcomes without counting them twice", async () => {
+    actor.setIdentity(lending);
+    const outcome = {
+      LoanDefaulted: { loan_id: 3n, borrower: owner.getPrincipal() },
+    };
+    expect(await actor.report_transaction_outcome(outcome)).toEqual({ Ok: 1 });
+    expect(await actor.report_transaction_outcome(outcome)).toEqual({ Ok: 0 });
+    expect(
+      await actor.report_transaction_outcome({
+        LoanLiquidated: { loan_id: 3n, borrower: owner.getPrincipal() },
+      }),
+    ).toEqual({ Ok: 0 });
+
+    const defaulted = (await sources()).find(
+      (entry) => "LoanDefaulted" in entry.source,
+    );
+    expect(defaulted?.event_count).toBe(1);
+  });
+});
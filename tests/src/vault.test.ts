@@ -0,0 +1,128 @@
+import { describe, beforeEach, afterEach, it, expect, inject } from "vitest";
+import { resolve, dirname } from "path";
+import { fileURLToPath } from "url";
+import { PocketIc, createIdentity, type Actor } from "@dfinity/pic";
+import { Principal } from "@dfinity/principal";
+
+import {
+  type _SERVICE,
+  idlFactory,
+} from "../../src/declarations/backend/backend.did.js";
+
+const WASM_PATH = resolve(
+  dirname(fileURLToPath(import.meta.url)),
+  "..",
+  "..",
+  "target",
+  "wasm32-unknown-unknown",
+  "release",
+  "backend.wasm",
+);
+
+const TRANSPORT_KEY = new Uint8Array(48).fill(7);
+const KEY_NONCE = new Uint8Array(16).fill(1);
+
+describe("Encrypted document vault", () => {
+  let pic: PocketIc;
+  let actor: Actor<_SERVICE>;
+  let canisterId: Principal;
+  const admin = createIdentity();
+  const owner = createIdentity();
+  const grantee = createIdentity();
+
+  beforeEach(async () => {
+    pic = await PocketIc.create(inject("PIC_URL"));
+
+    const fixture = await pic.setupCanister<_SERVICE>({
+      idlFactory,
+      wasm: WASM_PATH,
+      sender: admin.getPrincipal(),
+    });
+
+    actor = fixture.actor;
+    canisterId = fixture.canisterId;
+    actor.setIdentity(admin);
+    await actor.set_vetkd_config({ key_name: "key_a", mode: { Mock: null } });
+  });
+
+  afterEach(async () => {
+    await pic.tearDown();
+  });
+
+  const uploadDocument = async () => {
+    actor.setIdentity(owner);
+    const result = await actor.upload_encrypted_file({
+      original_name: "passport.pdf",
+      mime_type: "application/pdf",
+      ciphertext: new Uint8Array([1, 2, 3, 4]),
+      plaintext_size: 4n,
+      algorithm: "AES-256-GCM",
+      iv: new Uint8Array(12).fill(9),
+      key_scope: { File: { key_nonce: KEY_NONCE } },
+      asset_id: [],
+      identity_id: [],
+      tags: [],
+    });
+    if (!("Ok" in result)) {
+      throw new Error(`upload failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok.file_id;
+  };
+
+  const decryptionKey = async (fileId: string) => {
+    const result = await actor.get_file_decryption_key(fileId, TRANSPORT_KEY);
+    if (!("Ok" in result)) {
+      throw new Error(`get_file_decryption_key failed: ${JSON.stringify(result)}`);
+    }
+    return result.Ok;
+  };
+
+  it("should return the key the owner encrypted with", async () => {
+    const fileId = await uploadDocument();
+
+    const derived = await actor.derive_vault_key(
+      { File: { key_nonce: KEY_NONCE } },
+      TRANSPORT_KEY,
+    );
+    expect(derived).toEqual({ Ok: await decryptionKey(fileId) });
+  });
+
+  it("should only hand keys to the owner and grantees", async () => {
+    const fileId = await uploadDocument();
+    const ownerKey = await decryptionKey(fileId);
+
+    actor.setIdentity(grantee);
+    expect(
+      await actor.get_file_decryption_key(fileId, TRANSPORT_KEY),
+    ).toEqual({ Err: "Access denied" });
+
+    actor.setIdentity(owner);
+    await actor.share_encrypted_file(fileId, grantee.getPrincipal());
+
+    actor.setIdentity(grantee);
+    expect(await decryptionKey(fileId)).toEqual(ownerKey);
+  });
+
+  it("should derive from the file's key after the key is rotated", async () => {
+    const fileId = await uploadDocument();
+    const before = await decryptionKey(fileId);
+
+    actor.setIdentity(admin);
+    await actor.set_vetkd_config({ key_name: "key_b", mode: { Mock: null } });
+
+    actor.setIdentity(owner);
+    expect(await decryptionKey(fileId)).toEqual(before);
+  });
+
+  it("should keep the vetKD configuration across upgrades", async () => {
+    const before = await actor.get_vault_public_key();
+
+    await pic.upgradeCanister({
+      canisterId,
+      wasm: WASM_PATH,
+      sender: admin.getPrincipal(),
+    });
+
+    expect(await actor.get_vault_public_key()).toEqual(before);
+  });
+});